@@ -0,0 +1,160 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi;
+use crate::{Error, Guid, Result};
+use std::path::Path;
+
+/// Where [`advertise_product()`] advertises a product.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AdvertiseTarget<'a> {
+    /// Generate an advertise script at the given path instead of advertising the product on this machine.
+    Script(&'a Path),
+    /// Advertise the product per-machine.
+    Machine,
+    /// Advertise the product for the current user.
+    User,
+}
+
+flags! {
+    /// The platform for which an advertise script is generated by [`advertise_product()`].
+    ///
+    /// Pass [`AdvertisePlatform::empty()`] to use the platform of the current machine.
+    pub struct AdvertisePlatform: u32 {
+        /// 32-bit x86.
+        const X86 = 1 << 0;
+        /// Itanium.
+        const IA64 = 1 << 1;
+        /// x64.
+        const AMD64 = 1 << 2;
+        /// ARM64.
+        const ARM64 = 1 << 3;
+    }
+}
+
+flags! {
+    /// Options passed to [`advertise_product()`].
+    pub struct AdvertiseOptions: u32 {
+        /// Advertise a new instance of the product using a transform that changes its product code.
+        const INSTANCE = 1 << 0;
+    }
+}
+
+/// Advertises a product per-machine or for the current user, or generates an advertise script.
+///
+/// Pass semicolon-delimited `transforms` to apply, and a `language` of 0 to use the package's default language.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{advertise_product, AdvertiseOptions, AdvertisePlatform, AdvertiseTarget};
+///
+/// advertise_product(
+///     "example.msi",
+///     AdvertiseTarget::Script("example.aas".as_ref()),
+///     None,
+///     0,
+///     AdvertisePlatform::AMD64,
+///     AdvertiseOptions::empty(),
+/// )?;
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[track_caller]
+pub fn advertise_product(
+    package: impl AsRef<Path>,
+    target: AdvertiseTarget<'_>,
+    transforms: Option<&str>,
+    language: u16,
+    platform: AdvertisePlatform,
+    options: AdvertiseOptions,
+) -> Result<()> {
+    unsafe {
+        let package = ffi::to_wide_os(package.as_ref().as_os_str())?;
+        let script = match target {
+            AdvertiseTarget::Script(path) => Some(ffi::to_wide_os(path.as_os_str())?),
+            _ => None,
+        };
+        let transforms = match transforms {
+            Some(transforms) => Some(ffi::to_wide(transforms)?),
+            None => None,
+        };
+
+        // ADVERTISEFLAGS_MACHINEASSIGN and ADVERTISEFLAGS_USERASSIGN are passed instead of a script path.
+        let script = match (&script, target) {
+            (Some(script), _) => script.as_ptr(),
+            (None, AdvertiseTarget::User) => 1 as ffi::LPCWSTR,
+            (None, _) => std::ptr::null(),
+        };
+
+        let ret = ffi::MsiAdvertiseProductEx(
+            package.as_ptr(),
+            script,
+            transforms
+                .as_ref()
+                .map_or(std::ptr::null(), |transforms| transforms.as_ptr()),
+            language,
+            platform.bits(),
+            options.bits(),
+        );
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+
+        Ok(())
+    }
+}
+
+/// Information about the product in an advertise script, returned from [`advertise_script_info()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ScriptInfo {
+    /// The product code GUID.
+    pub product_code: Guid,
+    /// The product language.
+    pub language: u16,
+    /// The product version packed as `major << 24 | minor << 16 | build`.
+    pub version: u32,
+    /// The product name.
+    pub name: String,
+    /// The name of the package.
+    pub package_name: String,
+}
+
+/// Gets information about the product in an advertise script e.g., generated by [`advertise_product()`].
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::advertise_script_info;
+///
+/// let info = advertise_script_info("example.aas")?;
+/// println!("{} ({})", info.name, info.product_code);
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub fn advertise_script_info(path: impl AsRef<Path>) -> Result<ScriptInfo> {
+    let path = ffi::to_wide_os(path.as_ref().as_os_str())?;
+    let mut product_code = [0u16; ffi::GUID_LEN];
+    let mut language = 0u16;
+    let mut version = 0u32;
+    let [name, package_name] = ffi::get_strings(
+        |[(name, name_len), (package_name, package_name_len)]| unsafe {
+            ffi::MsiGetProductInfoFromScript(
+                path.as_ptr(),
+                product_code.as_mut_ptr(),
+                &mut language,
+                &mut version,
+                name,
+                name_len,
+                package_name,
+                package_name_len,
+            )
+        },
+    )?;
+
+    Ok(ScriptInfo {
+        product_code: Guid::from_terminated(&product_code)?,
+        language,
+        version,
+        name,
+        package_name,
+    })
+}
@@ -0,0 +1,316 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Attributes stored in the `Attributes` column of standard tables.
+
+use crate::InstallFeatureAttributes;
+
+flags! {
+    /// The `Attributes` column of the `Feature` table.
+    pub struct FeatureAttributes: u32 {
+        /// Install the feature locally by default. This is the default when no other source attribute is set.
+        const FAVOR_LOCAL = 0;
+        /// Install the feature to run from source by default.
+        const FAVOR_SOURCE = 1 << 0;
+        /// Install the feature in the same state as its parent.
+        const FOLLOW_PARENT = 1 << 1;
+        /// Advertise the feature by default.
+        const FAVOR_ADVERTISE = 1 << 2;
+        /// The feature cannot be advertised.
+        const DISALLOW_ADVERTISE = 1 << 3;
+        /// The feature cannot be made absent in the selection tree.
+        const UI_DISALLOW_ABSENT = 1 << 4;
+        /// The feature is not advertised on platforms that do not support advertising.
+        const NO_UNSUPPORTED_ADVERTISE = 1 << 5;
+    }
+}
+
+flags! {
+    /// The `Attributes` column of the `Component` table.
+    pub struct ComponentAttributes: u32 {
+        /// Install the component locally. This is the default when no other source attribute is set.
+        const LOCAL_ONLY = 0;
+        /// Run the component from source only.
+        const SOURCE_ONLY = 1 << 0;
+        /// Install the component locally or run it from source.
+        const OPTIONAL = 1 << 1;
+        /// The `KeyPath` column is a key in the `Registry` table.
+        const REGISTRY_KEY_PATH = 1 << 2;
+        /// Increment the shared DLL reference count of the key file.
+        const SHARED_DLL_REF_COUNT = 1 << 3;
+        /// Never uninstall the component.
+        const PERMANENT = 1 << 4;
+        /// The `KeyPath` column is a key in the `ODBCDataSource` table.
+        const ODBC_DATA_SOURCE = 1 << 5;
+        /// Reevaluate the component condition during a reinstall.
+        const TRANSITIVE = 1 << 6;
+        /// Do not install or reinstall the component if the key file or registry value already exists.
+        const NEVER_OVERWRITE = 1 << 7;
+        /// The component is 64-bit.
+        const WIN64 = 1 << 8;
+        /// Disable registry reflection on all keys written by the component.
+        const DISABLE_REGISTRY_REFLECTION = 1 << 9;
+        /// Uninstall the component when the patch that installed it is superseded.
+        const UNINSTALL_ON_SUPERSEDENCE = 1 << 10;
+        /// Share the component with other products if patched to the same version.
+        const SHARED = 1 << 11;
+    }
+}
+
+flags! {
+    /// The `Attributes` column of the `File` table.
+    pub struct FileAttributes: u32 {
+        /// The file is read-only.
+        const READ_ONLY = 1 << 0;
+        /// The file is hidden.
+        const HIDDEN = 1 << 1;
+        /// The file is a system file.
+        const SYSTEM = 1 << 2;
+        /// The installation fails if the file cannot be installed.
+        const VITAL = 1 << 9;
+        /// The file contains a valid checksum, which is verified during repair.
+        const CHECKSUM = 1 << 10;
+        /// The file was added by a patch.
+        const PATCH_ADDED = 1 << 12;
+        /// The file is not compressed regardless of the `Word Count` summary property.
+        const NONCOMPRESSED = 1 << 13;
+        /// The file is compressed regardless of the `Word Count` summary property.
+        const COMPRESSED = 1 << 14;
+    }
+}
+
+impl FileAttributes {
+    /// Gets whether the attributes are valid, which is not the case if both
+    /// [`FileAttributes::COMPRESSED`] and [`FileAttributes::NONCOMPRESSED`] are set.
+    pub const fn is_valid(&self) -> bool {
+        !self.contains(Self(Self::COMPRESSED.0 | Self::NONCOMPRESSED.0))
+    }
+
+    /// Gets whether the file is compressed, given whether files are compressed by default
+    /// according to the `Word Count` summary property.
+    pub const fn is_compressed(&self, default: bool) -> bool {
+        match (
+            self.contains(Self::COMPRESSED),
+            self.contains(Self::NONCOMPRESSED),
+        ) {
+            (true, false) => true,
+            (false, true) => false,
+            _ => default,
+        }
+    }
+}
+
+flags! {
+    /// The `Attributes` column of the `Upgrade` table.
+    pub struct UpgradeAttributes: u32 {
+        /// Migrate the feature states of related products to the new product.
+        const MIGRATE_FEATURES = 1 << 0;
+        /// Only detect related products and do not remove them.
+        const ONLY_DETECT = 1 << 1;
+        /// Continue the installation if removing a related product fails.
+        const IGNORE_REMOVE_FAILURE = 1 << 2;
+        /// Include the `VersionMin` in the range of detected versions.
+        const VERSION_MIN_INCLUSIVE = 1 << 8;
+        /// Include the `VersionMax` in the range of detected versions.
+        const VERSION_MAX_INCLUSIVE = 1 << 9;
+        /// Detect all languages except those in the `Language` column.
+        const LANGUAGES_EXCLUSIVE = 1 << 10;
+    }
+}
+
+flags! {
+    /// The `Attributes` column of the `Dialog` table.
+    pub struct DialogAttributes: u32 {
+        /// The dialog is visible.
+        const VISIBLE = 1 << 0;
+        /// The dialog is modal.
+        const MODAL = 1 << 1;
+        /// The dialog can be minimized.
+        const MINIMIZE = 1 << 2;
+        /// The dialog is system modal.
+        const SYS_MODAL = 1 << 3;
+        /// Other modeless dialogs are not destroyed when the dialog is created.
+        const KEEP_MODELESS = 1 << 4;
+        /// The dialog periodically checks available disk space.
+        const TRACK_DISK_SPACE = 1 << 5;
+        /// Use the palette of the first control that has one.
+        const USE_CUSTOM_PALETTE = 1 << 6;
+        /// Display text in right-to-left reading order.
+        const RTL_READING_ORDER = 1 << 7;
+        /// Right-align text.
+        const RIGHT_ALIGNED = 1 << 8;
+        /// Show the scroll bar on the left.
+        const LEFT_SCROLL = 1 << 9;
+        /// The dialog is an error dialog.
+        const ERROR = 1 << 16;
+    }
+}
+
+impl DialogAttributes {
+    /// Bidirectional attributes for right-to-left languages.
+    pub const BIDI: Self =
+        Self(Self::RTL_READING_ORDER.0 | Self::RIGHT_ALIGNED.0 | Self::LEFT_SCROLL.0);
+}
+
+flags! {
+    /// The `Attributes` column of the `Control` table.
+    ///
+    /// Attributes from bit 16 depend on the type of control, so associated constants
+    /// e.g., [`ControlAttributes::MULTILINE`] and [`ControlAttributes::SORTED`] may share bits.
+    pub struct ControlAttributes: u32 {
+        /// The control is visible.
+        const VISIBLE = 1 << 0;
+        /// The control is enabled.
+        const ENABLED = 1 << 1;
+        /// The control has a sunken 3D look.
+        const SUNKEN = 1 << 2;
+        /// The `Property` column names a property that contains the name of the property to set.
+        const INDIRECT = 1 << 3;
+        /// The property the control sets is an integer.
+        const INTEGER = 1 << 4;
+        /// Display text in right-to-left reading order.
+        const RTL_READING_ORDER = 1 << 5;
+        /// Right-align text.
+        const RIGHT_ALIGNED = 1 << 6;
+        /// Show the scroll bar on the left.
+        const LEFT_SCROLL = 1 << 7;
+    }
+}
+
+impl ControlAttributes {
+    /// Bidirectional attributes for right-to-left languages.
+    pub const BIDI: Self =
+        Self(Self::RTL_READING_ORDER.0 | Self::RIGHT_ALIGNED.0 | Self::LEFT_SCROLL.0);
+
+    /// A `Text` control is transparent.
+    pub const TRANSPARENT: Self = Self(1 << 16);
+    /// A `Text` control does not treat `&` as an access key prefix.
+    pub const NO_PREFIX: Self = Self(1 << 17);
+    /// A `Text` control does not wrap text.
+    pub const NO_WRAP: Self = Self(1 << 18);
+    /// A `Text` control formats a number of bytes as a size e.g., "10 MB".
+    pub const FORMAT_SIZE: Self = Self(1 << 19);
+    /// A `Text` control uses the user's default UI code page.
+    pub const USERS_LANGUAGE: Self = Self(1 << 20);
+
+    /// An `Edit` control has multiple lines.
+    pub const MULTILINE: Self = Self(1 << 16);
+    /// An `Edit` control hides its text.
+    pub const PASSWORD_INPUT: Self = Self(1 << 21);
+
+    /// A `ProgressBar` control draws a continuous bar instead of blocks.
+    pub const PROGRESS_95: Self = Self(1 << 16);
+
+    /// A `ComboBox` or `ListBox` control sorts its items.
+    pub const SORTED: Self = Self(1 << 16);
+    /// A `ComboBox` control only allows selecting items from its list.
+    pub const COMBO_LIST: Self = Self(1 << 17);
+
+    /// A `PushButton`, `CheckBox`, or `RadioButtonGroup` control displays an image instead of text.
+    pub const IMAGE_HANDLE: Self = Self(1 << 16);
+    /// A `CheckBox` or `RadioButtonGroup` control looks like a push button.
+    pub const PUSH_LIKE: Self = Self(1 << 17);
+    /// The control displays a bitmap.
+    pub const BITMAP: Self = Self(1 << 18);
+    /// The control displays an icon.
+    pub const ICON: Self = Self(1 << 19);
+    /// An image is centered instead of stretched to fill the control.
+    pub const FIXED_SIZE: Self = Self(1 << 20);
+    /// A `PushButton` control requires elevation and shows a shield icon.
+    pub const ELEVATION_SHIELD: Self = Self(1 << 23);
+
+    /// A `RadioButtonGroup` control has a border.
+    pub const HAS_BORDER: Self = Self(1 << 24);
+}
+
+impl From<FeatureAttributes> for InstallFeatureAttributes {
+    /// Converts `Feature` table attributes to those passed to [`Session::set_feature_attributes()`](crate::Session::set_feature_attributes).
+    ///
+    /// [`FeatureAttributes::UI_DISALLOW_ABSENT`] has no equivalent and is ignored.
+    fn from(value: FeatureAttributes) -> Self {
+        let mut attributes = match value.contains(FeatureAttributes::FAVOR_SOURCE) {
+            true => InstallFeatureAttributes::FAVOR_SOURCE,
+            false => InstallFeatureAttributes::FAVOR_LOCAL,
+        };
+        for (from, to) in [
+            (
+                FeatureAttributes::FOLLOW_PARENT,
+                InstallFeatureAttributes::FOLLOW_PARENT,
+            ),
+            (
+                FeatureAttributes::FAVOR_ADVERTISE,
+                InstallFeatureAttributes::FAVOR_ADVERTISE,
+            ),
+            (
+                FeatureAttributes::DISALLOW_ADVERTISE,
+                InstallFeatureAttributes::DISALLOW_ADVERTISE,
+            ),
+            (
+                FeatureAttributes::NO_UNSUPPORTED_ADVERTISE,
+                InstallFeatureAttributes::NO_UNSUPPORTED_ADVERTISE,
+            ),
+        ] {
+            attributes.set(to, value.contains(from));
+        }
+        attributes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn component_attributes_debug() {
+        let attributes = ComponentAttributes::from_bits_retain(0x114);
+        assert!(attributes.contains(ComponentAttributes::LOCAL_ONLY));
+        assert_eq!(
+            "ComponentAttributes(REGISTRY_KEY_PATH | PERMANENT | WIN64)",
+            format!("{attributes:?}")
+        );
+    }
+
+    #[test]
+    fn file_attributes_compressed() {
+        let attributes = FileAttributes::VITAL | FileAttributes::COMPRESSED;
+        assert!(attributes.is_valid());
+        assert!(attributes.is_compressed(false));
+        assert!(!FileAttributes::NONCOMPRESSED.is_compressed(true));
+        assert!(FileAttributes::VITAL.is_compressed(true));
+
+        let attributes = FileAttributes::COMPRESSED | FileAttributes::NONCOMPRESSED;
+        assert!(!attributes.is_valid());
+    }
+
+    #[test]
+    fn control_attributes() {
+        let attributes = ControlAttributes::VISIBLE | ControlAttributes::ENABLED;
+        assert_eq!(3, attributes.bits());
+        assert_eq!(ControlAttributes::MULTILINE, ControlAttributes::SORTED);
+        assert_eq!(0xe0, ControlAttributes::BIDI.bits());
+        assert_eq!(0x380, DialogAttributes::BIDI.bits());
+        assert_eq!(
+            "DialogAttributes(VISIBLE | MODAL | ERROR)",
+            format!(
+                "{:?}",
+                DialogAttributes::VISIBLE | DialogAttributes::MODAL | DialogAttributes::ERROR
+            )
+        );
+    }
+
+    #[test]
+    fn feature_attributes_into_install() {
+        assert_eq!(
+            InstallFeatureAttributes::FAVOR_LOCAL,
+            FeatureAttributes::FAVOR_LOCAL.into()
+        );
+        assert_eq!(
+            InstallFeatureAttributes::FAVOR_SOURCE | InstallFeatureAttributes::DISALLOW_ADVERTISE,
+            InstallFeatureAttributes::from(
+                FeatureAttributes::FAVOR_SOURCE
+                    | FeatureAttributes::DISALLOW_ADVERTISE
+                    | FeatureAttributes::UI_DISALLOW_ABSENT
+            )
+        );
+    }
+}
@@ -0,0 +1,670 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi;
+use crate::{Error, Guid, InstallContext, InstallMode, InstallState, Result};
+use std::path::{Path, PathBuf};
+
+/// An installed component identified by its component code GUID.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Component {
+    component_code: Guid,
+}
+
+impl Component {
+    /// Creates a [`Component`] for the given component code GUID.
+    pub fn new(component_code: Guid) -> Self {
+        Self { component_code }
+    }
+
+    /// Gets the component code GUID.
+    pub fn component_code(&self) -> Guid {
+        self.component_code
+    }
+
+    /// Enumerates the product codes of products installed for the current user or per-machine that use this component.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use msica::Component;
+    ///
+    /// let component = Component::new("{01234567-89AB-CDEF-0123-456789ABCDEF}".parse()?);
+    /// if component.clients()?.next().is_none() {
+    ///     println!("no products use {}", component.component_code());
+    /// }
+    /// # Ok::<(), msica::Error>(())
+    /// ```
+    pub fn clients(&self) -> Result<Clients> {
+        Ok(Clients {
+            component_code: ffi::to_wide(&self.component_code)?,
+            index: 0,
+            done: false,
+        })
+    }
+
+    /// Enumerates products installed in the given contexts for a user that use this component.
+    ///
+    /// Pass `None` for the `sid` to use the current user, or `Some("S-1-1-0")` for all users.
+    pub fn clients_ex(&self, sid: Option<&str>, context: InstallContext) -> Result<ClientsEx> {
+        let sid = match sid {
+            Some(sid) => Some(ffi::to_wide(sid)?),
+            None => None,
+        };
+
+        Ok(ClientsEx {
+            component_code: ffi::to_wide(&self.component_code)?,
+            sid,
+            context,
+            index: 0,
+            done: false,
+        })
+    }
+}
+
+/// A product that uses a [`Component`], returned from [`Component::clients_ex()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComponentClient {
+    /// The product code GUID.
+    pub product_code: Guid,
+    /// The context in which the product is installed.
+    pub context: InstallContext,
+    /// The security identifier (SID) of the user for which the product is installed,
+    /// or `None` if the product is installed per-machine.
+    pub sid: Option<String>,
+}
+
+/// Enumerates the product codes of products that use a [`Component`]. Returned from [`Component::clients()`].
+pub struct Clients {
+    component_code: Vec<u16>,
+    index: u32,
+    done: bool,
+}
+
+impl Iterator for Clients {
+    type Item = Result<Guid>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut product_code = [0u16; ffi::GUID_LEN];
+        let ret = unsafe {
+            ffi::MsiEnumClients(
+                self.component_code.as_ptr(),
+                self.index,
+                product_code.as_mut_ptr(),
+            )
+        };
+        match ret {
+            ffi::ERROR_SUCCESS => {
+                self.index += 1;
+                Some(Guid::from_terminated(&product_code))
+            }
+            ffi::ERROR_NO_MORE_ITEMS => {
+                self.done = true;
+                None
+            }
+            _ => {
+                self.done = true;
+                Some(Err(Error::from_error_code(ret)))
+            }
+        }
+    }
+}
+
+/// Enumerates products in the given contexts that use a [`Component`]. Returned from [`Component::clients_ex()`].
+pub struct ClientsEx {
+    component_code: Vec<u16>,
+    sid: Option<Vec<u16>>,
+    context: InstallContext,
+    index: u32,
+    done: bool,
+}
+
+impl Iterator for ClientsEx {
+    type Item = Result<ComponentClient>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut product_code = [0u16; ffi::GUID_LEN];
+        let mut context = 0i32;
+
+        let sid = unsafe {
+            ffi::get_string(|sid, sid_len| {
+                ffi::MsiEnumClientsEx(
+                    self.component_code.as_ptr(),
+                    self.sid
+                        .as_ref()
+                        .map_or(std::ptr::null(), |sid| sid.as_ptr()),
+                    self.context.bits(),
+                    self.index,
+                    product_code.as_mut_ptr(),
+                    &mut context,
+                    sid,
+                    sid_len,
+                )
+            })
+        };
+
+        let sid = match sid {
+            Ok(sid) => sid,
+            Err(err) => {
+                self.done = true;
+                if err.is(ffi::ERROR_NO_MORE_ITEMS) {
+                    return None;
+                }
+                return Some(Err(err));
+            }
+        };
+        self.index += 1;
+
+        let client = Guid::from_terminated(&product_code).map(|product_code| ComponentClient {
+            product_code,
+            context: InstallContext::from_bits_retain(context as u32),
+            sid: (!sid.is_empty()).then_some(sid),
+        });
+        Some(client)
+    }
+}
+
+/// Gets the installed state of a component of a product installed in a single `context` for a user.
+///
+/// Pass `None` for the `sid` to use the current user, which is required for [`InstallContext::MACHINE`].
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{component_state, InstallContext, InstallState};
+///
+/// let state = component_state(
+///     "{01234567-89AB-CDEF-0123-456789ABCDEF}",
+///     "{FEDCBA98-7654-3210-FEDC-BA9876543210}",
+///     None,
+///     InstallContext::MACHINE,
+/// )?;
+/// if state != InstallState::Local {
+///     println!("component is not installed locally: {state:?}");
+/// }
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub fn component_state(
+    product_code: impl AsRef<str>,
+    component_code: impl AsRef<str>,
+    sid: Option<&str>,
+    context: InstallContext,
+) -> Result<InstallState> {
+    unsafe {
+        let product_code = ffi::to_wide(product_code.as_ref())?;
+        let component_code = ffi::to_wide(component_code.as_ref())?;
+        let sid = match sid {
+            Some(sid) => Some(ffi::to_wide(sid)?),
+            None => None,
+        };
+        let mut state = 0i32;
+
+        let ret = ffi::MsiQueryComponentState(
+            product_code.as_ptr(),
+            sid.as_ref().map_or(std::ptr::null(), |sid| sid.as_ptr()),
+            context.bits() as i32,
+            component_code.as_ptr(),
+            &mut state,
+        );
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_error_code(ret));
+        }
+
+        InstallState::try_from(state)
+    }
+}
+
+/// Gets the product code of the product that installed a component.
+///
+/// If multiple products installed the component, the product code of a product installed for the current user
+/// or per-machine is returned. A custom action DLL installed as a component can use this to find its own product.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{product_code_from_component, ProductInfo};
+///
+/// let product_code = product_code_from_component("{FEDCBA98-7654-3210-FEDC-BA9876543210}")?;
+/// println!("{}", ProductInfo::query(product_code)?.name);
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub fn product_code_from_component(component_code: impl AsRef<str>) -> Result<Guid> {
+    unsafe {
+        let component_code = ffi::to_wide(component_code.as_ref())?;
+        let mut product_code = [0u16; ffi::GUID_LEN];
+
+        let ret = ffi::MsiGetProductCode(component_code.as_ptr(), product_code.as_mut_ptr());
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_error_code(ret));
+        }
+
+        Guid::from_terminated(&product_code)
+    }
+}
+
+/// Reinstalls a missing or broken component of a product.
+///
+/// Pass [`InstallState::Local`], [`InstallState::Source`], or [`InstallState::Default`] for how to install the component.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{install_missing_component, InstallState};
+///
+/// install_missing_component(
+///     "{01234567-89AB-CDEF-0123-456789ABCDEF}",
+///     "{FEDCBA98-7654-3210-FEDC-BA9876543210}",
+///     InstallState::Default,
+/// )?;
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[track_caller]
+pub fn install_missing_component(
+    product_code: impl AsRef<str>,
+    component_code: impl AsRef<str>,
+    state: InstallState,
+) -> Result<()> {
+    unsafe {
+        let product_code = ffi::to_wide(product_code.as_ref())?;
+        let component_code = ffi::to_wide(component_code.as_ref())?;
+
+        let ret =
+            ffi::MsiInstallMissingComponent(product_code.as_ptr(), component_code.as_ptr(), state);
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+
+        Ok(())
+    }
+}
+
+/// Reinstalls a missing file of a product e.g., when an application detects a file it needs was deleted.
+#[track_caller]
+pub fn install_missing_file(product_code: impl AsRef<str>, file: &str) -> Result<()> {
+    unsafe {
+        let product_code = ffi::to_wide(product_code.as_ref())?;
+        let file = ffi::to_wide(file)?;
+
+        let ret = ffi::MsiInstallMissingFile(product_code.as_ptr(), file.as_ptr());
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+
+        Ok(())
+    }
+}
+
+/// Gets the installed state and key path of a component installed by any product for the current user or per-machine.
+///
+/// The path is `None` unless the component is installed [`InstallState::Local`] or [`InstallState::Source`].
+/// Use this when the product code is unknown e.g., for components shared by multiple products.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::locate_component;
+///
+/// if let (_, Some(path)) = locate_component("{FEDCBA98-7654-3210-FEDC-BA9876543210}")? {
+///     println!("{}", path.display());
+/// }
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub fn locate_component(
+    component_code: impl AsRef<str>,
+) -> Result<(InstallState, Option<PathBuf>)> {
+    unsafe {
+        let component_code = ffi::to_wide(component_code.as_ref())?;
+        let mut state = 0i32;
+
+        let path = ffi::get_wide(|path, path_len| {
+            state = ffi::MsiLocateComponent(component_code.as_ptr(), path, path_len);
+            match InstallState::try_from(state) {
+                Ok(InstallState::MoreData) => ffi::ERROR_MORE_DATA,
+                Ok(InstallState::InvalidArg) => ffi::ERROR_INVALID_PARAMETER,
+                _ => ffi::ERROR_SUCCESS,
+            }
+        })?;
+
+        let state = InstallState::try_from(state)?;
+        let path = match state {
+            InstallState::Local | InstallState::Source => Some(ffi::from_wide_os(&path).into()),
+            _ => None,
+        };
+
+        Ok((state, path))
+    }
+}
+
+/// Gets the key path of a component of a product, installing or repairing the feature as the [`InstallMode`] selects.
+///
+/// This is meant for applications that install components on demand.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{provide_component, InstallMode};
+///
+/// let path = provide_component(
+///     "{01234567-89AB-CDEF-0123-456789ABCDEF}",
+///     "Documentation",
+///     "{FEDCBA98-7654-3210-FEDC-BA9876543210}",
+///     InstallMode::Default,
+/// )?;
+/// println!("{}", path.display());
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[track_caller]
+pub fn provide_component(
+    product_code: impl AsRef<str>,
+    feature: &str,
+    component_code: impl AsRef<str>,
+    mode: InstallMode,
+) -> Result<PathBuf> {
+    unsafe {
+        let product_code = ffi::to_wide(product_code.as_ref())?;
+        let feature = ffi::to_wide(feature)?;
+        let component_code = ffi::to_wide(component_code.as_ref())?;
+
+        let path = ffi::get_wide(|path, path_len| {
+            ffi::MsiProvideComponent(
+                product_code.as_ptr(),
+                feature.as_ptr(),
+                component_code.as_ptr(),
+                mode.into(),
+                path,
+                path_len,
+            )
+        })?;
+
+        Ok(ffi::from_wide_os(&path).into())
+    }
+}
+
+/// Gets the key path of a qualified component in the `PublishComponent` table, installing or repairing it
+/// as the [`InstallMode`] selects.
+///
+/// Qualified components are grouped by a `category` GUID and identified by a `qualifier` e.g., a language.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{provide_qualified_component, InstallMode};
+///
+/// let path = provide_qualified_component("{FEDCBA98-7654-3210-FEDC-BA9876543210}", "1033", InstallMode::Default)?;
+/// println!("{}", path.display());
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[track_caller]
+pub fn provide_qualified_component(
+    category: &str,
+    qualifier: &str,
+    mode: InstallMode,
+) -> Result<PathBuf> {
+    unsafe {
+        let category = ffi::to_wide(category)?;
+        let qualifier = ffi::to_wide(qualifier)?;
+
+        let path = ffi::get_wide(|path, path_len| {
+            ffi::MsiProvideQualifiedComponent(
+                category.as_ptr(),
+                qualifier.as_ptr(),
+                mode.into(),
+                path,
+                path_len,
+            )
+        })?;
+
+        Ok(ffi::from_wide_os(&path).into())
+    }
+}
+
+/// Gets the key path of a qualified component like [`provide_qualified_component()`], but only from the product
+/// with the given `product_code` if not `None`.
+#[track_caller]
+pub fn provide_qualified_component_ex(
+    category: &str,
+    qualifier: &str,
+    mode: InstallMode,
+    product_code: Option<&str>,
+) -> Result<PathBuf> {
+    unsafe {
+        let category = ffi::to_wide(category)?;
+        let qualifier = ffi::to_wide(qualifier)?;
+        let product_code = match product_code {
+            Some(product_code) => Some(ffi::to_wide(product_code)?),
+            None => None,
+        };
+
+        let path = ffi::get_wide(|path, path_len| {
+            ffi::MsiProvideQualifiedComponentEx(
+                category.as_ptr(),
+                qualifier.as_ptr(),
+                mode.into(),
+                product_code
+                    .as_ref()
+                    .map_or(std::ptr::null(), |product_code| product_code.as_ptr()),
+                0,
+                0,
+                path,
+                path_len,
+            )
+        })?;
+
+        Ok(ffi::from_wide_os(&path).into())
+    }
+}
+
+/// A component installed in a given context, returned from [`installed_components_ex()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstalledComponent {
+    /// The component code GUID.
+    pub component_code: Guid,
+    /// The context in which the component is installed.
+    pub context: InstallContext,
+    /// The security identifier (SID) of the user for which the component is installed,
+    /// or `None` if the component is installed per-machine.
+    pub sid: Option<String>,
+}
+
+/// Enumerates the component codes of components installed for the current user or per-machine.
+/// Returned from [`installed_components()`].
+pub struct Components {
+    index: u32,
+    done: bool,
+}
+
+/// Enumerates the component codes of components installed for the current user or per-machine.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::installed_components;
+///
+/// for component_code in installed_components() {
+///     println!("{}", component_code?);
+/// }
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub fn installed_components() -> Components {
+    Components {
+        index: 0,
+        done: false,
+    }
+}
+
+impl Iterator for Components {
+    type Item = Result<Guid>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut component_code = [0u16; ffi::GUID_LEN];
+        let ret = unsafe { ffi::MsiEnumComponents(self.index, component_code.as_mut_ptr()) };
+        match ret {
+            ffi::ERROR_SUCCESS => {
+                self.index += 1;
+                Some(Guid::from_terminated(&component_code))
+            }
+            ffi::ERROR_NO_MORE_ITEMS => {
+                self.done = true;
+                None
+            }
+            _ => {
+                self.done = true;
+                Some(Err(Error::from_error_code(ret)))
+            }
+        }
+    }
+}
+
+/// Enumerates components installed in the given contexts. Returned from [`installed_components_ex()`].
+pub struct ComponentsEx {
+    sid: Option<Vec<u16>>,
+    context: InstallContext,
+    index: u32,
+    done: bool,
+}
+
+/// Enumerates components installed in the given contexts for a user.
+///
+/// Pass `None` for the `sid` to use the current user, or `Some("S-1-1-0")` for all users.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{installed_components_ex, InstallContext};
+///
+/// for component in installed_components_ex(None, InstallContext::MACHINE)? {
+///     println!("{}", component?.component_code);
+/// }
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub fn installed_components_ex(sid: Option<&str>, context: InstallContext) -> Result<ComponentsEx> {
+    let sid = match sid {
+        Some(sid) => Some(ffi::to_wide(sid)?),
+        None => None,
+    };
+
+    Ok(ComponentsEx {
+        sid,
+        context,
+        index: 0,
+        done: false,
+    })
+}
+
+impl Iterator for ComponentsEx {
+    type Item = Result<InstalledComponent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut component_code = [0u16; ffi::GUID_LEN];
+        let mut context = 0i32;
+
+        let sid = unsafe {
+            ffi::get_string(|sid, sid_len| {
+                ffi::MsiEnumComponentsEx(
+                    self.sid
+                        .as_ref()
+                        .map_or(std::ptr::null(), |sid| sid.as_ptr()),
+                    self.context.bits(),
+                    self.index,
+                    component_code.as_mut_ptr(),
+                    &mut context,
+                    sid,
+                    sid_len,
+                )
+            })
+        };
+
+        let sid = match sid {
+            Ok(sid) => sid,
+            Err(err) => {
+                self.done = true;
+                if err.is(ffi::ERROR_NO_MORE_ITEMS) {
+                    return None;
+                }
+                return Some(Err(err));
+            }
+        };
+        self.index += 1;
+
+        let component =
+            Guid::from_terminated(&component_code).map(|component_code| InstalledComponent {
+                component_code,
+                context: InstallContext::from_bits_retain(context as u32),
+                sid: (!sid.is_empty()).then_some(sid),
+            });
+        Some(component)
+    }
+}
+
+/// The product, feature, and component an advertised shortcut targets, returned from [`shortcut_target()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShortcutTarget {
+    /// The product code GUID.
+    pub product: Guid,
+    /// The name of the feature in the `Feature` table.
+    pub feature: String,
+    /// The component code GUID, or `None` if the shortcut does not target a specific component.
+    pub component: Option<Guid>,
+}
+
+/// Gets the product, feature, and component that an advertised shortcut (.lnk) targets.
+///
+/// Returns an error if the shortcut is not an advertised shortcut created by Windows Installer.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{provide_component, shortcut_target, InstallMode};
+///
+/// let target = shortcut_target("app.lnk")?;
+/// if let Some(component) = &target.component {
+///     let path = provide_component(target.product, &target.feature, component, InstallMode::Default)?;
+///     println!("{}", path.display());
+/// }
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub fn shortcut_target(path: impl AsRef<Path>) -> Result<ShortcutTarget> {
+    unsafe {
+        let path = ffi::to_wide_os(path.as_ref().as_os_str())?;
+        let mut product = [0u16; ffi::GUID_LEN];
+        // MAX_FEATURE_CHARS is the same length as a GUID.
+        let mut feature = [0u16; ffi::GUID_LEN];
+        let mut component = [0u16; ffi::GUID_LEN];
+
+        let ret = ffi::MsiGetShortcutTarget(
+            path.as_ptr(),
+            product.as_mut_ptr(),
+            feature.as_mut_ptr(),
+            component.as_mut_ptr(),
+        );
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_error_code(ret));
+        }
+
+        let component = match component[0] {
+            0 => None,
+            _ => Some(Guid::from_terminated(&component)?),
+        };
+        Ok(ShortcutTarget {
+            product: Guid::from_terminated(&product)?,
+            feature: ffi::from_terminated(&feature)?,
+            component,
+        })
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::Result;
+
+repr_enum! {
+    /// The kind of code a custom action runs, stored in the low bits of [`CustomActionType`].
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub enum CustomActionCode: u32 {
+        /// A function exported from a DLL.
+        Dll = 0x01,
+        /// An executable.
+        Exe = 0x02,
+        /// Text data e.g., to set a property or directory, or display an error.
+        TextData = 0x03,
+        /// A JScript function or script.
+        JScript = 0x05,
+        /// A VBScript function or script.
+        VBScript = 0x06,
+        /// A nested installation.
+        Install = 0x07,
+    }
+}
+
+repr_enum! {
+    /// Where the code for a custom action is found, stored in [`CustomActionType`].
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub enum CustomActionSource: u32 {
+        /// A stream in the `Binary` table.
+        BinaryData = 0x00,
+        /// A file installed with the product.
+        SourceFile = 0x10,
+        /// A directory, for executables run from a working directory or text data that sets a directory.
+        Directory = 0x20,
+        /// A property value.
+        Property = 0x30,
+    }
+}
+
+flags! {
+    /// The `Type` column of the `CustomAction` table.
+    ///
+    /// Combine a [`CustomActionCode`] and [`CustomActionSource`] with [`CustomActionType::new()`],
+    /// then add options e.g., with [`CustomActionType::deferred()`].
+    ///
+    /// [`CustomActionType::FIRST_SEQUENCE`] and [`CustomActionType::ONCE_PER_PROCESS`] share bits with
+    /// [`CustomActionType::ROLLBACK`] and [`CustomActionType::COMMIT`], which are only valid with [`CustomActionType::IN_SCRIPT`].
+    pub struct CustomActionType: u32 {
+        /// Run during rollback of a deferred installation script.
+        const ROLLBACK = 0x500;
+        /// Run when a deferred installation script completes successfully.
+        const COMMIT = 0x600;
+        /// Run deferred in the installation script.
+        const IN_SCRIPT = 0x400;
+        /// Run on the client only when the action is also run on the server.
+        const CLIENT_REPEAT = 0x300;
+        /// Run only the first time the action is found in a sequence.
+        const FIRST_SEQUENCE = 0x100;
+        /// Run only once if run in both the UI and execute sequences.
+        const ONCE_PER_PROCESS = 0x200;
+        /// Ignore the exit code.
+        const CONTINUE = 0x40;
+        /// Run asynchronously.
+        const ASYNC = 0x80;
+        /// Run deferred as the local system account instead of impersonating the user.
+        const NO_IMPERSONATE = 0x800;
+        /// Run a script in a 64-bit process.
+        const WIN64 = 0x1000;
+        /// Do not write the action data to the log.
+        const HIDE_TARGET = 0x2000;
+        /// Impersonate the user on a terminal server.
+        const TS_AWARE = 0x4000;
+        /// Run only when a patch is uninstalled.
+        const PATCH_UNINSTALL = 0x8000;
+    }
+}
+
+impl CustomActionType {
+    /// Creates an immediate custom action type that runs `code` from `source`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use msica::{CustomActionCode, CustomActionSource, CustomActionType};
+    ///
+    /// let kind = CustomActionType::new(CustomActionCode::Dll, CustomActionSource::BinaryData)
+    ///     .deferred()
+    ///     .no_impersonate();
+    /// assert_eq!(3073, kind.bits());
+    /// ```
+    pub const fn new(code: CustomActionCode, source: CustomActionSource) -> Self {
+        Self(code as u32 | source as u32)
+    }
+
+    /// Gets the kind of code the custom action runs.
+    pub fn code(&self) -> Result<CustomActionCode> {
+        CustomActionCode::try_from(self.0 & 0x07)
+    }
+
+    /// Gets where the code for the custom action is found.
+    pub fn source(&self) -> CustomActionSource {
+        match self.0 & 0x30 {
+            0x10 => CustomActionSource::SourceFile,
+            0x20 => CustomActionSource::Directory,
+            0x30 => CustomActionSource::Property,
+            _ => CustomActionSource::BinaryData,
+        }
+    }
+
+    /// Runs the custom action deferred in the installation script.
+    pub const fn deferred(self) -> Self {
+        Self(self.0 | Self::IN_SCRIPT.0)
+    }
+
+    /// Runs the custom action deferred during rollback.
+    pub const fn rollback(self) -> Self {
+        Self(self.0 | Self::ROLLBACK.0)
+    }
+
+    /// Runs the custom action deferred when the installation script completes successfully.
+    pub const fn commit(self) -> Self {
+        Self(self.0 | Self::COMMIT.0)
+    }
+
+    /// Runs the custom action deferred as the local system account.
+    pub const fn no_impersonate(self) -> Self {
+        Self(self.0 | Self::IN_SCRIPT.0 | Self::NO_IMPERSONATE.0)
+    }
+
+    /// Ignores the exit code of the custom action.
+    pub const fn ignore_exit_code(self) -> Self {
+        Self(self.0 | Self::CONTINUE.0)
+    }
+
+    /// Gets whether the custom action runs deferred in the installation script, including during rollback or commit.
+    pub const fn is_deferred(&self) -> bool {
+        self.contains(Self::IN_SCRIPT)
+    }
+
+    /// Gets whether the custom action runs during rollback.
+    pub const fn is_rollback(&self) -> bool {
+        self.0 & 0x700 == Self::ROLLBACK.0
+    }
+
+    /// Gets whether the custom action runs when the installation script completes successfully.
+    pub const fn is_commit(&self) -> bool {
+        self.0 & 0x700 == Self::COMMIT.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compose() {
+        let kind = CustomActionType::new(CustomActionCode::Dll, CustomActionSource::BinaryData)
+            .rollback()
+            .no_impersonate();
+        assert_eq!(0x0d01, kind.bits());
+        assert_eq!(CustomActionCode::Dll, kind.code().unwrap());
+        assert_eq!(CustomActionSource::BinaryData, kind.source());
+        assert!(kind.is_deferred());
+        assert!(kind.is_rollback());
+        assert!(!kind.is_commit());
+    }
+
+    #[test]
+    fn commit() {
+        let kind = CustomActionType::new(CustomActionCode::Exe, CustomActionSource::Property)
+            .commit()
+            .ignore_exit_code();
+        assert_eq!(0x0672, kind.bits());
+        assert_eq!(CustomActionSource::Property, kind.source());
+        assert!(kind.is_commit());
+        assert!(!kind.is_rollback());
+    }
+
+    #[test]
+    fn immediate() {
+        let kind = CustomActionType::new(CustomActionCode::TextData, CustomActionSource::Directory);
+        assert_eq!(35, kind.bits());
+        assert!(!kind.is_deferred());
+        assert!(!kind.is_rollback());
+        assert!(!kind.is_commit());
+        assert!(CustomActionType::from_bits_retain(0x04).code().is_err());
+    }
+
+    #[test]
+    fn debug() {
+        let kind = CustomActionType::new(CustomActionCode::Dll, CustomActionSource::BinaryData)
+            .rollback()
+            .no_impersonate();
+        assert_eq!(
+            "CustomActionType(ROLLBACK | NO_IMPERSONATE | 0x1)",
+            format!("{kind:?}")
+        );
+    }
+}
@@ -2,40 +2,90 @@
 // Licensed under the MIT License. See LICENSE.txt in the project root for license information.
 
 use crate::ffi;
-use crate::{Error, Record, Result, View};
-use std::ffi::CString;
+use crate::tables::Row;
+use crate::{Error, ModifyMode, Record, Result, UiPreview, View};
+use std::path::Path;
 
 /// The database for the current install session.
+///
+/// Cloning a [`Database`] is cheap and shares the same underlying handle, which is closed when the last clone is dropped.
+#[derive(Clone)]
 pub struct Database {
-    h: ffi::PMSIHANDLE,
+    h: ffi::SharedHandle<ffi::kind::Database>,
 }
 
 impl Database {
+    /// Creates a new database at `path`, replacing any existing file.
+    ///
+    /// Changes are not written to the file until [`Database::commit()`] is called.
+    #[track_caller]
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        unsafe {
+            let mut h = ffi::DatabaseHandle::null();
+            let path = ffi::to_wide_os(path.as_ref().as_os_str())?;
+            let ret = ffi::MsiOpenDatabase(path.as_ptr(), ffi::MSIDBOPEN_CREATE, &mut h);
+            if ret != ffi::ERROR_SUCCESS {
+                return Err(Error::from_last_error(ret));
+            }
+
+            Ok(Self::from_handle(h))
+        }
+    }
+
+    /// Writes changes to a database opened for writing e.g., by [`Database::create()`].
+    pub fn commit(&self) -> Result<()> {
+        unsafe {
+            let ret = ffi::MsiDatabaseCommit(*self.h);
+            if ret != ffi::ERROR_SUCCESS {
+                return Err(Error::from_last_error(ret));
+            }
+
+            Ok(())
+        }
+    }
+
     /// Returns a [`View`] object that represents the query specified by a
     /// [SQL string](https://docs.microsoft.com/windows/win32/msi/sql-syntax).
     pub fn open_view(&self, sql: &str) -> Result<View> {
         unsafe {
-            let mut h = ffi::MSIHANDLE::null();
-            let sql = CString::new(sql)?;
+            let mut h = ffi::ViewHandle::null();
+            let sql = ffi::to_wide(sql)?;
             let ret = ffi::MsiDatabaseOpenView(*self.h, sql.as_ptr(), &mut h);
             if ret != ffi::ERROR_SUCCESS {
-                return Err(
-                    Error::from_last_error_record().unwrap_or_else(|| Error::from_error_code(ret))
-                );
+                return Err(Error::from_last_error(ret));
             }
 
             Ok(View::from_handle(h))
         }
     }
 
+    /// Imports a table from an archive file e.g., an `.idt` file, replacing any existing table with the same name.
+    ///
+    /// Streams are imported from files in a folder with the same name as the table next to the archive file.
+    pub fn import(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let folder = path.parent().unwrap_or_else(|| Path::new("."));
+        let file = path.file_name().unwrap_or_default();
+        unsafe {
+            let folder = ffi::to_wide_os(folder.as_os_str())?;
+            let file = ffi::to_wide_os(file)?;
+            let ret = ffi::MsiDatabaseImport(*self.h, folder.as_ptr(), file.as_ptr());
+            if ret != ffi::ERROR_SUCCESS {
+                return Err(Error::from_last_error(ret));
+            }
+
+            Ok(())
+        }
+    }
+
     /// Returns a [`Record`] object containing the table name in field 0 and the column names
     /// (comprising the primary keys) in succeeding fields corresponding to their column numbers.
     ///
     /// The field count of the record is the count of primary key columns.
     pub fn primary_keys(&self, table: &str) -> Result<Record> {
         unsafe {
-            let mut h = ffi::MSIHANDLE::null();
-            let table = CString::new(table)?;
+            let mut h = ffi::RecordHandle::null();
+            let table = ffi::to_wide(table)?;
             let ret = ffi::MsiDatabaseGetPrimaryKeys(*self.h, table.as_ptr(), &mut h);
             if ret != ffi::ERROR_SUCCESS {
                 return Err(Error::from_error_code(ret));
@@ -45,7 +95,63 @@ impl Database {
         }
     }
 
-    pub(crate) fn from_handle(h: ffi::MSIHANDLE) -> Self {
-        Database { h: h.to_owned() }
+    /// Gets all rows of a standard table e.g., [`FileRow`](crate::tables::FileRow).
+    pub fn rows<T: Row>(&self) -> Result<Vec<T>> {
+        let view = self.open_view(&T::query())?;
+        view.execute(None)?;
+        view.map(|record| T::from_record(&record)).collect()
+    }
+
+    /// Inserts a row into a standard table e.g., with [`ModifyMode::InsertTemporary`] from a custom action.
+    pub fn insert_row<T: Row>(&self, row: &T, mode: ModifyMode) -> Result<()> {
+        let view = self.open_view(&T::query())?;
+        view.execute(None)?;
+        view.modify(mode, &row.to_record()?)
+    }
+
+    /// Returns a [`UiPreview`] to display the dialogs and billboards authored in the [`Database`].
+    #[track_caller]
+    pub fn ui_preview(&self) -> Result<UiPreview> {
+        unsafe {
+            let mut h = ffi::PreviewHandle::null();
+            let ret = ffi::MsiEnableUIPreview(*self.h, &mut h);
+            if ret != ffi::ERROR_SUCCESS {
+                return Err(Error::from_last_error(ret));
+            }
+
+            Ok(UiPreview::from_handle(h, self.clone()))
+        }
+    }
+
+    /// Creates a [`Database`] from a raw handle, taking ownership of it.
+    ///
+    /// The handle is closed when the [`Database`] is dropped.
+    ///
+    /// # Safety
+    ///
+    /// The handle must be a valid, open database handle that is not closed elsewhere.
+    #[track_caller]
+    pub unsafe fn from_raw(h: ffi::MSIHANDLE) -> Self {
+        Self::from_handle(ffi::DatabaseHandle::from_raw(h))
+    }
+
+    /// Consumes the [`Database`] and returns the raw handle without closing it.
+    ///
+    /// The caller is responsible for closing the handle e.g., by passing it to [`Database::from_raw()`].
+    /// If the [`Database`] has been cloned and other clones are still alive, the [`Database`] is returned instead.
+    pub fn into_raw(self) -> std::result::Result<ffi::MSIHANDLE, Self> {
+        self.h.try_into_raw().map_err(|h| Self { h })
+    }
+
+    /// Gets the raw handle without transferring ownership.
+    pub fn as_raw(&self) -> ffi::MSIHANDLE {
+        **self.h
+    }
+
+    #[track_caller]
+    pub(crate) fn from_handle(h: ffi::DatabaseHandle) -> Self {
+        Database {
+            h: h.to_owned().into(),
+        }
     }
 }
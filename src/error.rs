@@ -1,11 +1,17 @@
 // Copyright 2024 Heath Stewart.
 // Licensed under the MIT License. See LICENSE.txt in the project root for license information.
 
-use crate::Record;
+use crate::{Field, Record, Session};
 use std::fmt::Display;
 use std::num::{NonZeroU32, TryFromIntError};
+use std::sync::OnceLock;
 
+pub mod codes;
 pub mod experimental;
+mod windows;
+
+const HRESULT_FACILITY_WIN32: u32 = 0x8007_0000;
+const E_FAIL: u32 = 0x8000_4005;
 
 /// Results returned by this crate.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -65,12 +71,131 @@ impl Error {
 
     pub(crate) fn from_error_record(record: Record) -> Self {
         Self {
-            context: Context::Record(record),
+            context: Context::Record(ErrorRecord::capture(&record)),
+        }
+    }
+
+    /// Creates an `Error` from the error code returned by a function and the last error record, if any.
+    ///
+    /// Only the handle to the last error record is retrieved; its fields are not read or formatted
+    /// unless the `Error` is displayed or its record or fields are requested.
+    pub(crate) fn from_last_error(code: u32) -> Self {
+        match crate::last_error_record() {
+            Some(record) => Self {
+                context: Context::Record(ErrorRecord::deferred(code, record)),
+            },
+            None => Error::from_error_code(code),
+        }
+    }
+
+    /// Gets the Windows error code if this `Error` has [`ErrorKind::ErrorCode`],
+    /// or if it has [`ErrorKind::ErrorRecord`] and was returned from a function that failed with an error code.
+    pub fn code(&self) -> Option<u32> {
+        match &self.context {
+            Context::Record(record) => record.code.map(NonZeroU32::get),
+            _ => match self.kind() {
+                ErrorKind::ErrorCode(code) => Some(code.get()),
+                _ => None,
+            },
         }
     }
 
-    pub(crate) fn from_last_error_record() -> Option<Self> {
-        crate::last_error_record().map(Error::from_error_record)
+    /// Creates an `Error` from a failed `HRESULT` returned by COM or other Windows APIs.
+    ///
+    /// An `HRESULT` with `FACILITY_WIN32` is converted to its Windows error code;
+    /// otherwise, the `HRESULT` is retained as the error code so that [`Error::to_hresult()`] returns it unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use msica::Error;
+    ///
+    /// let error = Error::from_hresult(0x8007_0643_u32 as i32);
+    /// assert_eq!(error.code(), Some(1603));
+    /// assert_eq!(error.to_hresult(), 0x8007_0643_u32 as i32);
+    /// ```
+    pub fn from_hresult(hr: i32) -> Self {
+        let code = match hr as u32 {
+            _ if hr >= 0 => None,
+            hr if hr & 0xffff_0000 == HRESULT_FACILITY_WIN32 => NonZeroU32::new(hr & 0xffff),
+            hr => NonZeroU32::new(hr),
+        };
+        match code {
+            Some(code) => Self {
+                context: Context::Simple(ErrorKind::ErrorCode(code)),
+            },
+            None => Error::new(
+                ErrorKind::Other,
+                format!("HRESULT {:#010x} is not an error", hr),
+            ),
+        }
+    }
+
+    /// Converts this `Error` to an `HRESULT` like `HRESULT_FROM_WIN32`.
+    ///
+    /// Errors without a Windows error code return `E_FAIL`.
+    pub fn to_hresult(&self) -> i32 {
+        match self.code() {
+            Some(code) if code & 0x8000_0000 != 0 => code as i32,
+            Some(code) => ((code & 0xffff) | HRESULT_FACILITY_WIN32) as i32,
+            None => E_FAIL as i32,
+        }
+    }
+
+    /// Gets whether this `Error` contains the given Windows error code e.g., [`ERROR_FUNCTION_FAILED`](crate::codes::ERROR_FUNCTION_FAILED).
+    pub fn is(&self, code: u32) -> bool {
+        self.code() == Some(code)
+    }
+
+    /// Gets whether the user canceled the installation.
+    pub fn is_cancelled(&self) -> bool {
+        self.is(codes::ERROR_INSTALL_USEREXIT)
+    }
+
+    /// Gets whether a SQL query was invalid or unsupported.
+    pub fn is_bad_query_syntax(&self) -> bool {
+        self.is(codes::ERROR_BAD_QUERY_SYNTAX)
+    }
+
+    /// Gets whether a function failed during execution.
+    pub fn is_function_failed(&self) -> bool {
+        self.is(codes::ERROR_FUNCTION_FAILED)
+    }
+
+    /// Gets a [`Record`] containing Windows Installer error information if this `Error` has [`ErrorKind::ErrorRecord`].
+    ///
+    /// Field 1 typically contains the installer error number, and subsequent fields contain its parameters.
+    /// The fields are captured when the `Error` is created, so a new [`Record`] is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use msica::{Error, Field, Record};
+    ///
+    /// let record = Record::with_fields(Some("error [1]"), vec![Field::IntegerData(2228)])?;
+    /// let error = Error::from(record);
+    /// assert_eq!(error.record().and_then(|r| r.integer_data(1)), Some(2228));
+    /// # Ok::<(), msica::Error>(())
+    /// ```
+    pub fn record(&self) -> Option<Record> {
+        match &self.context {
+            Context::Record(record) => {
+                let data = record.data();
+                Record::with_fields(data.template.as_deref(), data.fields.clone()).ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Gets the fields of the [`Record`] containing Windows Installer error information
+    /// if this `Error` has [`ErrorKind::ErrorRecord`].
+    ///
+    /// Fields are 0-based in the returned slice, so field 1 of the [`Record`] is at index 0.
+    pub fn fields(&self) -> Option<&[Field]> {
+        match &self.context {
+            Context::Record(record) => Some(&record.data().fields),
+            _ => None,
+        }
     }
 
     /// Gets the [`ErrorKind`] of this `Error`.
@@ -87,7 +212,7 @@ impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self.context {
             Context::Simple(kind) => write!(f, "{}", kind),
-            Context::Record(record) => write!(f, "{}", record),
+            Context::Record(record) => write!(f, "{}", record.data().text),
             Context::Custom(Custom { error, .. }) => write!(f, "{}", error),
         }
     }
@@ -120,19 +245,190 @@ impl From<std::string::FromUtf8Error> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        let kind = match error
+            .raw_os_error()
+            .and_then(|code| NonZeroU32::new(code as u32))
+        {
+            Some(code) => ErrorKind::ErrorCode(code),
+            None => ErrorKind::Other,
+        };
+        Error::new(kind, error)
+    }
+}
+
+impl From<Error> for std::io::Error {
+    fn from(error: Error) -> Self {
+        if let Some(code) = error.code() {
+            return std::io::Error::from_raw_os_error(code as i32);
+        }
+
+        match error.context {
+            Context::Custom(Custom { error, .. }) => match error.downcast::<std::io::Error>() {
+                Ok(error) => *error,
+                Err(error) => std::io::Error::other(error),
+            },
+            context => std::io::Error::other(Error { context }),
+        }
+    }
+}
+
+impl From<std::string::FromUtf16Error> for Error {
+    fn from(error: std::string::FromUtf16Error) -> Self {
+        Error::new(ErrorKind::DataConversion, error)
+    }
+}
+
 impl From<Record> for Error {
     fn from(record: Record) -> Self {
         Error::from_error_record(record)
     }
 }
 
+/// Finishes a custom action with any `Result<T, E>` where `E` can be converted into an [`Error`].
+///
+/// Errors are written to the installation log, and the appropriate custom action return code is returned:
+/// [`ERROR_NO_MORE_ITEMS`](codes::ERROR_NO_MORE_ITEMS), [`ERROR_INSTALL_USEREXIT`](codes::ERROR_INSTALL_USEREXIT),
+/// or [`ERROR_FUNCTION_NOT_CALLED`](codes::ERROR_FUNCTION_NOT_CALLED) are returned as-is;
+/// all other errors return [`ERROR_INSTALL_FAILURE`](codes::ERROR_INSTALL_FAILURE).
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::prelude::*;
+///
+/// #[no_mangle]
+/// pub extern "C" fn MyCustomAction(session: Session) -> u32 {
+///     run(&session).into_ca_result(&session)
+/// }
+///
+/// fn run(session: &Session) -> Result<()> {
+///     let _product_name = session.property("ProductName")?;
+///     Ok(())
+/// }
+/// ```
+pub trait IntoCustomActionResult {
+    /// Writes any error to the installation log and returns the custom action return code.
+    fn into_ca_result(self, session: &Session) -> u32;
+}
+
+impl<T, E> IntoCustomActionResult for std::result::Result<T, E>
+where
+    E: Into<Error>,
+{
+    fn into_ca_result(self, session: &Session) -> u32 {
+        match self {
+            Ok(_) => codes::ERROR_SUCCESS,
+            Err(error) => {
+                let error: Error = error.into();
+                match error.code() {
+                    // Skipping remaining actions or canceling are intentional outcomes, not failures.
+                    Some(
+                        code @ (codes::ERROR_NO_MORE_ITEMS
+                        | codes::ERROR_INSTALL_USEREXIT
+                        | codes::ERROR_FUNCTION_NOT_CALLED),
+                    ) => {
+                        session.info(format!("custom action returned {}: {}", code, error));
+                        code
+                    }
+                    _ => {
+                        session.info(format!("custom action failed: {}", error));
+                        codes::ERROR_INSTALL_FAILURE
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug)]
 enum Context {
     Simple(ErrorKind),
-    Record(Record),
+    Record(ErrorRecord),
     Custom(Custom),
 }
 
+/// Windows Installer error information from a [`Record`].
+#[derive(Debug)]
+struct ErrorRecord {
+    code: Option<NonZeroU32>,
+    record: Option<DeferredRecord>,
+    data: OnceLock<ErrorRecordData>,
+}
+
+/// A [`Record`] from which data is captured only once, when first needed.
+#[derive(Debug)]
+struct DeferredRecord(Record);
+
+// SAFETY: The record is not cloned and is only used while initializing `ErrorRecord::data`,
+// which `OnceLock` ensures is never done from multiple threads at the same time.
+unsafe impl Send for DeferredRecord {}
+unsafe impl Sync for DeferredRecord {}
+
+impl ErrorRecord {
+    /// Captures data from the [`Record`] immediately.
+    fn capture(record: &Record) -> Self {
+        Self {
+            code: None,
+            record: None,
+            data: OnceLock::from(ErrorRecordData::capture(record)),
+        }
+    }
+
+    /// Keeps the [`Record`] to capture data from only when needed.
+    fn deferred(code: u32, record: Record) -> Self {
+        Self {
+            code: NonZeroU32::new(code),
+            record: Some(DeferredRecord(record)),
+            data: OnceLock::new(),
+        }
+    }
+
+    fn data(&self) -> &ErrorRecordData {
+        self.data.get_or_init(|| {
+            let record = self
+                .record
+                .as_ref()
+                .expect("expected record if data was not captured");
+            ErrorRecordData::capture(&record.0)
+        })
+    }
+}
+
+/// Data captured from a [`Record`].
+#[derive(Debug)]
+struct ErrorRecordData {
+    template: Option<String>,
+    text: String,
+    fields: Vec<Field>,
+}
+
+impl ErrorRecordData {
+    fn capture(record: &Record) -> Self {
+        let template = match record.is_null(0) {
+            true => None,
+            false => record.string_data(0).ok(),
+        };
+        let fields = (1..=record.field_count())
+            .map(|i| match record.integer_data(i) {
+                _ if record.is_null(i) => Field::Null,
+                Some(value) => Field::IntegerData(value),
+                None => record
+                    .string_data(i)
+                    .map(Field::StringData)
+                    .unwrap_or(Field::Null),
+            })
+            .collect();
+
+        Self {
+            template,
+            text: record.to_string(),
+            fields,
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Custom {
     kind: ErrorKind,
@@ -142,9 +438,14 @@ struct Custom {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Field;
     use std::ffi::CString;
 
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Error>();
+    }
+
     #[test]
     fn from_error_code() {
         let error = Error::from_error_code(1603);
@@ -155,6 +456,77 @@ mod tests {
         assert_eq!("ErrorCode(1603)", error.to_string());
     }
 
+    #[test]
+    fn is_code() {
+        let error = Error::from_error_code(codes::ERROR_INSTALL_USEREXIT);
+        assert!(error.is(1602));
+        assert!(error.is_cancelled());
+        assert!(!error.is_function_failed());
+
+        let error = Error::from_error_code(codes::ERROR_BAD_QUERY_SYNTAX);
+        assert!(error.is_bad_query_syntax());
+        assert!(!error.is_cancelled());
+
+        let error = Error::new(ErrorKind::Other, "other");
+        assert!(!error.is(0));
+    }
+
+    #[test]
+    fn code() {
+        assert_eq!(Some(1603), Error::from_error_code(1603).code());
+        assert_eq!(None, Error::new(ErrorKind::DataConversion, "test").code());
+    }
+
+    #[test]
+    // cspell:ignore ioerror
+    fn from_ioerror() {
+        let error: Error = std::io::Error::from_raw_os_error(5).into();
+        assert_eq!(Some(5), error.code());
+        assert_eq!(
+            std::io::Error::from_raw_os_error(5).to_string(),
+            error.to_string()
+        );
+
+        let error: Error = std::io::Error::new(std::io::ErrorKind::NotFound, "test").into();
+        assert_eq!(&ErrorKind::Other, error.kind());
+        assert_eq!("test", error.to_string());
+    }
+
+    #[test]
+    // cspell:ignore ioerror
+    fn into_ioerror() {
+        let error: std::io::Error = Error::from_error_code(1603).into();
+        assert_eq!(Some(1603), error.raw_os_error());
+
+        let error: std::io::Error =
+            Error::from(std::io::Error::new(std::io::ErrorKind::NotFound, "test")).into();
+        assert_eq!(std::io::ErrorKind::NotFound, error.kind());
+
+        let error: std::io::Error = Error::new(ErrorKind::DataConversion, "test").into();
+        assert_eq!(std::io::ErrorKind::Other, error.kind());
+        assert_eq!("test", error.to_string());
+    }
+
+    #[test]
+    fn hresult() {
+        let error = Error::from_hresult(0x8007_0005_u32 as i32);
+        assert_eq!(Some(5), error.code());
+        assert_eq!(0x8007_0005_u32 as i32, error.to_hresult());
+
+        let error = Error::from_hresult(0x8000_4001_u32 as i32);
+        assert_eq!(Some(0x8000_4001), error.code());
+        assert_eq!(0x8000_4001_u32 as i32, error.to_hresult());
+
+        let error = Error::from_hresult(0);
+        assert_eq!(&ErrorKind::Other, error.kind());
+        assert_eq!(0x8000_4005_u32 as i32, error.to_hresult());
+
+        assert_eq!(
+            0x8007_0642_u32 as i32,
+            Error::from_error_code(1602).to_hresult()
+        );
+    }
+
     #[test]
     fn from_record() {
         let record = Record::with_fields(
@@ -165,6 +537,28 @@ mod tests {
         let error: Error = record.into();
         assert_eq!(&ErrorKind::ErrorRecord, error.kind());
         assert_eq!("error text", error.to_string());
+        assert_eq!("text", error.record().unwrap().string_data(1).unwrap());
+        assert_eq!(
+            Some(&[Field::StringData("text".to_owned())][..]),
+            error.fields()
+        );
+        assert!(Error::from_error_code(1603).record().is_none());
+    }
+
+    #[test]
+    fn deferred_record() {
+        let record = Record::with_fields(Some("error [1]"), vec![Field::IntegerData(2228)])
+            .expect("failed to create record");
+        let error = Error {
+            context: Context::Record(ErrorRecord::deferred(codes::ERROR_BAD_QUERY_SYNTAX, record)),
+        };
+        assert!(error.is_bad_query_syntax());
+        assert!(match &error.context {
+            Context::Record(record) => record.data.get().is_none(),
+            _ => false,
+        });
+        assert_eq!("error 2228", error.to_string());
+        assert_eq!(Some(&[Field::IntegerData(2228)][..]), error.fields());
     }
 
     #[test]
@@ -183,6 +577,14 @@ mod tests {
         assert_ne!("DataConversion", error.to_string());
     }
 
+    #[test]
+    // cspell:ignore fromutf16error
+    fn from_fromutf16error() {
+        let error: Error = String::from_utf16(&[0xd800]).unwrap_err().into();
+        assert_eq!(&ErrorKind::DataConversion, error.kind());
+        assert_ne!("DataConversion", error.to_string());
+    }
+
     #[test]
     // cspell:ignore fromutf8error
     fn from_fromutf8error() {
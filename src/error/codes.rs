@@ -0,0 +1,152 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Windows and Windows Installer error codes returned by installer functions and custom actions.
+//!
+//! See [MsiExec.exe and InstMsi.exe Error Messages](https://learn.microsoft.com/windows/win32/msi/error-codes).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use msica::codes::ERROR_BAD_QUERY_SYNTAX;
+//! use msica::prelude::*;
+//!
+//! # fn example(database: &Database) -> Result<()> {
+//! match database.open_view("SELECT * FROM `Missing`") {
+//!     Ok(view) => view.execute(None)?,
+//!     Err(err) if err.is(ERROR_BAD_QUERY_SYNTAX) => return Ok(()),
+//!     Err(err) => return Err(err),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+// cspell:ignore safeboot
+/// The action completed successfully.
+pub const ERROR_SUCCESS: u32 = 0;
+/// The system cannot find the file specified.
+pub const ERROR_FILE_NOT_FOUND: u32 = 2;
+/// Access is denied.
+pub const ERROR_ACCESS_DENIED: u32 = 5;
+/// The handle is invalid.
+pub const ERROR_INVALID_HANDLE: u32 = 6;
+/// Not enough storage is available to process this command.
+pub const ERROR_NOT_ENOUGH_MEMORY: u32 = 8;
+/// The data is invalid.
+pub const ERROR_INVALID_DATA: u32 = 13;
+/// Not enough storage is available to complete this operation.
+pub const ERROR_OUTOFMEMORY: u32 = 14;
+/// The parameter is incorrect.
+pub const ERROR_INVALID_PARAMETER: u32 = 87;
+/// This function is not supported on this system.
+pub const ERROR_CALL_NOT_IMPLEMENTED: u32 = 120;
+/// More data is available.
+pub const ERROR_MORE_DATA: u32 = 234;
+/// No more data is available. Returned from a custom action, skips remaining actions.
+pub const ERROR_NO_MORE_ITEMS: u32 = 259;
+/// The Windows Installer service could not be accessed.
+pub const ERROR_INSTALL_SERVICE_FAILURE: u32 = 1601;
+/// User canceled installation.
+pub const ERROR_INSTALL_USEREXIT: u32 = 1602;
+/// Fatal error during installation.
+pub const ERROR_INSTALL_FAILURE: u32 = 1603;
+/// Installation suspended, incomplete.
+pub const ERROR_INSTALL_SUSPEND: u32 = 1604;
+/// This action is only valid for products that are currently installed.
+pub const ERROR_UNKNOWN_PRODUCT: u32 = 1605;
+/// The feature identifier is not registered.
+pub const ERROR_UNKNOWN_FEATURE: u32 = 1606;
+/// The component identifier is not registered.
+pub const ERROR_UNKNOWN_COMPONENT: u32 = 1607;
+/// This is an unknown property.
+pub const ERROR_UNKNOWN_PROPERTY: u32 = 1608;
+/// The handle is in an invalid state.
+pub const ERROR_INVALID_HANDLE_STATE: u32 = 1609;
+/// The configuration data for this product is corrupt.
+pub const ERROR_BAD_CONFIGURATION: u32 = 1610;
+/// The component qualifier not present.
+pub const ERROR_INDEX_ABSENT: u32 = 1611;
+/// The installation source for this product is not available.
+pub const ERROR_INSTALL_SOURCE_ABSENT: u32 = 1612;
+/// This installation package cannot be installed by the Windows Installer service.
+pub const ERROR_INSTALL_PACKAGE_VERSION: u32 = 1613;
+/// The product is uninstalled.
+pub const ERROR_PRODUCT_UNINSTALLED: u32 = 1614;
+/// The SQL query syntax is invalid or unsupported.
+pub const ERROR_BAD_QUERY_SYNTAX: u32 = 1615;
+/// The record field does not exist.
+pub const ERROR_INVALID_FIELD: u32 = 1616;
+/// The device has been removed.
+pub const ERROR_DEVICE_REMOVED: u32 = 1617;
+/// Another installation is already in progress.
+pub const ERROR_INSTALL_ALREADY_RUNNING: u32 = 1618;
+/// This installation package could not be opened.
+pub const ERROR_INSTALL_PACKAGE_OPEN_FAILED: u32 = 1619;
+/// This installation package could not be opened because it is not valid.
+pub const ERROR_INSTALL_PACKAGE_INVALID: u32 = 1620;
+/// There was an error starting the Windows Installer service user interface.
+pub const ERROR_INSTALL_UI_FAILURE: u32 = 1621;
+/// There was an error opening the installation log file.
+pub const ERROR_INSTALL_LOG_FAILURE: u32 = 1622;
+/// This language of this installation package is not supported by your system.
+pub const ERROR_INSTALL_LANGUAGE_UNSUPPORTED: u32 = 1623;
+/// There was an error applying transforms.
+pub const ERROR_INSTALL_TRANSFORM_FAILURE: u32 = 1624;
+/// This installation is forbidden by system policy.
+pub const ERROR_INSTALL_PACKAGE_REJECTED: u32 = 1625;
+/// The function could not be executed. Returned from a custom action, the action was not executed.
+pub const ERROR_FUNCTION_NOT_CALLED: u32 = 1626;
+/// The function failed during execution.
+pub const ERROR_FUNCTION_FAILED: u32 = 1627;
+/// An invalid or unknown table was specified.
+pub const ERROR_INVALID_TABLE: u32 = 1628;
+/// The data supplied is the wrong type.
+pub const ERROR_DATATYPE_MISMATCH: u32 = 1629;
+/// Data of this type is not supported.
+pub const ERROR_UNSUPPORTED_TYPE: u32 = 1630;
+/// The Windows Installer service failed to start.
+pub const ERROR_CREATE_FAILED: u32 = 1631;
+/// The Temp folder is either full or inaccessible.
+pub const ERROR_INSTALL_TEMP_UNWRITABLE: u32 = 1632;
+/// This installation package is not supported on this platform.
+pub const ERROR_INSTALL_PLATFORM_UNSUPPORTED: u32 = 1633;
+/// Component is not used on this machine.
+pub const ERROR_INSTALL_NOTUSED: u32 = 1634;
+/// This patch package could not be opened.
+pub const ERROR_PATCH_PACKAGE_OPEN_FAILED: u32 = 1635;
+/// This patch package could not be opened because it is not valid.
+pub const ERROR_PATCH_PACKAGE_INVALID: u32 = 1636;
+/// This patch package cannot be processed by the Windows Installer service.
+pub const ERROR_PATCH_PACKAGE_UNSUPPORTED: u32 = 1637;
+/// Another version of this product is already installed.
+pub const ERROR_PRODUCT_VERSION: u32 = 1638;
+/// Invalid command line argument.
+pub const ERROR_INVALID_COMMAND_LINE: u32 = 1639;
+/// Installation from a Terminal Server client session is not permitted for the current user.
+pub const ERROR_INSTALL_REMOTE_DISALLOWED: u32 = 1640;
+/// The installer has initiated a restart.
+pub const ERROR_SUCCESS_REBOOT_INITIATED: u32 = 1641;
+/// The installer cannot install the upgrade patch because the program being upgraded may be missing or the upgrade patch updates a different version of the program.
+pub const ERROR_PATCH_TARGET_NOT_FOUND: u32 = 1642;
+/// The patch package is not permitted by system policy.
+pub const ERROR_PATCH_PACKAGE_REJECTED: u32 = 1643;
+/// One or more customizations are not permitted by system policy.
+pub const ERROR_INSTALL_TRANSFORM_REJECTED: u32 = 1644;
+/// Windows Installer does not permit installation from a Remote Desktop Connection.
+pub const ERROR_INSTALL_REMOTE_PROHIBITED: u32 = 1645;
+/// The patch package is not a removable patch package.
+pub const ERROR_PATCH_REMOVAL_UNSUPPORTED: u32 = 1646;
+/// The patch is not applied to this product.
+pub const ERROR_UNKNOWN_PATCH: u32 = 1647;
+/// No valid sequence could be found for the set of patches.
+pub const ERROR_PATCH_NO_SEQUENCE: u32 = 1648;
+/// Patch removal was disallowed by policy.
+pub const ERROR_PATCH_REMOVAL_DISALLOWED: u32 = 1649;
+/// The XML patch data is invalid.
+pub const ERROR_INVALID_PATCH_XML: u32 = 1650;
+/// Administrative user failed to apply patch for a per-user managed or a per-machine application that is in advertise state.
+pub const ERROR_PATCH_MANAGED_ADVERTISED_PRODUCT: u32 = 1651;
+/// Windows Installer is not accessible when the computer is in Safe Mode.
+pub const ERROR_INSTALL_SERVICE_SAFEBOOT: u32 = 1652;
+/// A restart is required to complete the install.
+pub const ERROR_SUCCESS_REBOOT_REQUIRED: u32 = 3010;
@@ -108,7 +108,8 @@ impl FromResidual for CustomActionResult {
             ffi::ERROR_INSTALL_USEREXIT => CustomActionResult::Cancel,
             ffi::ERROR_INSTALL_FAILURE => CustomActionResult::Failure,
             ffi::ERROR_FUNCTION_NOT_CALLED => CustomActionResult::NotExecuted,
-            code => panic!("unexpected error code {}", code),
+            // Panicking would unwind across the FFI boundary, so fail the installation instead.
+            _ => CustomActionResult::Failure,
         }
     }
 }
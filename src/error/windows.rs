@@ -0,0 +1,46 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(feature = "windows-result")]
+//! Conversions for errors from the [`windows`](https://crates.io/crates/windows) crates.
+//!
+//! `windows::core::Error` is the same type as `windows_result::Error`,
+//! so these conversions let custom actions built on the `windows` crate use `?` to propagate errors.
+
+use crate::Error;
+use windows_result::HRESULT;
+
+impl From<windows_result::Error> for Error {
+    fn from(error: windows_result::Error) -> Self {
+        let kind = Error::from_hresult(error.code().0).kind().clone();
+        Error::new(kind, error)
+    }
+}
+
+impl From<Error> for windows_result::Error {
+    fn from(error: Error) -> Self {
+        windows_result::Error::new(HRESULT(error.to_hresult()), error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_windows_error() {
+        let error: Error =
+            windows_result::Error::from_hresult(HRESULT(0x8007_0005_u32 as i32)).into();
+        assert_eq!(Some(5), error.code());
+
+        let error: Error =
+            windows_result::Error::from_hresult(HRESULT(0x8000_4001_u32 as i32)).into();
+        assert_eq!(Some(0x8000_4001), error.code());
+    }
+
+    #[test]
+    fn into_windows_error() {
+        let error: windows_result::Error = Error::from_error_code(1603).into();
+        assert_eq!(HRESULT(0x8007_0643_u32 as i32), error.code());
+    }
+}
@@ -3,122 +3,756 @@
 
 #![allow(clippy::upper_case_acronyms)]
 
-use crate::ModifyMode;
+use crate::{ColumnInfo, InstallLevel, InstallState, InstallType, ModifyMode};
 use crate::{MessageType, RunMode};
 use std::{
+    cell::Cell,
+    ffi::c_void,
     fmt::Display,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
     ops::{Deref, Not},
-    os::raw::c_char,
+    rc::Rc,
 };
 
-pub(crate) type LPSTR = *mut c_char;
-pub(crate) type LPCSTR = *const c_char;
+#[cfg(feature = "windows-sys")]
+mod abi;
+#[cfg(feature = "delay-load")]
+mod delay;
+#[cfg(feature = "test-util")]
+pub(crate) mod fault;
+#[cfg(feature = "mock")]
+mod mock;
+mod string;
+#[cfg(feature = "trace-ffi")]
+mod trace;
+#[cfg(any(feature = "delay-load", feature = "mock", feature = "test-util"))]
+mod unavailable;
 
-pub const ERROR_SUCCESS: u32 = 0;
-pub const ERROR_NO_MORE_ITEMS: u32 = 259;
-pub const ERROR_INSTALL_USEREXIT: u32 = 1602;
-pub const ERROR_INSTALL_FAILURE: u32 = 1603;
-pub const ERROR_FUNCTION_NOT_CALLED: u32 = 1626;
+pub(crate) use string::*;
+pub use string::{decoding, set_decoding, Decoding};
+
+pub(crate) type LPWSTR = *mut u16;
+pub(crate) type LPCWSTR = *const u16;
+
+/// A callback passed to `MsiSetExternalUIW` that receives messages from the installer as formatted strings.
+#[allow(non_camel_case_types)]
+pub(crate) type INSTALLUI_HANDLERW =
+    Option<unsafe extern "system" fn(*mut c_void, u32, LPCWSTR) -> i32>;
+
+/// A callback passed to `MsiSetExternalUIRecord` that receives messages from the installer as records.
+#[allow(non_camel_case_types)]
+pub(crate) type INSTALLUI_HANDLER_RECORD =
+    Option<unsafe extern "system" fn(*mut c_void, u32, u32) -> i32>;
+
+pub use crate::codes::*;
+
+/// Passed to `MsiOpenDatabaseW` in place of an output path to create a new database.
+pub(crate) const MSIDBOPEN_CREATE: LPCWSTR = 3 as LPCWSTR;
 
-pub(crate) const ERROR_MORE_DATA: u32 = 234;
 pub(crate) const MSI_NULL_INTEGER: i32 = -0x8000_0000;
 
+/// Marks types that own or use a Windows Installer handle as [`Send`] but not [`Sync`].
+///
+/// Windows Installer handles may be used from another thread, but not concurrently.
+pub(crate) type NotSync = PhantomData<Cell<()>>;
+
+/// Declares Windows Installer functions and generates wrappers of the same name
+/// through which all calls into `msi.dll` are made.
+macro_rules! extern_msi {
+    (
+        $(
+            $(#[link_name = $link_name:ident])?
+            pub fn $name:ident($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty;
+        )*
+    ) => {
+        #[cfg(not(any(feature = "delay-load", feature = "mock", feature = "windows-sys")))]
+        mod sys {
+            use super::*;
+
+            // raw-dylib does not require msi.lib, which may not be available when cross-compiling.
+            #[cfg_attr(feature = "raw-dylib", link(name = "msi", kind = "raw-dylib"))]
+            #[cfg_attr(not(feature = "raw-dylib"), link(name = "msi"))]
+            extern "C" {
+                $(
+                    $(#[link_name = stringify!($link_name)])?
+                    pub fn $name($($arg: $ty),*) -> $ret;
+                )*
+            }
+        }
+
+        #[cfg(all(feature = "windows-sys", not(any(feature = "delay-load", feature = "mock"))))]
+        mod sys {
+            use super::abi::{FromAbi, IntoAbi};
+            use super::*;
+            use windows_sys::Win32::System::ApplicationInstallationAndServicing as msi;
+
+            $(
+                #[allow(non_snake_case, clippy::too_many_arguments)]
+                #[inline]
+                pub unsafe fn $name($($arg: $ty),*) -> $ret {
+                    FromAbi::from_abi(extern_msi!(@sys msi $name $($link_name)?)($($arg.into_abi()),*))
+                }
+            )*
+        }
+
+        #[cfg(all(feature = "delay-load", not(feature = "mock")))]
+        mod sys {
+            use super::delay::Proc;
+            use super::unavailable::Unavailable;
+            use super::*;
+
+            $(
+                #[allow(non_snake_case, clippy::too_many_arguments)]
+                #[inline]
+                pub unsafe fn $name($($arg: $ty),*) -> $ret {
+                    static PROC: Proc = Proc::new(concat!(extern_msi!(@symbol $name $($link_name)?), "\0"));
+                    match PROC.address() {
+                        Some(address) => {
+                            let f: unsafe extern "system" fn($($ty),*) -> $ret = std::mem::transmute(address);
+                            f($($arg),*)
+                        }
+                        None => Unavailable::unavailable(),
+                    }
+                }
+            )*
+        }
+
+        // Functions not implemented by the mock backend return the same values as when msi.dll could not be loaded.
+        #[cfg(feature = "mock")]
+        mod stubs {
+            use super::unavailable::Unavailable;
+            use super::*;
+
+            $(
+                #[allow(non_snake_case, unused_variables, clippy::too_many_arguments)]
+                pub unsafe fn $name($($arg: $ty),*) -> $ret {
+                    Unavailable::unavailable()
+                }
+            )*
+        }
+
+        #[cfg(feature = "mock")]
+        use mock as sys;
+
+        $(
+            #[allow(non_snake_case, clippy::too_many_arguments)]
+            #[inline]
+            pub unsafe fn $name($($arg: $ty),*) -> $ret {
+                #[cfg(feature = "test-util")]
+                let ret = match fault::inject(stringify!($name)) {
+                    Some(code) => fault::Fault::fault(code),
+                    None => sys::$name($($arg),*),
+                };
+                #[cfg(not(feature = "test-util"))]
+                let ret = sys::$name($($arg),*);
+
+                #[cfg(feature = "trace-ffi")]
+                trace::call(stringify!($name), &[$((stringify!($arg), &$arg as &dyn trace::TraceArg)),*], &ret);
+
+                ret
+            }
+        )*
+    };
+    (@sys $module:ident $name:ident) => { $module::$name };
+    (@sys $module:ident $name:ident $link_name:ident) => { $module::$link_name };
+    (@symbol $name:ident) => { stringify!($name) };
+    (@symbol $name:ident $link_name:ident) => { stringify!($link_name) };
+}
+
 // cspell:ignore pcch
-#[link(name = "msi")]
-extern "C" {
+extern_msi! {
+    #[link_name = MsiAdvertiseProductExW]
+    pub fn MsiAdvertiseProductEx(
+        szPackagePath: LPCWSTR,
+        szScriptfilePath: LPCWSTR,
+        szTransforms: LPCWSTR,
+        lgidLanguage: u16,
+        dwPlatform: u32,
+        dwOptions: u32,
+    ) -> u32;
+
+    #[link_name = MsiApplyMultiplePatchesW]
+    pub fn MsiApplyMultiplePatches(
+        szPatchPackages: LPCWSTR,
+        szProductCode: LPCWSTR,
+        szPropertiesList: LPCWSTR,
+    ) -> u32;
+
+    pub fn MsiCloseAllHandles() -> u32;
+
     pub fn MsiCloseHandle(hAny: MSIHANDLE) -> u32;
 
-    pub fn MsiCreateRecord(cParams: u32) -> MSIHANDLE;
+    #[link_name = MsiCollectUserInfoW]
+    pub fn MsiCollectUserInfo(szProduct: LPCWSTR) -> u32;
 
-    #[link_name = "MsiDatabaseGetPrimaryKeysA"]
+    #[link_name = MsiConfigureFeatureW]
+    pub fn MsiConfigureFeature(
+        szProduct: LPCWSTR,
+        szFeature: LPCWSTR,
+        eInstallState: InstallState,
+    ) -> u32;
+
+    #[link_name = MsiConfigureProductExW]
+    pub fn MsiConfigureProductEx(
+        szProduct: LPCWSTR,
+        iInstallLevel: InstallLevel,
+        eInstallState: InstallState,
+        szCommandLine: LPCWSTR,
+    ) -> u32;
+
+    pub fn MsiCreateRecord(cParams: u32) -> RecordHandle;
+
+    pub fn MsiDatabaseCommit(hDatabase: DatabaseHandle) -> u32;
+
+    #[link_name = MsiDatabaseImportW]
+    pub fn MsiDatabaseImport(
+        hDatabase: DatabaseHandle,
+        szFolderPath: LPCWSTR,
+        szFileName: LPCWSTR,
+    ) -> u32;
+
+    #[link_name = MsiDatabaseGetPrimaryKeysW]
     pub fn MsiDatabaseGetPrimaryKeys(
-        hDatabase: MSIHANDLE,
-        szTableName: LPCSTR,
-        hRecord: &mut MSIHANDLE,
+        hDatabase: DatabaseHandle,
+        szTableName: LPCWSTR,
+        hRecord: &mut RecordHandle,
     ) -> u32;
 
-    #[link_name = "MsiDatabaseOpenViewA"]
+    #[link_name = MsiDatabaseOpenViewW]
     pub fn MsiDatabaseOpenView(
-        hDatabase: MSIHANDLE,
-        szQuery: LPCSTR,
-        phView: &mut MSIHANDLE,
+        hDatabase: DatabaseHandle,
+        szQuery: LPCWSTR,
+        phView: &mut ViewHandle,
+    ) -> u32;
+
+    #[link_name = MsiEnableLogW]
+    pub fn MsiEnableLog(dwLogMode: u32, szLogFile: LPCWSTR, dwLogAttributes: u32) -> u32;
+
+    pub fn MsiEnableUIPreview(hDatabase: DatabaseHandle, phPreview: &mut PreviewHandle) -> u32;
+
+    #[link_name = MsiDetermineApplicablePatchesW]
+    pub fn MsiDetermineApplicablePatches(
+        szProductPackagePath: LPCWSTR,
+        cPatchInfo: u32,
+        pPatchInfo: *mut MSIPATCHSEQUENCEINFOW,
+    ) -> u32;
+
+    #[link_name = MsiDeterminePatchSequenceW]
+    pub fn MsiDeterminePatchSequence(
+        szProductCode: LPCWSTR,
+        szUserSid: LPCWSTR,
+        dwContext: i32,
+        cPatchInfo: u32,
+        pPatchInfo: *mut MSIPATCHSEQUENCEINFOW,
+    ) -> u32;
+
+    #[link_name = MsiDoActionW]
+    pub fn MsiDoAction(hInstall: SessionHandle, szAction: LPCWSTR) -> u32;
+
+    #[link_name = MsiEnumProductsW]
+    pub fn MsiEnumProducts(iProductIndex: u32, lpProductBuf: LPWSTR) -> u32;
+
+    #[link_name = MsiEnumProductsExW]
+    pub fn MsiEnumProductsEx(
+        szProductCode: LPCWSTR,
+        szUserSid: LPCWSTR,
+        dwContext: u32,
+        dwIndex: u32,
+        szInstalledProductCode: LPWSTR,
+        pdwInstalledContext: *mut i32,
+        szSid: LPWSTR,
+        pcchSid: *mut u32,
+    ) -> u32;
+
+    #[link_name = MsiEnumClientsW]
+    pub fn MsiEnumClients(szComponent: LPCWSTR, iProductIndex: u32, lpProductBuf: LPWSTR) -> u32;
+
+    #[link_name = MsiEnumClientsExW]
+    pub fn MsiEnumClientsEx(
+        szComponent: LPCWSTR,
+        szUserSid: LPCWSTR,
+        dwContext: u32,
+        dwProductIndex: u32,
+        szProductBuf: LPWSTR,
+        pdwInstalledContext: *mut i32,
+        szSid: LPWSTR,
+        pcchSid: *mut u32,
+    ) -> u32;
+
+    #[link_name = MsiEnumComponentsW]
+    pub fn MsiEnumComponents(iComponentIndex: u32, lpComponentBuf: LPWSTR) -> u32;
+
+    #[link_name = MsiEnumComponentsExW]
+    pub fn MsiEnumComponentsEx(
+        szUserSid: LPCWSTR,
+        dwContext: u32,
+        dwIndex: u32,
+        szInstalledComponentCode: LPWSTR,
+        pdwInstalledContext: *mut i32,
+        szSid: LPWSTR,
+        pcchSid: *mut u32,
+    ) -> u32;
+
+    #[link_name = MsiEnumFeaturesW]
+    pub fn MsiEnumFeatures(
+        szProduct: LPCWSTR,
+        iFeatureIndex: u32,
+        lpFeatureBuf: LPWSTR,
+        lpParentBuf: LPWSTR,
+    ) -> u32;
+
+    #[link_name = MsiEnumPatchesExW]
+    pub fn MsiEnumPatchesEx(
+        szProductCode: LPCWSTR,
+        szUserSid: LPCWSTR,
+        dwContext: u32,
+        dwFilter: u32,
+        dwIndex: u32,
+        szPatchCode: LPWSTR,
+        szTargetProductCode: LPWSTR,
+        pdwTargetProductContext: *mut i32,
+        szTargetUserSid: LPWSTR,
+        pcchTargetUserSid: *mut u32,
+    ) -> u32;
+
+    pub fn MsiGetActiveDatabase(hInstall: SessionHandle) -> DatabaseHandle;
+
+    #[link_name = MsiGetFeatureInfoW]
+    pub fn MsiGetFeatureInfo(
+        hProduct: SessionHandle,
+        szFeature: LPCWSTR,
+        lpAttributes: *mut u32,
+        lpTitleBuf: LPWSTR,
+        pcchTitleBuf: *mut u32,
+        lpHelpBuf: LPWSTR,
+        pcchHelpBuf: *mut u32,
+    ) -> u32;
+
+    #[link_name = MsiGetFeatureStateW]
+    pub fn MsiGetFeatureState(
+        hInstall: SessionHandle,
+        szFeature: LPCWSTR,
+        piInstalled: *mut i32,
+        piAction: *mut i32,
+    ) -> u32;
+
+    pub fn MsiGetLanguage(hInstall: SessionHandle) -> u16;
+
+    pub fn MsiGetLastErrorRecord() -> RecordHandle;
+
+    pub fn MsiGetMode(hInstall: SessionHandle, eRunMode: RunMode) -> BOOL;
+
+    #[link_name = MsiGetProductCodeW]
+    pub fn MsiGetProductCode(szComponent: LPCWSTR, lpBuf39: LPWSTR) -> u32;
+
+    #[link_name = MsiGetProductInfoW]
+    pub fn MsiGetProductInfo(
+        szProduct: LPCWSTR,
+        szAttribute: LPCWSTR,
+        lpValueBuf: LPWSTR,
+        pcchValueBuf: *mut u32,
+    ) -> u32;
+
+    #[link_name = MsiGetProductInfoExW]
+    pub fn MsiGetProductInfoEx(
+        szProductCode: LPCWSTR,
+        szUserSid: LPCWSTR,
+        dwContext: i32,
+        szProperty: LPCWSTR,
+        szValue: LPWSTR,
+        pcchValue: *mut u32,
+    ) -> u32;
+
+    #[link_name = MsiGetPatchInfoExW]
+    pub fn MsiGetPatchInfoEx(
+        szPatchCode: LPCWSTR,
+        szProductCode: LPCWSTR,
+        szUserSid: LPCWSTR,
+        dwContext: i32,
+        szProperty: LPCWSTR,
+        lpValue: LPWSTR,
+        pcchValue: *mut u32,
+    ) -> u32;
+
+    #[link_name = MsiGetProductInfoFromScriptW]
+    pub fn MsiGetProductInfoFromScript(
+        szScriptFile: LPCWSTR,
+        lpProductBuf39: LPWSTR,
+        plgidLanguage: *mut u16,
+        pdwVersion: *mut u32,
+        lpNameBuf: LPWSTR,
+        pcchNameBuf: *mut u32,
+        lpPackageBuf: LPWSTR,
+        pcchPackageBuf: *mut u32,
+    ) -> u32;
+
+    #[link_name = MsiGetProductPropertyW]
+    pub fn MsiGetProductProperty(
+        hProduct: SessionHandle,
+        szProperty: LPCWSTR,
+        lpValueBuf: LPWSTR,
+        pcchValueBuf: *mut u32,
     ) -> u32;
 
-    #[link_name = "MsiDoActionA"]
-    pub fn MsiDoAction(hInstall: MSIHANDLE, szAction: LPCSTR) -> u32;
+    #[link_name = MsiGetShortcutTargetW]
+    pub fn MsiGetShortcutTarget(
+        szShortcutPath: LPCWSTR,
+        szProductCode: LPWSTR,
+        szFeatureId: LPWSTR,
+        szComponentCode: LPWSTR,
+    ) -> u32;
 
-    pub fn MsiGetActiveDatabase(hInstall: MSIHANDLE) -> MSIHANDLE;
+    #[link_name = MsiGetUserInfoW]
+    pub fn MsiGetUserInfo(
+        szProduct: LPCWSTR,
+        lpUserNameBuf: LPWSTR,
+        pcchUserNameBuf: *mut u32,
+        lpOrgNameBuf: LPWSTR,
+        pcchOrgNameBuf: *mut u32,
+        lpSerialBuf: LPWSTR,
+        pcchSerialBuf: *mut u32,
+    ) -> i32;
 
-    pub fn MsiGetLanguage(hInstall: MSIHANDLE) -> u16;
+    #[link_name = MsiGetSummaryInformationW]
+    pub fn MsiGetSummaryInformation(
+        hDatabase: DatabaseHandle,
+        szDatabasePath: LPCWSTR,
+        uiUpdateCount: u32,
+        phSummaryInfo: &mut SummaryInfoHandle,
+    ) -> u32;
 
-    pub fn MsiGetLastErrorRecord() -> MSIHANDLE;
+    #[link_name = MsiGetSourcePathW]
+    pub fn MsiGetSourcePath(
+        hInstall: SessionHandle,
+        szFolder: LPCWSTR,
+        szPathBuf: LPWSTR,
+        pcchPathBuf: *mut u32,
+    ) -> u32;
 
-    pub fn MsiGetMode(hInstall: MSIHANDLE, eRunMode: RunMode) -> BOOL;
+    #[link_name = MsiGetTargetPathW]
+    pub fn MsiGetTargetPath(
+        hInstall: SessionHandle,
+        szFolder: LPCWSTR,
+        szPathBuf: LPWSTR,
+        pcchPathBuf: *mut u32,
+    ) -> u32;
 
-    #[link_name = "MsiGetPropertyA"]
+    #[link_name = MsiGetPropertyW]
     pub fn MsiGetProperty(
-        hInstall: MSIHANDLE,
-        szName: LPCSTR,
-        szValueBuf: LPSTR,
+        hInstall: SessionHandle,
+        szName: LPCWSTR,
+        szValueBuf: LPWSTR,
         pcchValueBuf: *mut u32,
     ) -> u32;
 
-    #[link_name = "MsiFormatRecordA"]
+    #[link_name = MsiFormatRecordW]
     pub fn MsiFormatRecord(
-        hInstall: MSIHANDLE,
-        hRecord: MSIHANDLE,
-        szResultBuf: LPSTR,
+        hInstall: SessionHandle,
+        hRecord: RecordHandle,
+        szResultBuf: LPWSTR,
         pcchResultBuf: *mut u32,
     ) -> u32;
 
+    #[link_name = MsiInstallMissingComponentW]
+    pub fn MsiInstallMissingComponent(
+        szProduct: LPCWSTR,
+        szComponent: LPCWSTR,
+        eInstallState: InstallState,
+    ) -> u32;
+
+    #[link_name = MsiInstallMissingFileW]
+    pub fn MsiInstallMissingFile(szProduct: LPCWSTR, szFile: LPCWSTR) -> u32;
+
+    #[link_name = MsiInstallProductW]
+    pub fn MsiInstallProduct(szPackagePath: LPCWSTR, szCommandLine: LPCWSTR) -> u32;
+
+    #[link_name = MsiLocateComponentW]
+    pub fn MsiLocateComponent(szComponent: LPCWSTR, lpPathBuf: LPWSTR, pcchBuf: *mut u32) -> i32;
+
+    #[link_name = MsiOpenDatabaseW]
+    pub fn MsiOpenDatabase(
+        szDatabasePath: LPCWSTR,
+        szPersist: LPCWSTR,
+        phDatabase: &mut DatabaseHandle,
+    ) -> u32;
+
+    #[link_name = MsiOpenPackageExW]
+    pub fn MsiOpenPackageEx(
+        szPackagePath: LPCWSTR,
+        dwOptions: u32,
+        hProduct: &mut SessionHandle,
+    ) -> u32;
+
+    #[link_name = MsiOpenProductW]
+    pub fn MsiOpenProduct(szProduct: LPCWSTR, hProduct: &mut SessionHandle) -> u32;
+
+    #[link_name = MsiPreviewBillboardW]
+    pub fn MsiPreviewBillboard(
+        hPreview: PreviewHandle,
+        szControlName: LPCWSTR,
+        szBillboard: LPCWSTR,
+    ) -> u32;
+
+    #[link_name = MsiPreviewDialogW]
+    pub fn MsiPreviewDialog(hPreview: PreviewHandle, szDialogName: LPCWSTR) -> u32;
+
     pub fn MsiProcessMessage(
-        hInstall: MSIHANDLE,
+        hInstall: SessionHandle,
         eMessageType: MessageType,
-        hRecord: MSIHANDLE,
+        hRecord: RecordHandle,
     ) -> i32;
 
-    pub fn MsiRecordGetFieldCount(hRecord: MSIHANDLE) -> u32;
+    #[link_name = MsiProvideComponentW]
+    pub fn MsiProvideComponent(
+        szProduct: LPCWSTR,
+        szFeature: LPCWSTR,
+        szComponent: LPCWSTR,
+        dwInstallMode: u32,
+        lpPathBuf: LPWSTR,
+        pcchPathBuf: *mut u32,
+    ) -> u32;
 
-    pub fn MsiRecordGetInteger(hRecord: MSIHANDLE, iField: u32) -> i32;
+    #[link_name = MsiProvideQualifiedComponentW]
+    pub fn MsiProvideQualifiedComponent(
+        szCategory: LPCWSTR,
+        szQualifier: LPCWSTR,
+        dwInstallMode: u32,
+        lpPathBuf: LPWSTR,
+        pcchPathBuf: *mut u32,
+    ) -> u32;
 
-    #[link_name = "MsiRecordGetStringA"]
+    #[link_name = MsiProvideQualifiedComponentExW]
+    pub fn MsiProvideQualifiedComponentEx(
+        szCategory: LPCWSTR,
+        szQualifier: LPCWSTR,
+        dwInstallMode: u32,
+        szProduct: LPCWSTR,
+        dwUnused1: u32,
+        dwUnused2: u32,
+        lpPathBuf: LPWSTR,
+        pcchPathBuf: *mut u32,
+    ) -> u32;
+
+    #[link_name = MsiQueryComponentStateW]
+    pub fn MsiQueryComponentState(
+        szProductCode: LPCWSTR,
+        szUserSid: LPCWSTR,
+        dwContext: i32,
+        szComponentCode: LPCWSTR,
+        pdwState: *mut i32,
+    ) -> u32;
+
+    #[link_name = MsiQueryFeatureStateW]
+    pub fn MsiQueryFeatureState(szProduct: LPCWSTR, szFeature: LPCWSTR) -> i32;
+
+    #[link_name = MsiQueryFeatureStateExW]
+    pub fn MsiQueryFeatureStateEx(
+        szProductCode: LPCWSTR,
+        szUserSid: LPCWSTR,
+        dwContext: i32,
+        szFeature: LPCWSTR,
+        pdwState: *mut i32,
+    ) -> u32;
+
+    pub fn MsiRecordGetFieldCount(hRecord: RecordHandle) -> u32;
+
+    pub fn MsiRecordGetInteger(hRecord: RecordHandle, iField: u32) -> i32;
+
+    #[link_name = MsiRecordGetStringW]
     pub fn MsiRecordGetString(
-        hRecord: MSIHANDLE,
+        hRecord: RecordHandle,
         iField: u32,
-        szValueBuf: LPSTR,
+        szValueBuf: LPWSTR,
         pcchValueBuf: *mut u32,
     ) -> u32;
 
-    pub fn MsiRecordIsNull(hRecord: MSIHANDLE, iField: u32) -> BOOL;
+    pub fn MsiRecordIsNull(hRecord: RecordHandle, iField: u32) -> BOOL;
+
+    pub fn MsiRecordSetInteger(hRecord: RecordHandle, iField: u32, iValue: i32) -> u32;
+
+    #[link_name = MsiRecordSetStringW]
+    pub fn MsiRecordSetString(hRecord: RecordHandle, iField: u32, szValue: LPCWSTR) -> u32;
+
+    #[link_name = MsiRecordSetStreamW]
+    pub fn MsiRecordSetStream(hRecord: RecordHandle, iField: u32, szFilePath: LPCWSTR) -> u32;
+
+    #[link_name = MsiReinstallFeatureW]
+    pub fn MsiReinstallFeature(szProduct: LPCWSTR, szFeature: LPCWSTR, dwReinstallMode: u32) -> u32;
+
+    #[link_name = MsiReinstallProductW]
+    pub fn MsiReinstallProduct(szProduct: LPCWSTR, szReinstallMode: u32) -> u32;
+
+    #[link_name = MsiRemovePatchesW]
+    pub fn MsiRemovePatches(
+        szPatchList: LPCWSTR,
+        szProductCode: LPCWSTR,
+        eUninstallType: InstallType,
+        szPropertyList: LPCWSTR,
+    ) -> u32;
+
+    #[link_name = MsiSourceListAddMediaDiskW]
+    pub fn MsiSourceListAddMediaDisk(
+        szProductCodeOrPatchCode: LPCWSTR,
+        szUserSid: LPCWSTR,
+        dwContext: i32,
+        dwOptions: u32,
+        dwDiskId: u32,
+        szVolumeLabel: LPCWSTR,
+        szDiskPrompt: LPCWSTR,
+    ) -> u32;
+
+    #[link_name = MsiSourceListClearMediaDiskW]
+    pub fn MsiSourceListClearMediaDisk(
+        szProductCodeOrPatchCode: LPCWSTR,
+        szUserSid: LPCWSTR,
+        dwContext: i32,
+        dwOptions: u32,
+        dwDiskId: u32,
+    ) -> u32;
+
+    #[link_name = MsiSourceListEnumMediaDisksW]
+    pub fn MsiSourceListEnumMediaDisks(
+        szProductCodeOrPatchCode: LPCWSTR,
+        szUserSid: LPCWSTR,
+        dwContext: i32,
+        dwOptions: u32,
+        dwIndex: u32,
+        pdwDiskId: *mut u32,
+        szVolumeLabel: LPWSTR,
+        pcchVolumeLabel: *mut u32,
+        szDiskPrompt: LPWSTR,
+        pcchDiskPrompt: *mut u32,
+    ) -> u32;
+
+    #[link_name = MsiSourceListForceResolutionExW]
+    pub fn MsiSourceListForceResolutionEx(
+        szProductCodeOrPatchCode: LPCWSTR,
+        szUserSid: LPCWSTR,
+        dwContext: i32,
+        dwOptions: u32,
+    ) -> u32;
+
+    #[link_name = MsiSetExternalUIW]
+    pub fn MsiSetExternalUI(
+        puiHandler: INSTALLUI_HANDLERW,
+        dwMessageFilter: u32,
+        pvContext: *const c_void,
+    ) -> INSTALLUI_HANDLERW;
+
+    pub fn MsiSetExternalUIRecord(
+        puiHandler: INSTALLUI_HANDLER_RECORD,
+        dwMessageFilter: u32,
+        pvContext: *const c_void,
+        ppuiPrevHandler: *mut INSTALLUI_HANDLER_RECORD,
+    ) -> u32;
+
+    #[link_name = MsiSetFeatureAttributesW]
+    pub fn MsiSetFeatureAttributes(
+        hInstall: SessionHandle,
+        szFeature: LPCWSTR,
+        dwAttributes: u32,
+    ) -> u32;
+
+    pub fn MsiSetInternalUI(dwUILevel: i32, phWnd: *mut *mut c_void) -> i32;
+
+    #[link_name = MsiSetPropertyW]
+    pub fn MsiSetProperty(hInstall: SessionHandle, szName: LPCWSTR, szValue: LPCWSTR) -> u32;
+
+    #[link_name = MsiSetTargetPathW]
+    pub fn MsiSetTargetPath(hInstall: SessionHandle, szFolder: LPCWSTR, szFolderPath: LPCWSTR)
+        -> u32;
+
+    pub fn MsiSummaryInfoPersist(hSummaryInfo: SummaryInfoHandle) -> u32;
+
+    #[link_name = MsiSummaryInfoSetPropertyW]
+    pub fn MsiSummaryInfoSetProperty(
+        hSummaryInfo: SummaryInfoHandle,
+        uiProperty: u32,
+        uiDataType: u32,
+        iValue: i32,
+        pftValue: *mut FILETIME,
+        szValue: LPCWSTR,
+    ) -> u32;
+
+    #[link_name = MsiUseFeatureExW]
+    pub fn MsiUseFeatureEx(
+        szProduct: LPCWSTR,
+        szFeature: LPCWSTR,
+        dwInstallMode: u32,
+        dwReserved: u32,
+    ) -> i32;
 
-    pub fn MsiRecordSetInteger(hRecord: MSIHANDLE, iField: u32, iValue: i32) -> u32;
+    pub fn MsiViewClose(hView: ViewHandle) -> u32;
 
-    #[link_name = "MsiRecordSetStringA"]
-    pub fn MsiRecordSetString(hRecord: MSIHANDLE, iField: u32, szValue: LPCSTR) -> u32;
+    pub fn MsiViewGetColumnInfo(
+        hView: ViewHandle,
+        eColumnInfo: ColumnInfo,
+        phRecord: &mut RecordHandle,
+    ) -> u32;
 
-    #[link_name = "MsiSetPropertyA"]
-    pub fn MsiSetProperty(hInstall: MSIHANDLE, szName: LPCSTR, szValue: LPCSTR) -> u32;
+    pub fn MsiViewExecute(hView: ViewHandle, hRecord: RecordHandle) -> u32;
 
-    pub fn MsiViewClose(hView: MSIHANDLE) -> u32;
+    pub fn MsiViewFetch(hView: ViewHandle, phRecord: &mut RecordHandle) -> u32;
 
-    pub fn MsiViewExecute(hView: MSIHANDLE, hRecord: MSIHANDLE) -> u32;
+    pub fn MsiViewModify(hView: ViewHandle, eModifyMode: ModifyMode, hRecord: RecordHandle) -> u32;
+}
 
-    pub fn MsiViewFetch(hView: MSIHANDLE, phRecord: &mut MSIHANDLE) -> u32;
+/// Patch data passed to and ordered by `MsiDetermineApplicablePatches` and `MsiDeterminePatchSequence`.
+#[allow(non_snake_case)]
+#[derive(Debug)]
+#[repr(C)]
+pub(crate) struct MSIPATCHSEQUENCEINFOW {
+    pub szPatchData: LPCWSTR,
+    pub ePatchDataType: i32,
+    pub dwOrder: u32,
+    pub uStatus: u32,
+}
 
-    pub fn MsiViewModify(hView: MSIHANDLE, eModifyMode: ModifyMode, hRecord: MSIHANDLE) -> u32;
+/// A Windows `FILETIME` used for date properties in the summary information stream.
+#[allow(non_snake_case)]
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub(crate) struct FILETIME {
+    pub dwLowDateTime: u32,
+    pub dwHighDateTime: u32,
 }
 
+/// A Windows `BOOL` returned by some Windows Installer functions, where any non-zero value is `true`.
+///
+/// Values compare and hash by whether they are `true` or `false`, not by their raw value.
 #[derive(Copy, Clone, Debug, Default)]
 #[repr(transparent)]
 pub struct BOOL(i32);
 
 impl BOOL {
+    /// Gets whether the value is `true`.
     #[inline]
     pub fn as_bool(self) -> bool {
         self.0 != 0
     }
 }
 
+impl From<BOOL> for bool {
+    fn from(value: BOOL) -> Self {
+        value.as_bool()
+    }
+}
+
+impl PartialEq for BOOL {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bool() == other.as_bool()
+    }
+}
+
+impl Eq for BOOL {}
+
+impl Hash for BOOL {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bool().hash(state);
+    }
+}
+
 impl Display for BOOL {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self.0 {
@@ -154,20 +788,21 @@ impl PartialEq<bool> for BOOL {
     }
 }
 
-/// A Windows Installer handle. This handle is not automatically closed.
+/// A raw Windows Installer handle. This handle is not automatically closed.
+///
+/// Use `from_raw()`, `into_raw()`, and `as_raw()` on [`Session`](crate::Session), [`Database`](crate::Database),
+/// [`View`](crate::View), and [`Record`](crate::Record) to interoperate with other code using Windows Installer handles.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(transparent)]
 pub struct MSIHANDLE(u32);
 
 impl MSIHANDLE {
+    /// Gets a null handle.
     pub fn null() -> MSIHANDLE {
         MSIHANDLE(0)
     }
 
-    pub fn to_owned(self) -> PMSIHANDLE {
-        PMSIHANDLE { h: self }
-    }
-
+    /// Gets whether the handle is null.
     pub fn is_null(&self) -> bool {
         self.0 == 0
     }
@@ -193,34 +828,189 @@ impl Deref for MSIHANDLE {
     }
 }
 
-/// A Windows Installer handle. This handle is automatically closed when dropped.
+/// A Windows Installer handle to a specific kind of object. This handle is not automatically closed.
+///
+/// Distinct handle types e.g., [`RecordHandle`] and [`ViewHandle`] prevent passing one kind of handle
+/// where another is expected.
 #[derive(Debug, Eq, PartialEq)]
-pub struct PMSIHANDLE {
+#[repr(transparent)]
+pub(crate) struct Handle<K> {
     h: MSIHANDLE,
+    _kind: PhantomData<K>,
+}
+
+// Derived implementations would require K: Clone + Copy.
+impl<K> Clone for Handle<K> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K> Copy for Handle<K> {}
+
+impl<K> Handle<K> {
+    pub(crate) fn null() -> Self {
+        Self::from_raw(MSIHANDLE::null())
+    }
+
+    pub(crate) fn from_raw(h: MSIHANDLE) -> Self {
+        Handle {
+            h,
+            _kind: PhantomData,
+        }
+    }
+
+    #[track_caller]
+    pub(crate) fn to_owned(self) -> PMSIHANDLE<K> {
+        #[cfg(feature = "debug-handles")]
+        crate::handles::opened(self.h, std::panic::Location::caller());
+
+        PMSIHANDLE {
+            h: self,
+            _marker: PhantomData,
+        }
+    }
 }
 
-impl Display for PMSIHANDLE {
+impl<K> Display for Handle<K> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "MSIHANDLE ({})", *self.h)
+        self.h.fmt(f)
     }
 }
 
-impl Drop for PMSIHANDLE {
+impl<K> Deref for Handle<K> {
+    type Target = MSIHANDLE;
+
+    fn deref(&self) -> &Self::Target {
+        &self.h
+    }
+}
+
+/// Kinds of objects referenced by a [`Handle`].
+pub(crate) mod kind {
+    #[derive(Debug, Eq, PartialEq)]
+    pub(crate) enum Session {}
+
+    #[derive(Debug, Eq, PartialEq)]
+    pub(crate) enum Database {}
+
+    #[derive(Debug, Eq, PartialEq)]
+    pub(crate) enum View {}
+
+    #[derive(Debug, Eq, PartialEq)]
+    pub(crate) enum Record {}
+
+    #[derive(Debug, Eq, PartialEq)]
+    pub(crate) enum Preview {}
+
+    #[derive(Debug, Eq, PartialEq)]
+    pub(crate) enum SummaryInfo {}
+}
+
+pub(crate) type SessionHandle = Handle<kind::Session>;
+pub(crate) type DatabaseHandle = Handle<kind::Database>;
+pub(crate) type ViewHandle = Handle<kind::View>;
+pub(crate) type RecordHandle = Handle<kind::Record>;
+pub(crate) type PreviewHandle = Handle<kind::Preview>;
+pub(crate) type SummaryInfoHandle = Handle<kind::SummaryInfo>;
+
+/// A Windows Installer handle. This handle is automatically closed when dropped.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct PMSIHANDLE<K> {
+    h: Handle<K>,
+    _marker: NotSync,
+}
+
+impl<K> PMSIHANDLE<K> {
+    /// Releases ownership of the handle without closing it.
+    pub(crate) fn into_raw(self) -> MSIHANDLE {
+        let h = self.h.h;
+        std::mem::forget(self);
+
+        #[cfg(feature = "debug-handles")]
+        crate::handles::closed(h);
+
+        h
+    }
+}
+
+impl<K> Display for PMSIHANDLE<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.h.fmt(f)
+    }
+}
+
+impl<K> Drop for PMSIHANDLE<K> {
     fn drop(&mut self) {
         unsafe {
-            MsiCloseHandle(**self);
+            MsiCloseHandle(self.h.h);
         }
+
+        #[cfg(feature = "debug-handles")]
+        crate::handles::closed(self.h.h);
     }
 }
 
-impl Deref for PMSIHANDLE {
-    type Target = MSIHANDLE;
+impl<K> Deref for PMSIHANDLE<K> {
+    type Target = Handle<K>;
 
     fn deref(&self) -> &Self::Target {
         &self.h
     }
 }
 
+/// A reference-counted Windows Installer handle. This handle is automatically closed when the last clone is dropped,
+/// unless it was borrowed from the installer.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum SharedHandle<K> {
+    Owned(Rc<PMSIHANDLE<K>>),
+    Borrowed(Handle<K>),
+}
+
+impl<K> SharedHandle<K> {
+    /// Wraps a handle owned by the installer e.g., passed to a callback, which is never closed by any clone.
+    pub(crate) fn borrowed(h: Handle<K>) -> Self {
+        SharedHandle::Borrowed(h)
+    }
+
+    /// Releases ownership of the handle without closing it if this is the only reference,
+    /// or returns the [`SharedHandle`] otherwise or if the handle is borrowed.
+    pub(crate) fn try_into_raw(self) -> Result<MSIHANDLE, Self> {
+        match self {
+            SharedHandle::Owned(h) => Rc::try_unwrap(h)
+                .map(PMSIHANDLE::into_raw)
+                .map_err(SharedHandle::Owned),
+            SharedHandle::Borrowed(_) => Err(self),
+        }
+    }
+}
+
+impl<K> Clone for SharedHandle<K> {
+    fn clone(&self) -> Self {
+        match self {
+            SharedHandle::Owned(h) => SharedHandle::Owned(Rc::clone(h)),
+            SharedHandle::Borrowed(h) => SharedHandle::Borrowed(*h),
+        }
+    }
+}
+
+impl<K> From<PMSIHANDLE<K>> for SharedHandle<K> {
+    fn from(h: PMSIHANDLE<K>) -> Self {
+        SharedHandle::Owned(Rc::new(h))
+    }
+}
+
+impl<K> Deref for SharedHandle<K> {
+    type Target = Handle<K>;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            SharedHandle::Owned(h) => h,
+            SharedHandle::Borrowed(h) => h,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -229,4 +1019,11 @@ mod tests {
     fn is_null() {
         assert!(MSIHANDLE::null().is_null());
     }
+
+    #[test]
+    fn bool_eq() {
+        assert_eq!(BOOL(1), BOOL(-1));
+        assert_ne!(BOOL(0), BOOL(1));
+        assert!(bool::from(!BOOL(0)));
+    }
 }
@@ -0,0 +1,143 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Conversions between the types declared in `extern_msi!` and those used by `windows-sys`.
+
+use super::*;
+use std::ffi::c_void;
+
+/// Converts a parameter to the type expected by `windows-sys`.
+pub(crate) trait IntoAbi {
+    type Abi;
+    fn into_abi(self) -> Self::Abi;
+}
+
+/// Converts a return value from the type returned by `windows-sys`.
+pub(crate) trait FromAbi {
+    type Abi;
+    fn from_abi(abi: Self::Abi) -> Self;
+}
+
+macro_rules! abi_identity {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoAbi for $ty {
+                type Abi = $ty;
+                fn into_abi(self) -> Self::Abi {
+                    self
+                }
+            }
+
+            impl FromAbi for $ty {
+                type Abi = $ty;
+                fn from_abi(abi: Self::Abi) -> Self {
+                    abi
+                }
+            }
+        )*
+    };
+}
+
+abi_identity!(
+    u16,
+    u32,
+    i32,
+    LPCWSTR,
+    LPWSTR,
+    *mut u32,
+    *mut i32,
+    *mut *mut c_void,
+    *const c_void,
+    INSTALLUI_HANDLERW,
+    INSTALLUI_HANDLER_RECORD
+);
+
+macro_rules! abi_enum {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoAbi for $ty {
+                type Abi = i32;
+                fn into_abi(self) -> Self::Abi {
+                    u32::from(self) as i32
+                }
+            }
+        )*
+    };
+}
+
+abi_enum!(ColumnInfo, InstallType, MessageType, ModifyMode, RunMode);
+
+impl IntoAbi for InstallLevel {
+    type Abi = i32;
+    fn into_abi(self) -> Self::Abi {
+        i32::from(self)
+    }
+}
+
+impl IntoAbi for InstallState {
+    type Abi = i32;
+    fn into_abi(self) -> Self::Abi {
+        i32::from(self)
+    }
+}
+
+impl IntoAbi for MSIHANDLE {
+    type Abi = u32;
+    fn into_abi(self) -> Self::Abi {
+        *self
+    }
+}
+
+impl<K> IntoAbi for Handle<K> {
+    type Abi = u32;
+    fn into_abi(self) -> Self::Abi {
+        **self
+    }
+}
+
+impl<K> FromAbi for Handle<K> {
+    type Abi = u32;
+    fn from_abi(abi: Self::Abi) -> Self {
+        Handle::from_raw(MSIHANDLE::from(abi))
+    }
+}
+
+impl<K> IntoAbi for &mut Handle<K> {
+    type Abi = *mut u32;
+    fn into_abi(self) -> Self::Abi {
+        // Handle and MSIHANDLE are repr(transparent) over u32.
+        self as *mut Handle<K> as *mut u32
+    }
+}
+
+impl FromAbi for BOOL {
+    type Abi = windows_sys::core::BOOL;
+    fn from_abi(abi: Self::Abi) -> Self {
+        BOOL::from(abi != 0)
+    }
+}
+
+impl IntoAbi for *mut MSIPATCHSEQUENCEINFOW {
+    type Abi =
+        *mut windows_sys::Win32::System::ApplicationInstallationAndServicing::MSIPATCHSEQUENCEINFOW;
+    fn into_abi(self) -> Self::Abi {
+        // The layout is the same as the windows-sys declaration.
+        self.cast()
+    }
+}
+
+impl IntoAbi for *mut FILETIME {
+    type Abi = *mut windows_sys::Win32::Foundation::FILETIME;
+    fn into_abi(self) -> Self::Abi {
+        // The layout is the same as the windows-sys declaration.
+        self.cast()
+    }
+}
+
+impl IntoAbi for *mut INSTALLUI_HANDLER_RECORD {
+    type Abi = INSTALLUI_HANDLER_RECORD;
+    fn into_abi(self) -> Self::Abi {
+        // windows-sys declares the out parameter as the handler itself instead of a pointer to it.
+        unsafe { std::mem::transmute::<*mut c_void, INSTALLUI_HANDLER_RECORD>(self.cast()) }
+    }
+}
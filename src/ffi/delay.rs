@@ -0,0 +1,66 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Resolves Windows Installer functions from `msi.dll` when first called instead of linking to `msi.lib`.
+
+use super::*;
+use std::{ffi::c_void, ptr::NonNull, sync::OnceLock};
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn LoadLibraryExW(lpLibFileName: LPCWSTR, hFile: *mut c_void, dwFlags: u32) -> *mut c_void;
+    fn GetProcAddress(hModule: *mut c_void, lpProcName: *const u8) -> *mut c_void;
+}
+
+const LOAD_LIBRARY_SEARCH_SYSTEM32: u32 = 0x0000_0800;
+
+/// The address of a module or function, which is never unloaded.
+#[derive(Clone, Copy)]
+struct Address(NonNull<c_void>);
+
+// SAFETY: The address is only read after it is resolved and msi.dll is never unloaded.
+unsafe impl Send for Address {}
+unsafe impl Sync for Address {}
+
+fn module() -> Option<Address> {
+    static MODULE: OnceLock<Option<Address>> = OnceLock::new();
+    *MODULE.get_or_init(|| {
+        // Only load msi.dll from the system directory to avoid planting attacks.
+        let name: Vec<u16> = "msi.dll\0".encode_utf16().collect();
+        let h = unsafe {
+            LoadLibraryExW(
+                name.as_ptr(),
+                std::ptr::null_mut(),
+                LOAD_LIBRARY_SEARCH_SYSTEM32,
+            )
+        };
+        NonNull::new(h).map(Address)
+    })
+}
+
+/// A function in `msi.dll` resolved when first called.
+pub(crate) struct Proc {
+    name: &'static str,
+    address: OnceLock<Option<Address>>,
+}
+
+impl Proc {
+    /// Creates a [`Proc`] for a null-terminated function name.
+    pub(crate) const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            address: OnceLock::new(),
+        }
+    }
+
+    /// Gets the address of the function, or `None` if `msi.dll` or the function could not be loaded.
+    pub(crate) fn address(&self) -> Option<*mut c_void> {
+        self.address
+            .get_or_init(|| {
+                let module = module()?;
+                let h = unsafe { GetProcAddress(module.0.as_ptr(), self.name.as_ptr()) };
+                NonNull::new(h).map(Address)
+            })
+            .map(|address| address.0.as_ptr())
+    }
+}
@@ -0,0 +1,106 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Makes calls into `msi.dll` fail with chosen error codes to test error handling.
+
+use super::unavailable::Unavailable;
+use super::*;
+use std::{cell::RefCell, collections::HashMap};
+
+/// The value returned by a function in place of calling it when a fault is injected.
+///
+/// Functions that return an error code return the injected code, and all other functions return the same value
+/// as when `msi.dll` could not be loaded e.g., a null handle.
+pub(crate) trait Fault: Unavailable + Sized {
+    fn fault(_code: u32) -> Self {
+        Self::unavailable()
+    }
+}
+
+impl Fault for u32 {
+    fn fault(code: u32) -> Self {
+        code
+    }
+}
+
+impl Fault for i32 {}
+impl Fault for u16 {}
+impl Fault for BOOL {}
+impl Fault for INSTALLUI_HANDLERW {}
+impl<K> Fault for Handle<K> {}
+
+#[derive(Debug)]
+struct Rule {
+    function: String,
+    call: Option<usize>,
+    code: u32,
+}
+
+#[derive(Debug, Default)]
+struct Faults {
+    rules: Vec<Rule>,
+    calls: HashMap<&'static str, usize>,
+}
+
+thread_local! {
+    static FAULTS: RefCell<Option<Faults>> = const { RefCell::new(None) };
+}
+
+/// Starts counting calls on the current thread. Returns false if faults were already started.
+pub(crate) fn start() -> bool {
+    FAULTS.with_borrow_mut(|faults| match faults {
+        Some(_) => false,
+        None => {
+            *faults = Some(Faults::default());
+            true
+        }
+    })
+}
+
+/// Stops counting calls and removes all faults on the current thread.
+pub(crate) fn stop() {
+    FAULTS.set(None);
+}
+
+/// Fails the `call`th call, or every call if `None`, to `function` with `code`.
+pub(crate) fn add(function: &str, call: Option<usize>, code: u32) {
+    FAULTS.with_borrow_mut(|faults| {
+        if let Some(faults) = faults {
+            faults.rules.push(Rule {
+                function: normalize(function).to_owned(),
+                call,
+                code,
+            });
+        }
+    })
+}
+
+/// Gets the number of calls to `function` since faults were started.
+pub(crate) fn calls(function: &str) -> usize {
+    FAULTS.with_borrow(|faults| {
+        faults
+            .as_ref()
+            .and_then(|faults| faults.calls.get(normalize(function)).copied())
+            .unwrap_or_default()
+    })
+}
+
+/// Counts a call to `function` and gets the error code to return in place of calling it, if any.
+pub(crate) fn inject(function: &'static str) -> Option<u32> {
+    FAULTS.with_borrow_mut(|faults| {
+        let faults = faults.as_mut()?;
+        let call = faults.calls.entry(function).or_default();
+        *call += 1;
+        let call = *call;
+        faults
+            .rules
+            .iter()
+            .find(|rule| rule.function == function && rule.call.is_none_or(|n| n == call))
+            .map(|rule| rule.code)
+    })
+}
+
+/// Functions are declared without the `W` suffix of their wide-character symbols.
+fn normalize(function: &str) -> &str {
+    function.strip_suffix('W').unwrap_or(function)
+}
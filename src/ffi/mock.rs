@@ -0,0 +1,303 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Implements record functions in memory instead of calling `msi.dll`, so code using records can be tested on any platform.
+//!
+//! All other functions return the same values as when `msi.dll` could not be loaded e.g., `ERROR_CALL_NOT_IMPLEMENTED`.
+
+#![allow(non_snake_case)]
+
+pub(crate) use super::stubs::*;
+
+use super::{
+    RecordHandle, SessionHandle, BOOL, ERROR_INVALID_HANDLE, ERROR_INVALID_PARAMETER,
+    ERROR_MORE_DATA, ERROR_SUCCESS, LPCWSTR, LPWSTR, MSIHANDLE, MSI_NULL_INTEGER,
+};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Mutex, MutexGuard, PoisonError,
+    },
+    thread::{self, ThreadId},
+};
+
+#[derive(Clone, Debug)]
+enum Value {
+    Null,
+    Integer(i32),
+    String(Vec<u16>),
+}
+
+impl Value {
+    fn is_null(&self) -> bool {
+        match self {
+            Value::Null => true,
+            Value::Integer(_) => false,
+            Value::String(s) => s.is_empty(),
+        }
+    }
+
+    fn to_wide(&self) -> Vec<u16> {
+        match self {
+            Value::Null => Vec::new(),
+            Value::Integer(i) => i.to_string().encode_utf16().collect(),
+            Value::String(s) => s.clone(),
+        }
+    }
+}
+
+/// A record and the thread that created it, which closes it in [`MsiCloseAllHandles`].
+struct MockRecord {
+    thread: ThreadId,
+    fields: Vec<Value>,
+}
+
+static RECORDS: Mutex<BTreeMap<u32, MockRecord>> = Mutex::new(BTreeMap::new());
+static NEXT_HANDLE: AtomicU32 = AtomicU32::new(1);
+
+fn records() -> MutexGuard<'static, BTreeMap<u32, MockRecord>> {
+    // Records are only modified while the lock is held, so they are consistent even if another thread panicked.
+    RECORDS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Calls `f` with the fields of a record, or returns `invalid` if the handle is not an open record.
+fn with_fields<T>(h: RecordHandle, invalid: T, f: impl FnOnce(&mut Vec<Value>) -> T) -> T {
+    match records().get_mut(&**h) {
+        Some(record) => f(&mut record.fields),
+        None => invalid,
+    }
+}
+
+/// Copies a value to a buffer using the same protocol as `msi.dll`.
+unsafe fn copy_to(value: &[u16], buf: LPWSTR, pcch: *mut u32) -> u32 {
+    if pcch.is_null() {
+        return ERROR_INVALID_PARAMETER;
+    }
+
+    let len = *pcch as usize;
+    *pcch = value.len() as u32;
+    if buf.is_null() {
+        return ERROR_SUCCESS;
+    }
+    if value.len() >= len {
+        if len > 0 {
+            *buf = 0;
+        }
+        return ERROR_MORE_DATA;
+    }
+
+    std::ptr::copy_nonoverlapping(value.as_ptr(), buf, value.len());
+    *buf.add(value.len()) = 0;
+    ERROR_SUCCESS
+}
+
+pub(crate) unsafe fn MsiCreateRecord(cParams: u32) -> RecordHandle {
+    let h = NEXT_HANDLE.fetch_add(1, Ordering::Relaxed);
+    let record = MockRecord {
+        thread: thread::current().id(),
+        fields: vec![Value::Null; cParams as usize + 1],
+    };
+    records().insert(h, record);
+    RecordHandle::from_raw(MSIHANDLE::from(h))
+}
+
+pub(crate) unsafe fn MsiCloseHandle(hAny: MSIHANDLE) -> u32 {
+    if hAny.is_null() || records().remove(&*hAny).is_some() {
+        return ERROR_SUCCESS;
+    }
+    ERROR_INVALID_HANDLE
+}
+
+pub(crate) unsafe fn MsiCloseAllHandles() -> u32 {
+    let thread = thread::current().id();
+    let mut records = records();
+    let before = records.len();
+    records.retain(|_, record| record.thread != thread);
+    (before - records.len()) as u32
+}
+
+pub(crate) unsafe fn MsiRecordGetFieldCount(hRecord: RecordHandle) -> u32 {
+    with_fields(hRecord, u32::MAX, |fields| fields.len() as u32 - 1)
+}
+
+pub(crate) unsafe fn MsiRecordGetInteger(hRecord: RecordHandle, iField: u32) -> i32 {
+    with_fields(hRecord, MSI_NULL_INTEGER, |fields| {
+        match fields.get(iField as usize) {
+            Some(Value::Integer(i)) => *i,
+            Some(Value::String(s)) => String::from_utf16(s)
+                .ok()
+                .and_then(|s| s.trim().parse().ok())
+                .unwrap_or(MSI_NULL_INTEGER),
+            _ => MSI_NULL_INTEGER,
+        }
+    })
+}
+
+pub(crate) unsafe fn MsiRecordGetString(
+    hRecord: RecordHandle,
+    iField: u32,
+    szValueBuf: LPWSTR,
+    pcchValueBuf: *mut u32,
+) -> u32 {
+    let value = with_fields(hRecord, None, |fields| {
+        Some(
+            fields
+                .get(iField as usize)
+                .map(Value::to_wide)
+                .unwrap_or_default(),
+        )
+    });
+    match value {
+        Some(value) => copy_to(&value, szValueBuf, pcchValueBuf),
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+pub(crate) unsafe fn MsiRecordIsNull(hRecord: RecordHandle, iField: u32) -> BOOL {
+    let is_null = with_fields(hRecord, true, |fields| {
+        fields.get(iField as usize).is_none_or(Value::is_null)
+    });
+    BOOL::from(is_null)
+}
+
+pub(crate) unsafe fn MsiRecordSetInteger(hRecord: RecordHandle, iField: u32, iValue: i32) -> u32 {
+    let value = match iValue {
+        MSI_NULL_INTEGER => Value::Null,
+        i => Value::Integer(i),
+    };
+    set(hRecord, iField, value)
+}
+
+pub(crate) unsafe fn MsiRecordSetString(
+    hRecord: RecordHandle,
+    iField: u32,
+    szValue: LPCWSTR,
+) -> u32 {
+    let mut value = Vec::new();
+    if !szValue.is_null() {
+        let len = (0..).take_while(|&i| *szValue.add(i) != 0).count();
+        value.extend_from_slice(std::slice::from_raw_parts(szValue, len));
+    }
+    let value = match value.is_empty() {
+        true => Value::Null,
+        false => Value::String(value),
+    };
+    set(hRecord, iField, value)
+}
+
+fn set(h: RecordHandle, field: u32, value: Value) -> u32 {
+    with_fields(h, ERROR_INVALID_HANDLE, |fields| {
+        match fields.get_mut(field as usize) {
+            Some(field) => {
+                *field = value;
+                ERROR_SUCCESS
+            }
+            None => ERROR_INVALID_PARAMETER,
+        }
+    })
+}
+
+/// Formats field references in the template in field 0. Properties and other references are not formatted without a session.
+pub(crate) unsafe fn MsiFormatRecord(
+    _hInstall: SessionHandle,
+    hRecord: RecordHandle,
+    szResultBuf: LPWSTR,
+    pcchResultBuf: *mut u32,
+) -> u32 {
+    match with_fields(hRecord, None, |fields| Some(format(fields))) {
+        Some(value) => {
+            let value: Vec<u16> = value.encode_utf16().collect();
+            copy_to(&value, szResultBuf, pcchResultBuf)
+        }
+        None => ERROR_INVALID_HANDLE,
+    }
+}
+
+fn format(fields: &[Value]) -> String {
+    let text = |field: usize| String::from_utf16_lossy(&fields[field].to_wide());
+
+    // Without a template, each field is formatted with its index.
+    if fields[0].is_null() {
+        return (1..fields.len())
+            .map(|i| format!("{}: {} ", i, text(i)))
+            .collect();
+    }
+
+    let template = text(0);
+    let mut out = String::new();
+    // The text of the current {group} and whether any field referenced within it is null.
+    let mut group: Option<(String, bool)> = None;
+    let mut chars = template.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if group.is_none() => group = Some((String::new(), false)),
+            '}' if group.is_some() => {
+                if let Some((text, false)) = group.take() {
+                    out.push_str(&text);
+                }
+            }
+            '[' => {
+                let reference: String = chars.by_ref().take_while(|c| *c != ']').collect();
+                let value = match reference.parse::<usize>() {
+                    Ok(i) if i < fields.len() && !fields[i].is_null() => text(i),
+                    Ok(_) => {
+                        if let Some((_, missing)) = group.as_mut() {
+                            *missing = true;
+                        }
+                        String::new()
+                    }
+                    Err(_) => format!("[{reference}]"),
+                };
+                match group.as_mut() {
+                    Some((text, _)) => text.push_str(&value),
+                    None => out.push_str(&value),
+                }
+            }
+            c => match group.as_mut() {
+                Some((text, _)) => text.push(c),
+                None => out.push(c),
+            },
+        }
+    }
+
+    // An unterminated group is not a group.
+    if let Some((text, _)) = group {
+        out.push('{');
+        out.push_str(&text);
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> Value {
+        Value::String(s.encode_utf16().collect())
+    }
+
+    #[test]
+    fn format_fields() {
+        let fields = [
+            string("[1] and [2]{ with [3]}{ without [4]} [Property]"),
+            Value::Integer(1),
+            string("two"),
+            string("three"),
+            Value::Null,
+        ];
+        assert_eq!("1 and two with three [Property]", format(&fields));
+        assert_eq!(
+            "1: 1 2: two 3: three 4:  ",
+            format(&[
+                Value::Null,
+                fields[1].clone(),
+                fields[2].clone(),
+                fields[3].clone(),
+                Value::Null
+            ])
+        );
+    }
+}
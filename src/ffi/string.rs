@@ -0,0 +1,439 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Conversions between Rust strings and the UTF-16 strings used by the Windows Installer `W` functions.
+
+use super::*;
+use crate::{Error, ErrorKind, Result};
+use std::{
+    ffi::{OsStr, OsString},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// How strings returned from Windows Installer that are not valid UTF-16 are decoded.
+///
+/// Change how all strings are decoded by calling [`set_decoding()`](crate::set_decoding),
+/// or decode individual strings with methods like [`Session::property_lossy()`](crate::Session::property_lossy).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Decoding {
+    /// Return an error with [`ErrorKind::DataConversion`].
+    #[default]
+    Strict,
+
+    /// Replace invalid data with [`U+FFFD REPLACEMENT CHARACTER`](char::REPLACEMENT_CHARACTER).
+    Lossy,
+}
+
+static LOSSY: AtomicBool = AtomicBool::new(false);
+
+/// Gets how strings returned from Windows Installer are decoded by default.
+pub fn decoding() -> Decoding {
+    match LOSSY.load(Ordering::Relaxed) {
+        true => Decoding::Lossy,
+        false => Decoding::Strict,
+    }
+}
+
+/// Sets how strings returned from Windows Installer are decoded by default for the whole process.
+///
+/// # Example
+///
+/// ```
+/// use msica::Decoding;
+///
+/// msica::set_decoding(Decoding::Lossy);
+/// assert_eq!(msica::decoding(), Decoding::Lossy);
+/// # msica::set_decoding(Decoding::Strict);
+/// ```
+pub fn set_decoding(decoding: Decoding) {
+    LOSSY.store(decoding == Decoding::Lossy, Ordering::Relaxed);
+}
+
+/// Converts a string to a null-terminated UTF-16 string.
+///
+/// Returns an error with [`ErrorKind::DataConversion`] if the string contains an interior nul.
+pub(crate) fn to_wide(s: &str) -> Result<Vec<u16>> {
+    terminate(s.encode_utf16(), s.len())
+}
+
+/// Converts an optional string to a null-terminated UTF-16 string. `None` is converted to an empty string.
+pub(crate) fn to_wide_opt(s: Option<&str>) -> Result<Vec<u16>> {
+    to_wide(s.unwrap_or_default())
+}
+
+/// Converts an OS string to a null-terminated UTF-16 string.
+///
+/// Returns an error with [`ErrorKind::DataConversion`] if the string contains an interior nul.
+pub(crate) fn to_wide_os(s: &OsStr) -> Result<Vec<u16>> {
+    #[cfg(windows)]
+    let chars = std::os::windows::ffi::OsStrExt::encode_wide(s);
+    // Only the mock can build on other platforms, where OS strings are not UTF-16.
+    #[cfg(not(windows))]
+    let chars = s
+        .to_string_lossy()
+        .encode_utf16()
+        .collect::<Vec<_>>()
+        .into_iter();
+    terminate(chars, s.len())
+}
+
+/// Converts a UTF-16 string without a null terminator to an OS string.
+pub(crate) fn from_wide_os(s: &[u16]) -> OsString {
+    #[cfg(windows)]
+    return std::os::windows::ffi::OsStringExt::from_wide(s);
+    #[cfg(not(windows))]
+    return String::from_utf16_lossy(s).into();
+}
+
+fn terminate(chars: impl Iterator<Item = u16>, capacity: usize) -> Result<Vec<u16>> {
+    let mut wide = Vec::with_capacity(capacity + 1);
+    for (i, c) in chars.enumerate() {
+        if c == 0 {
+            return Err(Error::new(
+                ErrorKind::DataConversion,
+                format!("nul character found in provided data at position: {}", i),
+            ));
+        }
+        wide.push(c);
+    }
+    wide.push(0);
+
+    Ok(wide)
+}
+
+/// The length of a buffer for a GUID string in braces e.g., a product code, including the null terminator.
+pub(crate) const GUID_LEN: usize = 39;
+
+/// Gets a string from a fixed-size buffer up to the first null terminator, if any.
+pub(crate) fn from_terminated(buf: &[u16]) -> Result<String> {
+    let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+    String::from_utf16(&buf[..len]).map_err(|err| Error::new(ErrorKind::DataConversion, err))
+}
+
+/// Gets a string from a null-terminated UTF-16 string owned by Windows Installer, replacing invalid data.
+/// A null pointer is returned as an empty string.
+///
+/// # Safety
+///
+/// The pointer must be null or point to a null-terminated string.
+pub(crate) unsafe fn from_ptr_lossy(s: LPCWSTR) -> String {
+    if s.is_null() {
+        return String::new();
+    }
+    let len = (0..).take_while(|&i| *s.add(i) != 0).count();
+    String::from_utf16_lossy(std::slice::from_raw_parts(s, len))
+}
+
+/// Gets a string from a function that returns [`ERROR_MORE_DATA`] and the required length,
+/// not including the null terminator, when the buffer is too small.
+///
+/// Strings are decoded according to [`decoding()`].
+pub(crate) fn get_string<F>(f: F) -> Result<String>
+where
+    F: FnMut(LPWSTR, *mut u32) -> u32,
+{
+    get_string_with(decoding(), f)
+}
+
+/// Gets a string like [`get_string()`] decoded as specified.
+pub(crate) fn get_string_with<F>(decoding: Decoding, f: F) -> Result<String>
+where
+    F: FnMut(LPWSTR, *mut u32) -> u32,
+{
+    with_wide(f, |value| match decoding {
+        Decoding::Strict => Ok(String::from_utf16(value)?),
+        Decoding::Lossy => Ok(String::from_utf16_lossy(value)),
+    })
+}
+
+/// Gets a string like [`get_string()`] into an existing buffer, which is cleared first.
+///
+/// No allocations are made if the value fits in the stack buffer and `buf` has enough capacity.
+pub(crate) fn get_string_into<F>(buf: &mut String, f: F) -> Result<()>
+where
+    F: FnMut(LPWSTR, *mut u32) -> u32,
+{
+    buf.clear();
+    get_string_to(buf, f)
+}
+
+/// Writes a string like [`get_string()`] to a [`Write`](std::fmt::Write) without allocating a [`String`].
+pub(crate) fn get_string_to<F, W>(w: &mut W, f: F) -> Result<()>
+where
+    F: FnMut(LPWSTR, *mut u32) -> u32,
+    W: std::fmt::Write + ?Sized,
+{
+    let decoding = decoding();
+    with_wide(f, |value| {
+        for c in char::decode_utf16(value.iter().copied()) {
+            let c = match (c, decoding) {
+                (Ok(c), _) => c,
+                (Err(_), Decoding::Lossy) => char::REPLACEMENT_CHARACTER,
+                (Err(err), Decoding::Strict) => {
+                    return Err(Error::new(ErrorKind::DataConversion, err))
+                }
+            };
+            w.write_char(c)
+                .map_err(|err| Error::new(ErrorKind::Other, err))?;
+        }
+        Ok(())
+    })
+}
+
+/// Gets a UTF-16 string, not including the null terminator, from a function like [`get_string()`].
+pub(crate) fn get_wide<F>(f: F) -> Result<Vec<u16>>
+where
+    F: FnMut(LPWSTR, *mut u32) -> u32,
+{
+    with_wide(f, |value| Ok(value.to_vec()))
+}
+
+/// Gets `N` strings from a function that takes a buffer and length for each, and returns [`ERROR_MORE_DATA`]
+/// and the required lengths, not including the null terminators, when any buffer is too small.
+pub(crate) fn get_strings<const N: usize, F>(mut f: F) -> Result<[String; N]>
+where
+    F: FnMut([(LPWSTR, *mut u32); N]) -> u32,
+{
+    let mut lens = [STACK_BUFFER_LEN as u32; N];
+
+    // Retry with larger buffers if any value grew since the required lengths were returned.
+    for _ in 0..MAX_RETRIES {
+        let mut bufs = lens.map(|len| vec![0u16; len as usize]);
+        let mut args = [(std::ptr::null_mut(), std::ptr::null_mut()); N];
+        for ((arg, buf), len) in args.iter_mut().zip(&mut bufs).zip(&mut lens) {
+            *arg = (buf.as_mut_ptr(), len as *mut u32);
+        }
+
+        let ret = f(args);
+        match ret {
+            ERROR_SUCCESS => {
+                let mut values: [String; N] = std::array::from_fn(|_| String::new());
+                for (value, buf) in values.iter_mut().zip(&bufs) {
+                    *value = from_terminated(buf)?;
+                }
+                return Ok(values);
+            }
+            ERROR_MORE_DATA => lens = lens.map(|len| len + 1),
+            _ => return Err(Error::from_error_code(ret)),
+        }
+    }
+
+    Err(Error::from_error_code(ERROR_MORE_DATA))
+}
+
+/// Length of the buffer on the stack tried first before allocating a buffer of the required length.
+const STACK_BUFFER_LEN: usize = 256;
+
+/// Number of times to retry with a larger buffer if the value grew since the required length was returned.
+const MAX_RETRIES: usize = 8;
+
+/// Passes a UTF-16 string, not including the null terminator, from a function like [`get_string()`] to `g`.
+///
+/// Most values fit in a buffer on the stack, so only one call is made unless [`ERROR_MORE_DATA`] is returned.
+fn with_wide<F, G, T>(mut f: F, g: G) -> Result<T>
+where
+    F: FnMut(LPWSTR, *mut u32) -> u32,
+    G: FnOnce(&[u16]) -> Result<T>,
+{
+    let mut value_len = STACK_BUFFER_LEN as u32;
+    let mut value = [0u16; STACK_BUFFER_LEN];
+
+    let ret = f(value.as_mut_ptr(), &mut value_len as *mut u32);
+    match ret {
+        ERROR_SUCCESS => return g(&value[..value_len as usize]),
+        ERROR_MORE_DATA => {}
+        _ => return Err(Error::from_error_code(ret)),
+    }
+
+    // The value may grow between calls e.g., if another thread sets a property,
+    // so grow the buffer until it fits but give up if it keeps changing.
+    for _ in 0..MAX_RETRIES {
+        value_len += 1;
+        let mut value: Vec<u16> = vec![0; value_len as usize];
+
+        let ret = f(value.as_mut_ptr(), &mut value_len as *mut u32);
+        match ret {
+            ERROR_SUCCESS => return g(&value[..value_len as usize]),
+            ERROR_MORE_DATA => {}
+            _ => return Err(Error::from_error_code(ret)),
+        }
+    }
+
+    Err(Error::from_error_code(ERROR_MORE_DATA))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_terminated_stops_at_nul() {
+        assert_eq!("ab", from_terminated(&[0x61, 0x62, 0, 0x63]).unwrap());
+        assert_eq!("ab", from_terminated(&[0x61, 0x62]).unwrap());
+    }
+
+    #[test]
+    fn to_wide_terminates() {
+        assert_eq!(to_wide("ab").unwrap(), vec![0x61, 0x62, 0]);
+        assert_eq!(to_wide_opt(None).unwrap(), vec![0]);
+        assert_eq!(to_wide("ü").unwrap(), vec![0xfc, 0]);
+    }
+
+    #[test]
+    fn to_wide_interior_nul() {
+        let error = to_wide("t\0est").unwrap_err();
+        assert_eq!(&ErrorKind::DataConversion, error.kind());
+    }
+
+    #[test]
+    fn get_string_grows() {
+        let expected: Vec<u16> = "Ünïcödé".encode_utf16().collect();
+        let text = get_string(|buf, len| unsafe {
+            if (*len as usize) <= expected.len() {
+                *len = expected.len() as u32;
+                return ERROR_MORE_DATA;
+            }
+            std::ptr::copy_nonoverlapping(expected.as_ptr(), buf, expected.len());
+            *buf.add(expected.len()) = 0;
+            *len = expected.len() as u32;
+            ERROR_SUCCESS
+        })
+        .unwrap();
+        assert_eq!(text, "Ünïcödé");
+    }
+
+    #[test]
+    fn get_strings_grows() {
+        let long: Vec<u16> = "b".repeat(STACK_BUFFER_LEN).encode_utf16().collect();
+        let mut calls = 0;
+        let [short, long] = get_strings(|[(a, a_len), (b, b_len)]| unsafe {
+            calls += 1;
+            if (*b_len as usize) <= long.len() {
+                *a_len = 1;
+                *b_len = long.len() as u32;
+                return ERROR_MORE_DATA;
+            }
+            *a = 0x61;
+            *a.add(1) = 0;
+            std::ptr::copy_nonoverlapping(long.as_ptr(), b, long.len());
+            *b.add(long.len()) = 0;
+            ERROR_SUCCESS
+        })
+        .unwrap();
+        assert_eq!(short, "a");
+        assert_eq!(long, "b".repeat(STACK_BUFFER_LEN));
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn get_string_fits_stack_buffer() {
+        let mut calls = 0;
+        let text = get_string(|buf, len| unsafe {
+            calls += 1;
+            *buf = 0x61;
+            *buf.add(1) = 0;
+            *len = 1;
+            ERROR_SUCCESS
+        })
+        .unwrap();
+        assert_eq!(text, "a");
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn get_string_exceeds_stack_buffer() {
+        let expected = "a".repeat(STACK_BUFFER_LEN * 2);
+        let wide: Vec<u16> = expected.encode_utf16().collect();
+        let mut calls = 0;
+        let text = get_string(|buf, len| unsafe {
+            calls += 1;
+            if (*len as usize) <= wide.len() {
+                *len = wide.len() as u32;
+                return ERROR_MORE_DATA;
+            }
+            std::ptr::copy_nonoverlapping(wide.as_ptr(), buf, wide.len());
+            *buf.add(wide.len()) = 0;
+            *len = wide.len() as u32;
+            ERROR_SUCCESS
+        })
+        .unwrap();
+        assert_eq!(text, expected);
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn get_string_into_reuses_buffer() {
+        let mut buf = String::with_capacity(16);
+        buf.push_str("previous");
+        let ptr = buf.as_ptr();
+
+        get_string_into(&mut buf, |b, len| unsafe {
+            *b = 0x61;
+            *b.add(1) = 0;
+            *len = 1;
+            ERROR_SUCCESS
+        })
+        .unwrap();
+        assert_eq!(buf, "a");
+        assert_eq!(buf.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn get_string_value_grows_between_calls() {
+        let mut actual = STACK_BUFFER_LEN;
+        let mut grow = 2;
+        let text = get_string(|buf, len| unsafe {
+            if (*len as usize) <= actual {
+                *len = actual as u32;
+                // Grow the value after returning the required length.
+                if grow > 0 {
+                    actual += 10;
+                    grow -= 1;
+                }
+                return ERROR_MORE_DATA;
+            }
+            for i in 0..actual {
+                *buf.add(i) = 0x61;
+            }
+            *buf.add(actual) = 0;
+            *len = actual as u32;
+            ERROR_SUCCESS
+        })
+        .unwrap();
+        assert_eq!(text.len(), STACK_BUFFER_LEN + 10 * 2);
+    }
+
+    #[test]
+    fn get_string_value_keeps_growing() {
+        let mut actual = STACK_BUFFER_LEN;
+        let error = get_string(|_, len| unsafe {
+            *len = actual as u32;
+            actual += 1;
+            ERROR_MORE_DATA
+        })
+        .unwrap_err();
+        assert_eq!(Some(ERROR_MORE_DATA), error.code());
+    }
+
+    #[test]
+    fn get_string_with_lossy() {
+        // An unpaired surrogate is not valid UTF-16.
+        let invalid = [0x61, 0xd800, 0x62];
+        let f = |buf: LPWSTR, len: *mut u32| unsafe {
+            std::ptr::copy_nonoverlapping(invalid.as_ptr(), buf, invalid.len());
+            *buf.add(invalid.len()) = 0;
+            *len = invalid.len() as u32;
+            ERROR_SUCCESS
+        };
+
+        let error = get_string_with(Decoding::Strict, f).unwrap_err();
+        assert_eq!(&ErrorKind::DataConversion, error.kind());
+        assert_eq!(get_string_with(Decoding::Lossy, f).unwrap(), "a\u{fffd}b");
+    }
+
+    #[test]
+    fn get_string_error() {
+        let error = get_string(|_, _| ERROR_INVALID_HANDLE).unwrap_err();
+        assert_eq!(Some(ERROR_INVALID_HANDLE), error.code());
+    }
+}
@@ -0,0 +1,184 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Traces calls into `msi.dll` with their parameters and return values to the debugger output
+//! e.g., [DebugView](https://learn.microsoft.com/sysinternals/downloads/debugview).
+
+use super::*;
+use std::{ffi::c_void, fmt::Write};
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn OutputDebugStringW(lpOutputString: LPCWSTR);
+}
+
+/// Writes a parameter or return value to a trace message.
+pub(crate) trait TraceArg {
+    fn trace(&self, f: &mut String);
+}
+
+pub(crate) fn call(name: &str, args: &[(&str, &dyn TraceArg)], ret: &dyn TraceArg) {
+    let mut message = format!("msica: {}(", name);
+    for (i, (arg, value)) in args.iter().enumerate() {
+        if i > 0 {
+            message.push_str(", ");
+        }
+        let _ = write!(message, "{}=", arg);
+        value.trace(&mut message);
+    }
+    message.push_str(") -> ");
+    ret.trace(&mut message);
+    message.push('\n');
+
+    if let Ok(message) = to_wide(&message) {
+        unsafe {
+            OutputDebugStringW(message.as_ptr());
+        }
+    }
+}
+
+macro_rules! trace_display {
+    ($($ty:ty),*) => {
+        $(
+            impl TraceArg for $ty {
+                fn trace(&self, f: &mut String) {
+                    let _ = write!(f, "{}", self);
+                }
+            }
+        )*
+    };
+}
+
+trace_display!(u16, u32, i32, BOOL, MSIHANDLE);
+
+impl<K> TraceArg for Handle<K> {
+    fn trace(&self, f: &mut String) {
+        let _ = write!(f, "{}", self);
+    }
+}
+
+impl<K> TraceArg for &mut Handle<K> {
+    fn trace(&self, f: &mut String) {
+        let _ = write!(f, "{}", **self);
+    }
+}
+
+macro_rules! trace_ptr {
+    ($($ty:ty),*) => {
+        $(
+            impl TraceArg for *mut $ty {
+                fn trace(&self, f: &mut String) {
+                    match self.is_null() {
+                        true => f.push_str("null"),
+                        false => {
+                            let _ = write!(f, "{}", unsafe { **self });
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+trace_ptr!(u32, i32);
+
+impl TraceArg for LPCWSTR {
+    fn trace(&self, f: &mut String) {
+        match self.is_null() {
+            true => f.push_str("null"),
+            // Some functions accept small integers in place of a string e.g., ADVERTISEFLAGS_USERASSIGN.
+            false if (*self as usize) < 0x10000 => {
+                let _ = write!(f, "{:#x}", *self as usize);
+            }
+            false => {
+                let s = unsafe {
+                    let len = (0..).take_while(|&i| *self.add(i) != 0).count();
+                    String::from_utf16_lossy(std::slice::from_raw_parts(*self, len))
+                };
+                let _ = write!(f, "{:?}", s);
+            }
+        }
+    }
+}
+
+impl TraceArg for *mut MSIPATCHSEQUENCEINFOW {
+    fn trace(&self, f: &mut String) {
+        let _ = write!(f, "{:p}", *self);
+    }
+}
+
+impl TraceArg for *mut FILETIME {
+    fn trace(&self, f: &mut String) {
+        let _ = write!(f, "{:p}", *self);
+    }
+}
+
+impl TraceArg for *mut *mut c_void {
+    fn trace(&self, f: &mut String) {
+        let _ = write!(f, "{:p}", *self);
+    }
+}
+
+impl TraceArg for *const c_void {
+    fn trace(&self, f: &mut String) {
+        let _ = write!(f, "{:p}", *self);
+    }
+}
+
+macro_rules! trace_handler {
+    ($($ty:ty),*) => {
+        $(
+            impl TraceArg for $ty {
+                fn trace(&self, f: &mut String) {
+                    match self {
+                        Some(handler) => {
+                            let _ = write!(f, "{:p}", *handler as *const c_void);
+                        }
+                        None => f.push_str("null"),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+trace_handler!(INSTALLUI_HANDLERW, INSTALLUI_HANDLER_RECORD);
+
+impl TraceArg for *mut INSTALLUI_HANDLER_RECORD {
+    fn trace(&self, f: &mut String) {
+        let _ = write!(f, "{:p}", *self);
+    }
+}
+
+impl TraceArg for LPWSTR {
+    fn trace(&self, f: &mut String) {
+        // Output buffers may not be terminated, so only write the address.
+        let _ = write!(f, "{:p}", *self);
+    }
+}
+
+macro_rules! trace_enum {
+    ($($ty:ty),*) => {
+        $(
+            impl TraceArg for $ty {
+                fn trace(&self, f: &mut String) {
+                    let _ = write!(f, "{:#x}", u32::from(*self));
+                }
+            }
+        )*
+    };
+}
+
+trace_enum!(ColumnInfo, InstallType, MessageType, ModifyMode, RunMode);
+
+impl TraceArg for InstallLevel {
+    fn trace(&self, f: &mut String) {
+        let _ = write!(f, "{}", self.get());
+    }
+}
+
+impl TraceArg for InstallState {
+    fn trace(&self, f: &mut String) {
+        let _ = write!(f, "{:?}", self);
+    }
+}
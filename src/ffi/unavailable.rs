@@ -0,0 +1,61 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::*;
+
+/// The value returned by a function when `msi.dll` or the function could not be loaded,
+/// or when the function is not implemented by the mock backend.
+pub(crate) trait Unavailable {
+    fn unavailable() -> Self;
+}
+
+impl Unavailable for u32 {
+    fn unavailable() -> Self {
+        ERROR_CALL_NOT_IMPLEMENTED
+    }
+}
+
+impl Unavailable for i32 {
+    // Functions return -1 for INSTALLSTATE_UNKNOWN or to reject a message, so return a value that is
+    // read as a null integer and is not a valid state.
+    fn unavailable() -> Self {
+        MSI_NULL_INTEGER
+    }
+}
+
+impl Unavailable for u16 {
+    fn unavailable() -> Self {
+        0
+    }
+}
+
+impl Unavailable for BOOL {
+    fn unavailable() -> Self {
+        BOOL::from(false)
+    }
+}
+
+impl Unavailable for INSTALLUI_HANDLERW {
+    fn unavailable() -> Self {
+        None
+    }
+}
+
+impl<K> Unavailable for Handle<K> {
+    fn unavailable() -> Self {
+        Handle::null()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "mock")]
+mod tests {
+    use crate::{feature_state, locate_component, ErrorKind};
+
+    #[test]
+    fn unavailable_state_is_error() {
+        let err = feature_state("{01234567-89AB-CDEF-0123-456789ABCDEF}", "Feature").unwrap_err();
+        assert_eq!(&ErrorKind::DataConversion, err.kind());
+        assert!(locate_component("{01234567-89AB-CDEF-0123-456789ABCDEF}").is_err());
+    }
+}
@@ -0,0 +1,191 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{Error, ErrorKind, Result};
+use std::{fmt, ops::Deref, str::FromStr};
+
+/// The length of a GUID in braces e.g., `{01234567-89AB-CDEF-0123-456789ABCDEF}`.
+const BRACED_LEN: usize = 38;
+
+/// The positions of hyphens within a GUID in braces.
+const HYPHENS: [usize; 4] = [9, 14, 19, 24];
+
+/// A GUID e.g., a product, component, or patch code, stored as an uppercase string in braces.
+///
+/// Functions that enumerate or query products, components, and patches return a [`Guid`], and functions that take
+/// a product, component, or patch code accept a [`Guid`] or a string. A [`Guid`] also dereferences to a [`str`].
+/// It can also be converted to and from the packed format the installer uses in the registry.
+///
+/// # Example
+///
+/// ```
+/// use msica::Guid;
+///
+/// let product_code: Guid = "01234567-89ab-cdef-0123-456789abcdef".parse()?;
+/// assert_eq!("{01234567-89AB-CDEF-0123-456789ABCDEF}", product_code.as_str());
+/// assert_eq!("76543210BA98FEDC1032547698BADCFE", product_code.to_packed());
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[derive(Clone, Copy, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Guid([u8; BRACED_LEN]);
+
+impl Guid {
+    /// Parses a GUID with or without braces, ignoring case.
+    pub fn parse(s: &str) -> Result<Self> {
+        let invalid = || Error::new(ErrorKind::DataConversion, format!("invalid GUID: {s}"));
+
+        let unbraced = s
+            .strip_prefix('{')
+            .and_then(|s| s.strip_suffix('}'))
+            .unwrap_or(s);
+        if unbraced.len() != BRACED_LEN - 2 {
+            return Err(invalid());
+        }
+
+        let mut guid = [b'-'; BRACED_LEN];
+        guid[0] = b'{';
+        guid[BRACED_LEN - 1] = b'}';
+        for (i, c) in unbraced.bytes().enumerate() {
+            let i = i + 1;
+            match (HYPHENS.contains(&i), c) {
+                (true, b'-') => {}
+                (false, c) if c.is_ascii_hexdigit() => guid[i] = c.to_ascii_uppercase(),
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(Self(guid))
+    }
+
+    /// Converts a GUID from the packed format the installer uses in the registry e.g., under `Installer\Products`.
+    pub fn from_packed(s: &str) -> Result<Self> {
+        if s.len() != 32 || !s.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Error::new(
+                ErrorKind::DataConversion,
+                format!("invalid packed GUID: {s}"),
+            ));
+        }
+
+        let digits = unpack(s.as_bytes());
+        let mut guid = [b'-'; BRACED_LEN];
+        guid[0] = b'{';
+        guid[BRACED_LEN - 1] = b'}';
+        let positions = (1..BRACED_LEN - 1).filter(|i| !HYPHENS.contains(i));
+        for (i, c) in positions.zip(digits) {
+            guid[i] = c.to_ascii_uppercase();
+        }
+
+        Ok(Self(guid))
+    }
+
+    /// Converts the GUID to the packed format the installer uses in the registry.
+    ///
+    /// The first three groups of digits are reversed, and each pair of digits in the last two groups is swapped.
+    pub fn to_packed(&self) -> String {
+        let digits: Vec<u8> = self.0[1..BRACED_LEN - 1]
+            .iter()
+            .copied()
+            .filter(|c| *c != b'-')
+            .collect();
+        unpack(&digits).into_iter().map(char::from).collect()
+    }
+
+    /// Parses a GUID from a fixed-size buffer returned by Windows Installer up to the first null terminator.
+    pub(crate) fn from_terminated(buf: &[u16]) -> Result<Self> {
+        Self::parse(&crate::ffi::from_terminated(buf)?)
+    }
+
+    /// Gets the GUID as an uppercase string in braces.
+    pub fn as_str(&self) -> &str {
+        // Only ASCII digits, letters, hyphens, and braces are ever stored.
+        std::str::from_utf8(&self.0).unwrap_or_default()
+    }
+}
+
+/// Swaps between unbraced GUID digits and packed digits, which is its own inverse.
+fn unpack(digits: &[u8]) -> [u8; 32] {
+    let mut packed = [0u8; 32];
+    for range in [0..8, 8..12, 12..16] {
+        packed[range.clone()].copy_from_slice(&digits[range.clone()]);
+        packed[range].reverse();
+    }
+    for i in (16..32).step_by(2) {
+        packed[i] = digits[i + 1];
+        packed[i + 1] = digits[i];
+    }
+    packed
+}
+
+impl Deref for Guid {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for Guid {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Guid").field(&self.as_str()).finish()
+    }
+}
+
+impl fmt::Display for Guid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Guid {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<&str> for Guid {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        Self::parse(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let expected = "{12345678-ABCD-EF01-2345-6789ABCDEF01}";
+        assert_eq!(expected, Guid::parse(expected).unwrap().as_str());
+        assert_eq!(
+            expected,
+            Guid::parse("12345678-abcd-ef01-2345-6789abcdef01")
+                .unwrap()
+                .to_string()
+        );
+        assert!(Guid::parse("{12345678-ABCD-EF01-2345-6789ABCDEF0}").is_err());
+        assert!(Guid::parse("{12345678-ABCD-EF01-2345_6789ABCDEF01}").is_err());
+        assert!(Guid::parse("{12345678-ABCD-EF01-2345-6789ABCDEF0G}").is_err());
+        assert!(Guid::parse("{12345678-ABCD-EF01-2345-6789ABCDEF01").is_err());
+    }
+
+    #[test]
+    fn packed() {
+        let guid = Guid::parse("{12345678-ABCD-EF01-2345-6789ABCDEF01}").unwrap();
+        assert_eq!("87654321DCBA10FE32547698BADCFE10", guid.to_packed());
+        assert_eq!(
+            guid,
+            Guid::from_packed("87654321dcba10fe32547698badcfe10").unwrap()
+        );
+        assert!(Guid::from_packed("87654321DCBA10FE32547698BADCFE1").is_err());
+    }
+}
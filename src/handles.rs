@@ -0,0 +1,172 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(feature = "debug-handles")]
+use crate::ffi;
+use crate::{Field, MessageType, Record, Session};
+use std::{
+    collections::HashMap,
+    panic::Location,
+    sync::{Mutex, MutexGuard},
+    thread::{self, ThreadId},
+};
+
+type Handles = HashMap<u32, (&'static Location<'static>, ThreadId)>;
+
+static HANDLES: Mutex<Option<Handles>> = Mutex::new(None);
+
+fn handles() -> MutexGuard<'static, Option<Handles>> {
+    // Tracking is diagnostic only, so ignore poisoning from a panicking thread.
+    HANDLES.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+pub(crate) fn opened(h: ffi::MSIHANDLE, location: &'static Location<'static>) {
+    if !h.is_null() {
+        handles()
+            .get_or_insert_with(HashMap::new)
+            .insert(*h, (location, thread::current().id()));
+    }
+}
+
+pub(crate) fn closed(h: ffi::MSIHANDLE) {
+    if let Some(handles) = handles().as_mut() {
+        handles.remove(&*h);
+    }
+}
+
+/// Stops tracking all handles opened on the current thread.
+pub(crate) fn forget_thread() {
+    let id = thread::current().id();
+    if let Some(handles) = handles().as_mut() {
+        handles.retain(|_, (_, thread)| *thread != id);
+    }
+}
+
+/// Gets the handles opened on the given `thread` that are still open.
+fn snapshot(thread: ThreadId) -> HashMap<u32, &'static Location<'static>> {
+    handles()
+        .iter()
+        .flatten()
+        .filter(|(_, (_, id))| *id == thread)
+        .map(|(h, (location, _))| (*h, *location))
+        .collect()
+}
+
+/// Gets the count of handles opened by this crate on the current thread that are still open.
+pub(crate) fn live_handles() -> usize {
+    let id = thread::current().id();
+    handles()
+        .iter()
+        .flatten()
+        .filter(|(_, (_, thread))| *thread == id)
+        .count()
+}
+
+/// Detects handles opened by this crate that are still open when the guard is dropped.
+///
+/// Create a guard at the start of a custom action. When it is dropped at the end of the custom action,
+/// any handles opened on the same thread since the guard was created that have not been closed are written to
+/// the installation log along with the source location that opened them, which helps diagnose warnings from `msiexec` like
+/// "1 handle left open".
+///
+/// Requires the `debug-handles` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::prelude::*;
+/// use msica::HandleLeakGuard;
+/// const ERROR_SUCCESS: u32 = 0;
+///
+/// #[no_mangle]
+/// pub extern "C" fn MyCustomAction(session: Session) -> u32 {
+///     let _guard = HandleLeakGuard::new(&session);
+///
+///     // Do something with `session`.
+///
+///     ERROR_SUCCESS
+/// }
+/// ```
+pub struct HandleLeakGuard {
+    h: ffi::SessionHandle,
+    // Other custom actions may run concurrently in the same process, so only track handles from this thread.
+    thread: ThreadId,
+    open: HashMap<u32, &'static Location<'static>>,
+}
+
+impl HandleLeakGuard {
+    /// Creates a [`HandleLeakGuard`] that writes any leaked handles to the installation log for the given [`Session`].
+    pub fn new(session: &Session) -> Self {
+        let thread = thread::current().id();
+        Self {
+            h: session.h,
+            thread,
+            open: snapshot(thread),
+        }
+    }
+
+    /// Gets the handles opened on the thread that created the guard since it was created that are still open,
+    /// along with the source location that opened them.
+    pub fn leaked(&self) -> Vec<(u32, &'static Location<'static>)> {
+        let mut leaked: Vec<_> = snapshot(self.thread)
+            .into_iter()
+            .filter(|(h, _)| !self.open.contains_key(h))
+            .collect();
+        leaked.sort_by_key(|(h, _)| *h);
+        leaked
+    }
+}
+
+impl Drop for HandleLeakGuard {
+    fn drop(&mut self) {
+        let leaked = self.leaked();
+        if leaked.is_empty() {
+            return;
+        }
+
+        let session = Session::from_handle(self.h);
+        for (h, location) in leaked {
+            if let Ok(record) = Record::with_fields(
+                Some("msica: handle [1] opened at [2] was not closed"),
+                vec![
+                    Field::IntegerData(h as i32),
+                    Field::StringData(location.to_string()),
+                ],
+            ) {
+                session.message(MessageType::Info, &record);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opened_and_closed() {
+        let h = ffi::MSIHANDLE::from(u32::MAX);
+        let thread = thread::current().id();
+        opened(h, Location::caller());
+        assert!(snapshot(thread).contains_key(&u32::MAX));
+
+        closed(h);
+        assert!(!snapshot(thread).contains_key(&u32::MAX));
+    }
+
+    #[test]
+    fn other_thread_not_tracked() {
+        let h = ffi::MSIHANDLE::from(u32::MAX - 1);
+        thread::spawn(move || opened(h, Location::caller()))
+            .join()
+            .unwrap();
+        assert!(!snapshot(thread::current().id()).contains_key(&(u32::MAX - 1)));
+        closed(h);
+    }
+
+    #[test]
+    fn null_not_tracked() {
+        opened(ffi::MSIHANDLE::null(), Location::caller());
+        assert!(!snapshot(thread::current().id()).contains_key(&0));
+    }
+}
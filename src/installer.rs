@@ -0,0 +1,369 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi;
+use crate::{Error, Result, Session};
+use std::path::Path;
+
+flags! {
+    /// Kinds of messages written to the log passed to [`enable_log()`].
+    pub struct LogModes: u32 {
+        /// Premature termination of installation.
+        const FATAL_EXIT = 1 << 0;
+        /// Error messages.
+        const ERROR = 1 << 1;
+        /// Warning messages.
+        const WARNING = 1 << 2;
+        /// User requests.
+        const USER = 1 << 3;
+        /// Status messages.
+        const INFO = 1 << 4;
+        /// Files in use information.
+        const FILES_IN_USE = 1 << 5;
+        /// Request to determine a valid source location.
+        const RESOLVE_SOURCE = 1 << 6;
+        /// Insufficient disk space.
+        const OUT_OF_DISK_SPACE = 1 << 7;
+        /// Start of new installation actions.
+        const ACTION_START = 1 << 8;
+        /// Data record with the installation action.
+        const ACTION_DATA = 1 << 9;
+        /// Dumps all properties at the end of the installation.
+        const PROPERTY_DUMP = 1 << 10;
+        /// Initial user interface parameters.
+        const COMMON_DATA = 1 << 11;
+        /// Verbose output.
+        const VERBOSE = 1 << 12;
+        /// Extra debugging information.
+        const EXTRA_DEBUG = 1 << 13;
+        /// Only write the log if the installation fails.
+        const LOG_ONLY_ON_ERROR = 1 << 14;
+        /// Performance information.
+        const LOG_PERFORMANCE = 1 << 15;
+        /// Restart Manager files in use information.
+        const RM_FILES_IN_USE = 1 << 25;
+    }
+}
+
+impl LogModes {
+    /// Equivalent to `msiexec /l*v`: the `iwearucmop` messages of `/l*` plus verbose output.
+    pub const ALL_VERBOSE: Self = Self(
+        Self::FATAL_EXIT.0
+            | Self::ERROR.0
+            | Self::WARNING.0
+            | Self::USER.0
+            | Self::INFO.0
+            | Self::OUT_OF_DISK_SPACE.0
+            | Self::ACTION_START.0
+            | Self::ACTION_DATA.0
+            | Self::PROPERTY_DUMP.0
+            | Self::COMMON_DATA.0
+            | Self::VERBOSE.0,
+    );
+
+    /// Progress messages received by an [`ExternalUi`](crate::ExternalUi) handler.
+    pub const PROGRESS: Self = Self::PROPERTY_DUMP;
+    /// Initialization messages received by an [`ExternalUi`](crate::ExternalUi) handler.
+    pub const INITIALIZE: Self = Self::VERBOSE;
+    /// Termination messages received by an [`ExternalUi`](crate::ExternalUi) handler.
+    pub const TERMINATE: Self = Self::EXTRA_DEBUG;
+    /// Dialog messages received by an [`ExternalUi`](crate::ExternalUi) handler.
+    pub const SHOW_DIALOG: Self = Self::LOG_ONLY_ON_ERROR;
+    /// Start of an installation received by an [`ExternalUi`](crate::ExternalUi) handler.
+    pub const INSTALL_START: Self = Self(1 << 26);
+    /// End of an installation received by an [`ExternalUi`](crate::ExternalUi) handler.
+    pub const INSTALL_END: Self = Self(1 << 27);
+}
+
+flags! {
+    /// How the log passed to [`enable_log()`] is written.
+    pub struct LogAttributes: u32 {
+        /// Append to an existing log file.
+        const APPEND = 1 << 0;
+        /// Flush each line to the log file. This may slow installations significantly.
+        const FLUSH_EACH_LINE = 1 << 1;
+    }
+}
+
+flags! {
+    /// The contexts in which products and patches are installed.
+    pub struct InstallContext: u32 {
+        /// Installed per-user and managed by policy.
+        const USER_MANAGED = 1 << 0;
+        /// Installed per-user.
+        const USER_UNMANAGED = 1 << 1;
+        /// Installed per-machine.
+        const MACHINE = 1 << 2;
+    }
+}
+
+impl InstallContext {
+    /// All contexts.
+    pub const ALL: Self = Self(Self::USER_MANAGED.0 | Self::USER_UNMANAGED.0 | Self::MACHINE.0);
+
+    /// Gets the context from the values of the `ALLUSERS` and `MSIINSTALLPERUSER` properties.
+    fn from_properties(all_users: &str, per_user: &str) -> Self {
+        match (all_users, per_user) {
+            ("1", _) => Self::MACHINE,
+            ("2", "1") => Self::USER_UNMANAGED,
+            ("2", _) => Self::MACHINE,
+            _ => Self::USER_UNMANAGED,
+        }
+    }
+}
+
+impl Session {
+    /// Gets the context of the installation from the `ALLUSERS` and `MSIINSTALLPERUSER` properties.
+    ///
+    /// Returns [`InstallContext::MACHINE`] for a per-machine installation, or [`InstallContext::USER_UNMANAGED`]
+    /// for a per-user installation. Whether a per-user installation is managed cannot be determined from properties.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use msica::prelude::*;
+    /// use msica::InstallContext;
+    ///
+    /// # fn example(session: &Session) -> Result<()> {
+    /// if session.install_context()? == InstallContext::MACHINE {
+    ///     // Write to HKEY_LOCAL_MACHINE.
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn install_context(&self) -> Result<InstallContext> {
+        let all_users = self.property("ALLUSERS")?;
+        let per_user = self.property("MSIINSTALLPERUSER")?;
+        Ok(InstallContext::from_properties(
+            all_users.trim(),
+            per_user.trim(),
+        ))
+    }
+}
+
+/// Enables logging of the selected message types for all subsequent installation sessions in the current process.
+///
+/// Pass `None` for the `path` to disable logging.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{enable_log, LogAttributes, LogModes};
+///
+/// enable_log(LogModes::ALL_VERBOSE | LogModes::EXTRA_DEBUG, Some("install.log".as_ref()), LogAttributes::empty())?;
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub fn enable_log(modes: LogModes, path: Option<&Path>, attributes: LogAttributes) -> Result<()> {
+    unsafe {
+        let path = match path {
+            Some(path) => Some(ffi::to_wide_os(path.as_os_str())?),
+            None => None,
+        };
+
+        let ret = ffi::MsiEnableLog(
+            modes.bits(),
+            path.as_ref().map_or(std::ptr::null(), |path| path.as_ptr()),
+            attributes.bits(),
+        );
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_error_code(ret));
+        }
+
+        Ok(())
+    }
+}
+
+/// Installs or configures the product in the package at `path`.
+///
+/// Any `command_line` property assignments e.g., `ADDLOCAL=ALL REBOOT=ReallySuppress` are applied.
+/// Use [`set_internal_ui()`](crate::set_internal_ui) or an [`ExternalUi`](crate::ExternalUi) to control what UI is shown,
+/// or [`Installer`](crate::Installer) to install on a worker thread and receive progress.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::install_product;
+///
+/// install_product("example.msi", Some("REBOOT=ReallySuppress"))?;
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[track_caller]
+pub fn install_product(path: impl AsRef<Path>, command_line: Option<&str>) -> Result<()> {
+    unsafe {
+        let path = ffi::to_wide_os(path.as_ref().as_os_str())?;
+        let command_line = ffi::to_wide_opt(command_line)?;
+
+        let ret = ffi::MsiInstallProduct(path.as_ptr(), command_line.as_ptr());
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+
+        Ok(())
+    }
+}
+
+/// The install level that determines which features are installed by [`configure_product()`](crate::configure_product).
+///
+/// Features with a `Level` in the `Feature` table greater than zero and no greater than the install level are installed.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[repr(transparent)]
+pub struct InstallLevel(i32);
+
+impl InstallLevel {
+    /// The default install level authored in the package.
+    pub const DEFAULT: Self = Self(0);
+    /// Only the minimum set of features.
+    pub const MINIMUM: Self = Self(1);
+    /// All features.
+    pub const MAXIMUM: Self = Self(0xffff);
+
+    /// Creates an [`InstallLevel`] for a specific level.
+    pub const fn new(level: u16) -> Self {
+        Self(level as i32)
+    }
+
+    /// Gets the install level.
+    pub const fn get(self) -> i32 {
+        self.0
+    }
+}
+
+impl From<InstallLevel> for i32 {
+    fn from(value: InstallLevel) -> Self {
+        value.0
+    }
+}
+
+repr_enum! {
+    /// The installed or requested state of a feature or component.
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub enum InstallState: i32 {
+        /// The state is not used.
+        NotUsed = -7,
+        /// The configuration data is corrupt.
+        BadConfig = -6,
+        /// The installation is suspended or in progress.
+        Incomplete = -5,
+        /// Run from source, but the source is unavailable.
+        SourceAbsent = -4,
+        /// The buffer passed to the function was too small.
+        MoreData = -3,
+        /// An argument passed to the function was invalid.
+        InvalidArg = -2,
+        /// The product, feature, or component is unknown.
+        Unknown = -1,
+        /// The feature or component is broken.
+        Broken = 0,
+        /// The feature is advertised.
+        Advertised = 1,
+        /// The feature or component is not installed.
+        Absent = 2,
+        /// The feature or component is installed locally.
+        Local = 3,
+        /// The feature or component is installed to run from source.
+        Source = 4,
+        /// The feature or component is installed to its default location.
+        Default = 5,
+    }
+}
+
+impl InstallState {
+    /// The requested action state to remove a feature or component, which shares a value with [`InstallState::Advertised`].
+    pub const REMOVED: Self = Self::Advertised;
+
+    /// Converts a state returned from a function, which may indicate an error instead of a state.
+    pub(crate) fn from_result(state: i32) -> Result<Self> {
+        match Self::try_from(state)? {
+            Self::InvalidArg => Err(Error::from_error_code(ffi::ERROR_INVALID_PARAMETER)),
+            Self::MoreData => Err(Error::from_error_code(ffi::ERROR_MORE_DATA)),
+            state => Ok(state),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_modes_debug() {
+        assert_eq!(
+            "LogModes(ERROR | WARNING)",
+            format!("{:?}", LogModes::ERROR | LogModes::WARNING)
+        );
+        assert_eq!("LogModes(empty)", format!("{:?}", LogModes::empty()));
+        assert_eq!(
+            "LogModes(VERBOSE | 0x40000000)",
+            format!("{:?}", LogModes::from_bits_retain(0x4000_1000))
+        );
+    }
+
+    #[test]
+    fn log_modes_all_verbose() {
+        assert_eq!(0x1f9f, LogModes::ALL_VERBOSE.bits());
+        assert!(!LogModes::ALL_VERBOSE.contains(LogModes::RESOLVE_SOURCE));
+        assert!(LogModes::ALL_VERBOSE.contains(LogModes::VERBOSE | LogModes::PROPERTY_DUMP));
+        assert!(!LogModes::ALL_VERBOSE.contains(LogModes::EXTRA_DEBUG));
+    }
+
+    #[test]
+    fn install_context_all() {
+        assert_eq!(7, InstallContext::ALL.bits());
+        assert_eq!(
+            "InstallContext(USER_MANAGED | USER_UNMANAGED | MACHINE)",
+            format!("{:?}", InstallContext::ALL)
+        );
+    }
+
+    #[test]
+    fn install_context_from_properties() {
+        assert_eq!(
+            InstallContext::MACHINE,
+            InstallContext::from_properties("1", "")
+        );
+        assert_eq!(
+            InstallContext::MACHINE,
+            InstallContext::from_properties("2", "")
+        );
+        assert_eq!(
+            InstallContext::USER_UNMANAGED,
+            InstallContext::from_properties("2", "1")
+        );
+        assert_eq!(
+            InstallContext::USER_UNMANAGED,
+            InstallContext::from_properties("", "1")
+        );
+    }
+
+    #[test]
+    fn install_level() {
+        assert_eq!(0, InstallLevel::default().get());
+        assert_eq!(InstallLevel::MAXIMUM, InstallLevel::new(u16::MAX));
+        assert!(InstallLevel::MINIMUM < InstallLevel::new(100));
+    }
+
+    #[test]
+    fn install_state_try_from() {
+        assert_eq!(Some(InstallState::Unknown), InstallState::try_from(-1).ok());
+        assert_eq!(Some(InstallState::Local), InstallState::try_from(3).ok());
+        assert_eq!(
+            Some(InstallState::MoreData),
+            InstallState::try_from(-3).ok()
+        );
+        assert!(InstallState::try_from(-8).is_err());
+        assert_eq!(InstallState::Advertised, InstallState::REMOVED);
+    }
+
+    #[test]
+    fn install_state_from_result() {
+        assert_eq!(InstallState::Local, InstallState::from_result(3).unwrap());
+        assert_eq!(
+            Some(ffi::ERROR_INVALID_PARAMETER),
+            InstallState::from_result(-2).unwrap_err().code()
+        );
+        assert_eq!(
+            Some(ffi::ERROR_MORE_DATA),
+            InstallState::from_result(-3).unwrap_err().code()
+        );
+    }
+}
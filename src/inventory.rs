@@ -0,0 +1,170 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi;
+use crate::{
+    feature_state_ex, patches, Error, Guid, InstallContext, InstallState, PatchInfo, PatchState,
+    ProductInfo, Result,
+};
+use std::cell::OnceCell;
+
+/// The maximum length of a feature name, including the null terminator.
+const FEATURE_LEN: usize = 39;
+
+/// A product installed for the current user or per-machine, returned from [`inventory()`].
+///
+/// Features and patches are only queried when first requested.
+#[derive(Debug)]
+pub struct InstalledProduct {
+    product_code: Guid,
+    context: InstallContext,
+    sid: Option<String>,
+    info: ProductInfo,
+    features: OnceCell<Vec<InstalledFeature>>,
+    patches: OnceCell<Vec<PatchInfo>>,
+}
+
+impl InstalledProduct {
+    /// Gets the product code GUID.
+    pub fn product_code(&self) -> Guid {
+        self.product_code
+    }
+
+    /// Gets the context in which the product is installed.
+    pub fn context(&self) -> InstallContext {
+        self.context
+    }
+
+    /// Gets the security identifier (SID) of the user for which the product is installed,
+    /// or `None` if installed per-machine.
+    pub fn sid(&self) -> Option<&str> {
+        self.sid.as_deref()
+    }
+
+    /// Gets common registered information about the product.
+    pub fn info(&self) -> &ProductInfo {
+        &self.info
+    }
+
+    /// Gets the features of the product and their installed states.
+    pub fn features(&self) -> Result<&[InstalledFeature]> {
+        if let Some(features) = self.features.get() {
+            return Ok(features);
+        }
+
+        let features = self.query_features()?;
+        Ok(self.features.get_or_init(|| features))
+    }
+
+    /// Gets all patches registered for the product.
+    pub fn patches(&self) -> Result<&[PatchInfo]> {
+        if let Some(patches) = self.patches.get() {
+            return Ok(patches);
+        }
+
+        let patches = patches(Some(self.product_code), self.context, PatchState::ALL)?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(self.patches.get_or_init(|| patches))
+    }
+
+    fn query_features(&self) -> Result<Vec<InstalledFeature>> {
+        let product_code = ffi::to_wide(&self.product_code)?;
+        let mut features = Vec::new();
+
+        for index in 0.. {
+            let mut name = [0u16; FEATURE_LEN];
+            let mut parent = [0u16; FEATURE_LEN];
+            let ret = unsafe {
+                ffi::MsiEnumFeatures(
+                    product_code.as_ptr(),
+                    index,
+                    name.as_mut_ptr(),
+                    parent.as_mut_ptr(),
+                )
+            };
+            match ret {
+                ffi::ERROR_SUCCESS => {}
+                ffi::ERROR_NO_MORE_ITEMS => break,
+                _ => return Err(Error::from_error_code(ret)),
+            }
+
+            let name = ffi::from_terminated(&name)?;
+            let parent = ffi::from_terminated(&parent)?;
+            let state = feature_state_ex(self.product_code, None, self.context, &name)?;
+            features.push(InstalledFeature {
+                name,
+                parent: (!parent.is_empty()).then_some(parent),
+                state,
+            });
+        }
+
+        Ok(features)
+    }
+}
+
+/// A feature of an [`InstalledProduct`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InstalledFeature {
+    /// The name of the feature.
+    pub name: String,
+    /// The name of the parent feature, if any.
+    pub parent: Option<String>,
+    /// The installed state of the feature.
+    pub state: InstallState,
+}
+
+/// Gets all products installed for the current user or per-machine along with their registered information.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::inventory;
+///
+/// for product in inventory()? {
+///     let info = product.info();
+///     println!("{} {} ({} patches)", info.name, info.version, product.patches()?.len());
+/// }
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub fn inventory() -> Result<Vec<InstalledProduct>> {
+    let mut products = Vec::new();
+
+    for index in 0.. {
+        let mut product_code = [0u16; ffi::GUID_LEN];
+        let mut context = 0i32;
+
+        let sid = unsafe {
+            ffi::get_string(|sid, sid_len| {
+                ffi::MsiEnumProductsEx(
+                    std::ptr::null(),
+                    std::ptr::null(),
+                    InstallContext::ALL.bits(),
+                    index,
+                    product_code.as_mut_ptr(),
+                    &mut context,
+                    sid,
+                    sid_len,
+                )
+            })
+        };
+        let sid = match sid {
+            Ok(sid) => sid,
+            Err(err) if err.is(ffi::ERROR_NO_MORE_ITEMS) => break,
+            Err(err) => return Err(err),
+        };
+
+        let product_code = Guid::from_terminated(&product_code)?;
+        let context = InstallContext::from_bits_retain(context as u32);
+        let info = ProductInfo::query_ex(product_code, None, context)?;
+        products.push(InstalledProduct {
+            product_code,
+            context,
+            sid: (!sid.is_empty()).then_some(sid),
+            info,
+            features: OnceCell::new(),
+            patches: OnceCell::new(),
+        });
+    }
+
+    Ok(products)
+}
@@ -5,34 +5,120 @@
 #![cfg_attr(feature = "nightly", feature(min_specialization, try_trait_v2))]
 #![doc = include_str!("../README.md")]
 
-// Fail fast on non-Windows platforms.
-#[cfg(not(target_os = "windows"))]
-compile_error!("supported on windows only");
+// Fail fast on non-Windows platforms unless msi.dll is replaced with the in-memory mock.
+#[cfg(not(any(target_os = "windows", feature = "mock")))]
+compile_error!(
+    "supported on windows only; enable the \"mock\" feature to build on other platforms"
+);
+
+#[cfg(all(feature = "mock", any(feature = "delay-load", feature = "windows-sys")))]
+compile_error!("the \"mock\" feature cannot be combined with \"delay-load\" or \"windows-sys\"");
+
+#[cfg(all(not(target_os = "windows"), feature = "trace-ffi"))]
+compile_error!("the \"trace-ffi\" feature is supported on windows only");
 
 // See https://docs.microsoft.com/windows/win32/msi/automation-interface-reference
 // for inspiration for the shape of this API.
 
+#[macro_use]
+mod macros;
+
+mod advertise;
+mod attributes;
+mod component;
+mod custom_action;
 mod database;
 mod error;
 mod ffi;
+mod guid;
+mod handles;
+mod installer;
+mod inventory;
+pub mod logparse;
+mod ops;
+mod package;
+mod patch;
+mod preview;
+mod product;
+mod profiler;
 mod record;
+mod registry;
+pub mod replay;
+mod runner;
 mod session;
+mod source_list;
+pub mod standard_actions;
+mod subscriber;
+pub mod tables;
+pub mod test_util;
+pub mod testing;
+mod ui;
 mod view;
+pub mod wca;
 
+pub use advertise::{
+    advertise_product, advertise_script_info, AdvertiseOptions, AdvertisePlatform, AdvertiseTarget,
+    ScriptInfo,
+};
+pub use attributes::{
+    ComponentAttributes, ControlAttributes, DialogAttributes, FeatureAttributes, FileAttributes,
+    UpgradeAttributes,
+};
+pub use component::{
+    component_state, install_missing_component, install_missing_file, installed_components,
+    installed_components_ex, locate_component, product_code_from_component, provide_component,
+    provide_qualified_component, provide_qualified_component_ex, shortcut_target, Clients,
+    ClientsEx, Component, ComponentClient, Components, ComponentsEx, InstalledComponent,
+    ShortcutTarget,
+};
+pub use custom_action::{CustomActionCode, CustomActionSource, CustomActionType};
 pub use database::Database;
+pub use error::codes;
 #[cfg(feature = "nightly")]
 pub use error::experimental::CustomActionResult;
-pub use error::{Error, ErrorKind, Result};
-pub use record::{Field, Record};
-pub use session::{MessageType, RunMode, Session};
-pub use view::{ModifyMode, View};
+pub use error::{Error, ErrorKind, IntoCustomActionResult, Result};
+pub use ffi::{decoding, set_decoding, Decoding, BOOL, MSIHANDLE};
+pub use guid::Guid;
+#[cfg(feature = "debug-handles")]
+pub use handles::HandleLeakGuard;
+pub use installer::{
+    enable_log, install_product, InstallContext, InstallLevel, InstallState, LogAttributes,
+    LogModes,
+};
+pub use inventory::{inventory, InstalledFeature, InstalledProduct};
+pub use ops::{DatabaseOps, SessionOps, ViewOps};
+pub use package::{OpenPackageOptions, Package};
+pub use patch::{
+    applicable_patches, apply_patches, patch_sequence, patches, remove_patches, InstallType, Patch,
+    PatchApplicability, PatchData, PatchInfo, PatchProperty, PatchSpec, PatchState, Patches,
+};
+pub use preview::UiPreview;
+pub use product::{
+    collect_user_info, configure_feature, configure_product, feature_state, feature_state_ex,
+    installed_products, reinstall_feature, reinstall_product, use_feature, FeatureInfo,
+    InstallFeatureAttributes, InstallMode, Product, ProductInfo, Products, ReinstallMode, UserInfo,
+    UserInfoState,
+};
+pub use profiler::{ProfileScope, Profiler};
+pub use record::{Field, FromRecord, Record, ToRecord};
+pub use registry::{MultiStringMode, RegistryRoot, RegistryRow, RegistryValue, RemoveRegistryRow};
+pub use runner::{InstallEvent, Installation, Installer, Progress};
+pub use session::{CachedSession, MessageType, RunMode, Session, SyncSession};
+pub use source_list::{MediaDisk, MediaDisks, SourceList};
+#[cfg(feature = "tracing")]
+pub use subscriber::SessionLayer;
+pub use ui::{
+    set_internal_ui, ExternalUi, ExternalUiText, InstallUiLevel, InstallUiModifiers, MessageResult,
+};
+pub use view::{ColumnInfo, ModifyMode, View};
 
 pub mod prelude {
     #[cfg(feature = "nightly")]
     pub use crate::error::experimental::CustomActionResult::{self, *};
     // Export objects and enums used in inputs to those objects' methods.
     pub use crate::{
-        Database, Error, Field, MessageType, ModifyMode, Record, Result, RunMode, Session, View,
+        ColumnInfo, Database, Error, Field, IntoCustomActionResult, MessageType, ModifyMode,
+        Record, Result, RunMode, Session, View,
     };
 }
 
@@ -0,0 +1,399 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Parses verbose logs written by `msiexec /l*v` into structured events to assert on in tests
+//! or to triage failed installations.
+//!
+//! Lines that are not recognized are skipped, so logs from any version of Windows Installer can be parsed.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use msica::logparse::{self, LogEvent};
+//!
+//! let log = logparse::read(r"C:\Temp\install.log")?;
+//! for entry in logparse::parse(&log) {
+//!     if let LogEvent::CustomActionReturned { action, code } = entry.event {
+//!         println!("line {}: {action} returned {code}", entry.line);
+//!     }
+//! }
+//!
+//! for failure in logparse::failures(&log, 20) {
+//!     println!("{} failed on line {}:", failure.action, failure.line);
+//!     for line in failure.lines {
+//!         println!("  {line}");
+//!     }
+//! }
+//! # Ok::<(), msica::Error>(())
+//! ```
+
+use crate::Result;
+use std::{collections::HashMap, fs, path::Path};
+
+/// The value logged when an action fails.
+const RETURN_VALUE_FAILURE: u32 = 3;
+
+/// Reads a log, which is UTF-16 if it starts with a byte order mark and UTF-8 otherwise.
+pub fn read(path: impl AsRef<Path>) -> Result<String> {
+    Ok(decode(&fs::read(path)?))
+}
+
+/// Decodes a log, which is UTF-16 if it starts with a byte order mark and UTF-8 otherwise.
+///
+/// Invalid characters are replaced with `U+FFFD`.
+pub fn decode(bytes: &[u8]) -> String {
+    match bytes.strip_prefix(&[0xff, 0xfe]) {
+        Some(wide) => {
+            let wide: Vec<u16> = wide
+                .as_chunks::<2>()
+                .0
+                .iter()
+                .map(|c| u16::from_le_bytes(*c))
+                .collect();
+            String::from_utf16_lossy(&wide)
+        }
+        None => String::from_utf8_lossy(bytes).into_owned(),
+    }
+}
+
+/// The process that wrote a line to the log.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum LogContext {
+    /// The client process that runs the user interface sequence.
+    Client,
+    /// The server process that runs the execute sequence.
+    Server,
+    /// A nested installation.
+    Nested,
+}
+
+impl LogContext {
+    fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_lowercase() {
+            'c' => Some(Self::Client),
+            's' => Some(Self::Server),
+            'n' => Some(Self::Nested),
+            _ => None,
+        }
+    }
+}
+
+/// How a property changed.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PropertyChange<'a> {
+    /// The property was set to `value`.
+    Added { value: &'a str },
+    /// The property was changed from `previous` to `value`.
+    Modified { previous: &'a str, value: &'a str },
+    /// The property with `previous` value was removed.
+    Deleted { previous: &'a str },
+}
+
+/// An event parsed from a line of a log.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum LogEvent<'a> {
+    /// A standard or custom action started at `time`.
+    ActionStart { time: &'a str, action: &'a str },
+    /// An action ended at `time` with `return_value`: 1 for success, 2 if canceled, 3 for failure, or 0 if skipped.
+    ActionEnded {
+        time: &'a str,
+        action: &'a str,
+        return_value: u32,
+    },
+    /// A property was set, changed, or removed.
+    PropertyChange {
+        name: &'a str,
+        change: PropertyChange<'a>,
+    },
+    /// The final value of a property, which is logged when the installation ends.
+    Property { name: &'a str, value: &'a str },
+    /// A custom action returned an error code e.g., `ERROR_INSTALL_FAILURE`.
+    CustomActionReturned { action: &'a str, code: u32 },
+    /// An error message was logged e.g., error 1722 when a custom action failed.
+    Error { code: u32, message: &'a str },
+    /// The engine returned an exit code e.g., `ERROR_INSTALL_FAILURE`.
+    EngineReturned { code: u32 },
+}
+
+/// An event and where it was logged.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct LogEntry<'a> {
+    /// The 1-based line number.
+    pub line: usize,
+    /// The process that logged the event, if known.
+    pub context: Option<LogContext>,
+    /// The event.
+    pub event: LogEvent<'a>,
+}
+
+/// Parses events from a log.
+pub fn parse(log: &str) -> impl Iterator<Item = LogEntry<'_>> {
+    log.lines().enumerate().filter_map(|(i, line)| {
+        let (context, event) = parse_line(line)?;
+        Some(LogEntry {
+            line: i + 1,
+            context,
+            event,
+        })
+    })
+}
+
+/// Parses an event from a single `line` of a log.
+pub fn parse_line(line: &str) -> Option<(Option<LogContext>, LogEvent<'_>)> {
+    let (context, message) = strip_engine_prefix(line);
+
+    if let Some(rest) = message.strip_prefix("Action start ") {
+        let (time, action) = rest.split_once(": ")?;
+        let action = action.strip_suffix('.')?;
+        return Some((context, LogEvent::ActionStart { time, action }));
+    }
+
+    if let Some(rest) = message.strip_prefix("Action ended ") {
+        let (time, rest) = rest.split_once(": ")?;
+        let (action, return_value) = rest.rsplit_once(". Return value ")?;
+        let return_value = return_value.trim_end_matches('.').parse().ok()?;
+        return Some((
+            context,
+            LogEvent::ActionEnded {
+                time,
+                action,
+                return_value,
+            },
+        ));
+    }
+
+    if let Some(rest) = message.strip_prefix("PROPERTY CHANGE: ") {
+        return parse_property_change(rest).map(|event| (context, event));
+    }
+
+    if let Some(rest) = message.strip_prefix("Property(") {
+        let (c, rest) = rest.split_once("): ")?;
+        let context = LogContext::from_char(c.chars().next()?);
+        let (name, value) = rest.split_once(" = ")?;
+        return Some((context, LogEvent::Property { name, value }));
+    }
+
+    if let Some(rest) = message.strip_prefix("CustomAction ") {
+        let (action, rest) = rest.split_once(" returned actual error code ")?;
+        let code = leading_number(rest)?;
+        return Some((context, LogEvent::CustomActionReturned { action, code }));
+    }
+
+    if let Some(rest) = message.strip_prefix("MainEngineThread is returning ") {
+        let code = rest.trim().parse().ok()?;
+        return Some((context, LogEvent::EngineReturned { code }));
+    }
+
+    let error = message
+        .strip_prefix("Error ")
+        .or_else(|| message.strip_prefix("DEBUG: Error "))?;
+    let code = leading_number(error)?;
+    let message = error
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .trim_start_matches(['.', ':'])
+        .trim_start();
+    Some((context, LogEvent::Error { code, message }))
+}
+
+/// Strips the `MSI (s) (A4:B8) [12:00:00:000]: ` prefix the engine writes before its own messages.
+fn strip_engine_prefix(line: &str) -> (Option<LogContext>, &str) {
+    let parsed = line.strip_prefix("MSI (").and_then(|rest| {
+        let context = LogContext::from_char(rest.chars().next()?);
+        let (_, message) = rest.split_once("]: ")?;
+        Some((context, message))
+    });
+    parsed.unwrap_or((None, line))
+}
+
+fn parse_property_change(rest: &str) -> Option<LogEvent<'_>> {
+    let (verb, rest) = rest.split_once(' ')?;
+    let (name, rest) = rest.split_once(" property. ")?;
+    let change = match verb {
+        "Adding" => PropertyChange::Added {
+            value: quoted(rest.strip_prefix("Its value is ")?)?,
+        },
+        "Modifying" => {
+            let rest = rest.strip_prefix("Its current value is '")?;
+            let (previous, value) = rest.split_once("'. Its new value: ")?;
+            PropertyChange::Modified {
+                previous,
+                value: quoted(value)?,
+            }
+        }
+        "Deleting" => PropertyChange::Deleted {
+            previous: quoted(rest.strip_prefix("Its current value is ")?)?,
+        },
+        _ => return None,
+    };
+    Some(LogEvent::PropertyChange { name, change })
+}
+
+/// Gets the value from `'value'.`
+fn quoted(s: &str) -> Option<&str> {
+    s.strip_prefix('\'')?.strip_suffix("'.")
+}
+
+fn leading_number(s: &str) -> Option<u32> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    s[..end].parse().ok()
+}
+
+/// An action that failed and the lines logged while it ran.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Failure<'a> {
+    /// The name of the action that failed.
+    pub action: &'a str,
+    /// The 1-based line number where the action ended.
+    pub line: usize,
+    /// Lines logged after the action started and before it ended, which usually explain why it failed.
+    pub lines: Vec<&'a str>,
+}
+
+/// Finds actions that failed and up to `max_lines` lines logged before each ended.
+///
+/// When a custom action fails, the installation typically fails with `ERROR_INSTALL_FAILURE` (1603), and the cause
+/// is logged shortly before `Return value 3`. Deferred custom actions fail within `InstallFinalize`, and the installation
+/// itself fails within `INSTALL`, so failures are returned for each.
+pub fn failures(log: &str, max_lines: usize) -> Vec<Failure<'_>> {
+    let lines: Vec<&str> = log.lines().collect();
+    let mut starts = HashMap::new();
+    let mut failures = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        match parse_line(line) {
+            Some((_, LogEvent::ActionStart { action, .. })) => {
+                starts.insert(action, i + 1);
+            }
+            Some((
+                _,
+                LogEvent::ActionEnded {
+                    action,
+                    return_value: RETURN_VALUE_FAILURE,
+                    ..
+                },
+            )) => {
+                let start = starts.get(action).copied().unwrap_or(0);
+                let start = start.max(i.saturating_sub(max_lines));
+                failures.push(Failure {
+                    action,
+                    line: i + 1,
+                    lines: lines[start..i].to_vec(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = "\
+=== Verbose logging started: 1/1/2024  12:00:00  Build type: SHIP UNICODE 5.00.10011.00  Calling process: C:\\Windows\\system32\\msiexec.exe ===\r
+MSI (s) (A4:B8) [12:00:00:001]: PROPERTY CHANGE: Adding INSTALLDIR property. Its value is 'C:\\Example\\'.\r
+Action start 12:00:01: MyCustomAction.\r
+MSI (s) (A4:B8) [12:00:01:000]: PROPERTY CHANGE: Modifying INSTALLDIR property. Its current value is 'C:\\Example\\'. Its new value: 'C:\\It's\\'.\r
+MSI (s) (A4:B8) [12:00:01:001]: PROPERTY CHANGE: Deleting SOURCEDIR property. Its current value is 'C:\\Temp\\'.\r
+CustomAction MyCustomAction returned actual error code 1603 (note this may not be 100% accurate if translation happened inside sandbox)\r
+Error 1722. There is a problem with this Windows Installer package.\r
+Action ended 12:00:02: MyCustomAction. Return value 3.\r
+Action ended 12:00:02: INSTALL. Return value 3.\r
+MSI (s) (A4:B8) [12:00:02:000]: MainEngineThread is returning 1603\r
+Property(S): INSTALLDIR = C:\\It's\\\r
+Property(C): EMPTY = \r
+";
+
+    #[test]
+    fn parse_events() {
+        let entries: Vec<_> = parse(LOG).collect();
+        let events: Vec<_> = entries.iter().map(|entry| entry.event).collect();
+        assert_eq!(
+            vec![
+                LogEvent::PropertyChange {
+                    name: "INSTALLDIR",
+                    change: PropertyChange::Added {
+                        value: r"C:\Example\"
+                    },
+                },
+                LogEvent::ActionStart {
+                    time: "12:00:01",
+                    action: "MyCustomAction"
+                },
+                LogEvent::PropertyChange {
+                    name: "INSTALLDIR",
+                    change: PropertyChange::Modified {
+                        previous: r"C:\Example\",
+                        value: r"C:\It's\"
+                    },
+                },
+                LogEvent::PropertyChange {
+                    name: "SOURCEDIR",
+                    change: PropertyChange::Deleted {
+                        previous: r"C:\Temp\"
+                    },
+                },
+                LogEvent::CustomActionReturned {
+                    action: "MyCustomAction",
+                    code: 1603
+                },
+                LogEvent::Error {
+                    code: 1722,
+                    message: "There is a problem with this Windows Installer package."
+                },
+                LogEvent::ActionEnded {
+                    time: "12:00:02",
+                    action: "MyCustomAction",
+                    return_value: 3
+                },
+                LogEvent::ActionEnded {
+                    time: "12:00:02",
+                    action: "INSTALL",
+                    return_value: 3
+                },
+                LogEvent::EngineReturned { code: 1603 },
+                LogEvent::Property {
+                    name: "INSTALLDIR",
+                    value: r"C:\It's\"
+                },
+                LogEvent::Property {
+                    name: "EMPTY",
+                    value: ""
+                },
+            ],
+            events
+        );
+
+        assert_eq!(2, entries[0].line);
+        assert_eq!(Some(LogContext::Server), entries[0].context);
+        assert_eq!(None, entries[1].context);
+        assert_eq!(Some(LogContext::Client), entries[10].context);
+    }
+
+    #[test]
+    fn failure_context() {
+        let failures = failures(LOG, 3);
+        assert_eq!(2, failures.len());
+        assert_eq!("MyCustomAction", failures[0].action);
+        assert_eq!(8, failures[0].line);
+        assert_eq!(3, failures[0].lines.len());
+        assert!(failures[0].lines[0].contains("Deleting SOURCEDIR"));
+
+        // INSTALL never started, so lines are limited only by max_lines.
+        assert_eq!("INSTALL", failures[1].action);
+        assert_eq!(3, failures[1].lines.len());
+    }
+
+    #[test]
+    fn decode_utf16() {
+        let wide: Vec<u8> = [0xfeff_u16, b'o' as u16, b'k' as u16]
+            .iter()
+            .flat_map(|c| c.to_le_bytes())
+            .collect();
+        assert_eq!("ok", decode(&wide));
+        assert_eq!("ok", decode(b"ok"));
+    }
+}
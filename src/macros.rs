@@ -0,0 +1,209 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+/// Defines a transparent bit flags type with named constants.
+///
+/// This avoids taking a dependency on the `bitflags` crate for the handful of operations we need.
+macro_rules! flags {
+    (
+        $(#[$outer:meta])*
+        $vis:vis struct $name:ident: $ty:ty {
+            $(
+                $(#[$inner:meta])*
+                const $flag:ident = $value:expr;
+            )*
+        }
+    ) => {
+        $(#[$outer])*
+        #[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+        #[repr(transparent)]
+        $vis struct $name($ty);
+
+        #[allow(dead_code)]
+        impl $name {
+            $(
+                $(#[$inner])*
+                pub const $flag: Self = Self($value);
+            )*
+
+            /// Gets a value with no flags set.
+            #[inline]
+            pub const fn empty() -> Self {
+                Self(0)
+            }
+
+            /// Gets a value with all named flags set.
+            #[inline]
+            pub const fn all() -> Self {
+                Self(0 $(| $value)*)
+            }
+
+            /// Gets the raw bits.
+            #[inline]
+            pub const fn bits(&self) -> $ty {
+                self.0
+            }
+
+            /// Creates a value from raw bits, retaining any bits not defined by named flags.
+            #[inline]
+            pub const fn from_bits_retain(bits: $ty) -> Self {
+                Self(bits)
+            }
+
+            /// Gets whether no flags are set.
+            #[inline]
+            pub const fn is_empty(&self) -> bool {
+                self.0 == 0
+            }
+
+            /// Gets whether all flags in `other` are set.
+            #[inline]
+            pub const fn contains(&self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// Gets whether any flags in `other` are set.
+            #[inline]
+            pub const fn intersects(&self, other: Self) -> bool {
+                self.0 & other.0 != 0
+            }
+
+            /// Sets all flags in `other`.
+            #[inline]
+            pub fn insert(&mut self, other: Self) {
+                self.0 |= other.0;
+            }
+
+            /// Clears all flags in `other`.
+            #[inline]
+            pub fn remove(&mut self, other: Self) {
+                self.0 &= !other.0;
+            }
+
+            /// Sets or clears all flags in `other`.
+            #[inline]
+            pub fn set(&mut self, other: Self, value: bool) {
+                match value {
+                    true => self.insert(other),
+                    false => self.remove(other),
+                }
+            }
+        }
+
+        impl ::std::ops::BitOr for $name {
+            type Output = Self;
+            fn bitor(self, rhs: Self) -> Self::Output {
+                Self(self.0 | rhs.0)
+            }
+        }
+
+        impl ::std::ops::BitOrAssign for $name {
+            fn bitor_assign(&mut self, rhs: Self) {
+                self.0 |= rhs.0;
+            }
+        }
+
+        impl ::std::ops::BitAnd for $name {
+            type Output = Self;
+            fn bitand(self, rhs: Self) -> Self::Output {
+                Self(self.0 & rhs.0)
+            }
+        }
+
+        impl ::std::ops::BitAndAssign for $name {
+            fn bitand_assign(&mut self, rhs: Self) {
+                self.0 &= rhs.0;
+            }
+        }
+
+        impl ::std::ops::Sub for $name {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self::Output {
+                Self(self.0 & !rhs.0)
+            }
+        }
+
+        impl From<$name> for $ty {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                write!(f, "{}(", stringify!($name))?;
+                let mut remaining = self.0;
+                let mut first = true;
+                $(
+                    if $value != 0 && remaining & $value == $value && self.0 & $value == $value {
+                        if !first {
+                            f.write_str(" | ")?;
+                        }
+                        f.write_str(stringify!($flag))?;
+                        remaining &= !$value;
+                        first = false;
+                    }
+                )*
+                if remaining != 0 {
+                    if !first {
+                        f.write_str(" | ")?;
+                    }
+                    write!(f, "{:#x}", remaining)?;
+                    first = false;
+                }
+                if first {
+                    f.write_str("empty")?;
+                }
+                f.write_str(")")
+            }
+        }
+    };
+}
+
+/// Defines a non-exhaustive, fieldless enum with explicit discriminants
+/// that converts to its representation with `From` and from it with `TryFrom`.
+///
+/// Converting with `TryFrom` returns an error for unknown values instead of casting to an invalid variant.
+macro_rules! repr_enum {
+    (
+        $(#[$outer:meta])*
+        $vis:vis enum $name:ident: $ty:ident {
+            $(
+                $(#[$inner:meta])*
+                $variant:ident = $value:expr,
+            )*
+        }
+    ) => {
+        $(#[$outer])*
+        #[non_exhaustive]
+        #[repr($ty)]
+        $vis enum $name {
+            $(
+                $(#[$inner])*
+                $variant = $value,
+            )*
+        }
+
+        impl From<$name> for $ty {
+            fn from(value: $name) -> Self {
+                value as $ty
+            }
+        }
+
+        impl TryFrom<$ty> for $name {
+            type Error = crate::Error;
+
+            fn try_from(value: $ty) -> crate::Result<Self> {
+                match value {
+                    $(
+                        v if v == $name::$variant as $ty => Ok($name::$variant),
+                    )*
+                    _ => Err(crate::Error::new(
+                        crate::ErrorKind::DataConversion,
+                        format!(concat!("unknown ", stringify!($name), " value: {:#x}"), value),
+                    )),
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,144 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Traits for session and database operations so custom action logic can be tested without an installation.
+
+use crate::tables::Row;
+use crate::{Database, MessageType, ModifyMode, Record, Result, RunMode, Session, View};
+use std::fmt::Display;
+
+/// Operations on an installation session, implemented by [`Session`] and test doubles
+/// e.g., `MockSession` in the `testing` module.
+///
+/// # Example
+///
+/// Write custom action logic against the trait and call it with a [`Session`] from the custom action entry point:
+///
+/// ```no_run
+/// use msica::prelude::*;
+/// use msica::SessionOps;
+///
+/// fn schedule<S: SessionOps>(session: &S) -> Result<()> {
+///     let dir = session.property("INSTALLDIR")?;
+///     if dir.is_empty() {
+///         return Ok(());
+///     }
+///     session.do_deferred_action("MyDeferredCustomAction", &dir)
+/// }
+///
+/// #[no_mangle]
+/// pub extern "C" fn MyCustomAction(session: Session) -> u32 {
+///     schedule(&session).into_ca_result(&session)
+/// }
+/// ```
+pub trait SessionOps {
+    /// The type of database returned from [`SessionOps::database()`].
+    type Database: DatabaseOps;
+
+    /// Gets the database for the session.
+    fn database(&self) -> Self::Database;
+
+    /// Runs the action as specified, or `INSTALL` if `None`.
+    fn do_action(&self, action: Option<&str>) -> Result<()>;
+
+    /// Sets custom action data and schedules a deferred custom action.
+    ///
+    /// The `custom_action_data` may be any value that can be formatted e.g., a string or integer.
+    fn do_deferred_action(&self, action: &str, custom_action_data: impl Display) -> Result<()>;
+
+    /// Processes a [`Record`] within the session.
+    fn message(&self, kind: MessageType, record: &Record) -> i32;
+
+    /// Gets whether the specified run mode is set.
+    fn mode(&self, mode: RunMode) -> bool;
+
+    /// Gets the value of the named property, or an empty string if undefined.
+    fn property(&self, name: &str) -> Result<String>;
+
+    /// Sets the value of the named property. Pass `None` to clear the property.
+    fn set_property(&self, name: &str, value: Option<&str>) -> Result<()>;
+}
+
+/// Operations on a database, implemented by [`Database`] and test doubles e.g., `MockDatabase` in the `testing` module.
+pub trait DatabaseOps {
+    /// The type of view returned from [`DatabaseOps::open_view()`].
+    type View: ViewOps;
+
+    /// Prepares a database query and creates a view.
+    fn open_view(&self, sql: &str) -> Result<Self::View>;
+
+    /// Gets all rows of a standard table e.g., [`FileRow`](crate::tables::FileRow).
+    fn rows<T: Row>(&self) -> Result<Vec<T>> {
+        let view = self.open_view(&T::query())?;
+        view.execute(None)?;
+        view.map(|record| T::from_record(&record)).collect()
+    }
+
+    /// Inserts a row into a standard table e.g., with [`ModifyMode::InsertTemporary`] from a custom action.
+    fn insert_row<T: Row>(&self, row: &T, mode: ModifyMode) -> Result<()> {
+        let view = self.open_view(&T::query())?;
+        view.execute(None)?;
+        view.modify(mode, &row.to_record()?)
+    }
+}
+
+/// Operations on a view, implemented by [`View`] and test doubles e.g., `MockView` in the `testing` module.
+///
+/// Fetch records by iterating the view after calling [`ViewOps::execute()`].
+pub trait ViewOps: Iterator<Item = Record> {
+    /// Executes the query, replacing each `?` with the corresponding field of the `record`.
+    fn execute(&self, record: Option<Record>) -> Result<()>;
+
+    /// Updates a fetched record, or inserts a new record.
+    fn modify(&self, mode: ModifyMode, record: &Record) -> Result<()>;
+}
+
+impl SessionOps for Session {
+    type Database = Database;
+
+    fn database(&self) -> Database {
+        Session::database(self)
+    }
+
+    fn do_action(&self, action: Option<&str>) -> Result<()> {
+        Session::do_action(self, action)
+    }
+
+    fn do_deferred_action(&self, action: &str, custom_action_data: impl Display) -> Result<()> {
+        Session::do_deferred_action(self, action, custom_action_data)
+    }
+
+    fn message(&self, kind: MessageType, record: &Record) -> i32 {
+        Session::message(self, kind, record)
+    }
+
+    fn mode(&self, mode: RunMode) -> bool {
+        Session::mode(self, mode)
+    }
+
+    fn property(&self, name: &str) -> Result<String> {
+        Session::property(self, name)
+    }
+
+    fn set_property(&self, name: &str, value: Option<&str>) -> Result<()> {
+        Session::set_property(self, name, value)
+    }
+}
+
+impl DatabaseOps for Database {
+    type View = View;
+
+    fn open_view(&self, sql: &str) -> Result<View> {
+        Database::open_view(self, sql)
+    }
+}
+
+impl ViewOps for View {
+    fn execute(&self, record: Option<Record>) -> Result<()> {
+        View::execute(self, record)
+    }
+
+    fn modify(&self, mode: ModifyMode, record: &Record) -> Result<()> {
+        View::modify(self, mode, record)
+    }
+}
@@ -0,0 +1,64 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi;
+use crate::{Error, Result, Session};
+use std::{ops::Deref, path::Path};
+
+flags! {
+    /// Options passed to [`Session::open_package()`].
+    pub struct OpenPackageOptions: u32 {
+        /// Ignore the current machine state when opening the package e.g., to evaluate conditions
+        /// as if the product was not installed.
+        const IGNORE_MACHINE_STATE = 1 << 0;
+    }
+}
+
+/// A [`Session`] for a package opened with [`Session::open_package()`], which is closed when dropped.
+///
+/// Use it like any other [`Session`] to read properties, evaluate conditions, or query the database
+/// without running an installation.
+pub struct Package {
+    session: Session,
+    _h: ffi::PMSIHANDLE<ffi::kind::Session>,
+}
+
+impl Session {
+    /// Opens a Windows Installer package (.msi) and returns a [`Package`] that can be used as a [`Session`].
+    ///
+    /// This is meant for tools and tests. Custom actions should use the [`Session`] they are passed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use msica::{OpenPackageOptions, Session};
+    ///
+    /// let package = Session::open_package("example.msi", OpenPackageOptions::IGNORE_MACHINE_STATE)?;
+    /// println!("{}", package.property("ProductName")?);
+    /// # Ok::<(), msica::Error>(())
+    /// ```
+    #[track_caller]
+    pub fn open_package(path: impl AsRef<Path>, options: OpenPackageOptions) -> Result<Package> {
+        unsafe {
+            let mut h = ffi::SessionHandle::null();
+            let path = ffi::to_wide_os(path.as_ref().as_os_str())?;
+            let ret = ffi::MsiOpenPackageEx(path.as_ptr(), options.bits(), &mut h);
+            if ret != ffi::ERROR_SUCCESS {
+                return Err(Error::from_last_error(ret));
+            }
+
+            Ok(Package {
+                session: Session::from_handle(h),
+                _h: h.to_owned(),
+            })
+        }
+    }
+}
+
+impl Deref for Package {
+    type Target = Session;
+
+    fn deref(&self) -> &Self::Target {
+        &self.session
+    }
+}
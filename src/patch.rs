@@ -0,0 +1,630 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi;
+use crate::{Error, ErrorKind, Guid, InstallContext, Result};
+use std::{
+    ffi::{OsStr, OsString},
+    num::NonZeroU32,
+    path::{Path, PathBuf},
+};
+
+/// A patch package (.msp) passed to [`apply_patches()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PatchSpec {
+    path: PathBuf,
+}
+
+impl PatchSpec {
+    /// Creates a [`PatchSpec`] for the patch package at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Gets the path to the patch package.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl<T: Into<PathBuf>> From<T> for PatchSpec {
+    fn from(path: T) -> Self {
+        Self::new(path)
+    }
+}
+
+/// A patch passed to [`applicable_patches()`] or [`patch_sequence()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PatchData {
+    /// The path to a patch package (.msp).
+    File(PathBuf),
+    /// The path to an XML file describing a patch e.g., extracted from a patch package.
+    XmlPath(PathBuf),
+    /// XML describing a patch.
+    XmlBlob(String),
+}
+
+impl From<PatchSpec> for PatchData {
+    fn from(patch: PatchSpec) -> Self {
+        PatchData::File(patch.path)
+    }
+}
+
+/// Whether a patch applies and in what order, returned from [`applicable_patches()`] or [`patch_sequence()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PatchApplicability {
+    /// The patch that was passed in.
+    pub patch: PatchData,
+    /// The zero-based order in which the patch is applied, or `None` if the patch does not apply.
+    pub order: Option<u32>,
+    /// `ERROR_SUCCESS` if the patch applies; otherwise, the error code why it does not.
+    pub status: u32,
+}
+
+impl PatchApplicability {
+    /// Gets whether the patch applies.
+    pub fn is_applicable(&self) -> bool {
+        self.order.is_some()
+    }
+}
+
+flags! {
+    /// The states of patches enumerated by [`patches()`].
+    pub struct PatchState: u32 {
+        /// Patches applied to a product.
+        const APPLIED = 1 << 0;
+        /// Patches superseded by other patches.
+        const SUPERSEDED = 1 << 1;
+        /// Patches obsoleted by other patches.
+        const OBSOLETED = 1 << 2;
+        /// Patches registered but not yet applied.
+        const REGISTERED = 1 << 3;
+    }
+}
+
+impl PatchState {
+    /// All patch states.
+    pub const ALL: Self =
+        Self(Self::APPLIED.0 | Self::SUPERSEDED.0 | Self::OBSOLETED.0 | Self::REGISTERED.0);
+}
+
+/// A patch applied to a product, returned from [`patches()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PatchInfo {
+    /// The patch code GUID.
+    pub patch_code: Guid,
+    /// The product code GUID of the product to which the patch is applied.
+    pub product_code: Guid,
+    /// The context in which the product is installed.
+    pub context: InstallContext,
+    /// The security identifier (SID) of the user for which the product is installed,
+    /// or `None` if the product is installed per-machine.
+    pub sid: Option<String>,
+}
+
+/// A patch applied to a product for which information can be queried with [`Patch::info()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Patch {
+    patch_code: Guid,
+    product_code: Guid,
+    context: InstallContext,
+    sid: Option<String>,
+}
+
+impl Patch {
+    /// Creates a [`Patch`] for a patch applied to a product installed in a single `context`.
+    ///
+    /// Pass `None` for the `sid` to use the current user, which is required for [`InstallContext::MACHINE`].
+    pub fn new(
+        patch_code: Guid,
+        product_code: Guid,
+        sid: Option<&str>,
+        context: InstallContext,
+    ) -> Self {
+        Self {
+            patch_code,
+            product_code,
+            context,
+            sid: sid.map(str::to_owned),
+        }
+    }
+
+    /// Gets the patch code GUID.
+    pub fn patch_code(&self) -> Guid {
+        self.patch_code
+    }
+
+    /// Gets the product code GUID of the product to which the patch is applied.
+    pub fn product_code(&self) -> Guid {
+        self.product_code
+    }
+
+    /// Gets information about the patch.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use msica::{patches, InstallContext, Patch, PatchProperty, PatchState};
+    ///
+    /// for patch in patches(None, InstallContext::ALL, PatchState::APPLIED)? {
+    ///     let patch = Patch::from(patch?);
+    ///     println!("{}", patch.info(PatchProperty::DisplayName)?);
+    /// }
+    /// # Ok::<(), msica::Error>(())
+    /// ```
+    pub fn info(&self, property: PatchProperty) -> Result<String> {
+        unsafe {
+            let patch_code = ffi::to_wide(&self.patch_code)?;
+            let product_code = ffi::to_wide(&self.product_code)?;
+            let sid = match &self.sid {
+                Some(sid) => Some(ffi::to_wide(sid)?),
+                None => None,
+            };
+            let property = ffi::to_wide(property.name())?;
+
+            ffi::get_string(|value, value_len| {
+                ffi::MsiGetPatchInfoEx(
+                    patch_code.as_ptr(),
+                    product_code.as_ptr(),
+                    sid.as_ref().map_or(std::ptr::null(), |sid| sid.as_ptr()),
+                    self.context.bits() as i32,
+                    property.as_ptr(),
+                    value,
+                    value_len,
+                )
+            })
+        }
+    }
+}
+
+impl From<PatchInfo> for Patch {
+    fn from(info: PatchInfo) -> Self {
+        Self {
+            patch_code: info.patch_code,
+            product_code: info.product_code,
+            context: info.context,
+            sid: info.sid,
+        }
+    }
+}
+
+/// Information about a [`Patch`] returned from [`Patch::info()`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum PatchProperty {
+    /// The path to the locally cached patch package.
+    LocalPackage,
+    /// The semicolon-delimited list of transforms applied by the patch.
+    Transforms,
+    /// The date the patch was applied in the form `YYYYMMDD`.
+    InstallDate,
+    /// `1` if the patch can be uninstalled; otherwise, `0`.
+    Uninstallable,
+    /// `1` if applied, `2` if superseded, `4` if obsoleted, or `8` if registered. See [`PatchState`].
+    State,
+    /// The registered display name of the patch.
+    DisplayName,
+    /// The registered URL for more information about the patch.
+    MoreInfoUrl,
+}
+
+impl PatchProperty {
+    /// Gets the name of the property passed to Windows Installer.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PatchProperty::LocalPackage => "LocalPackage",
+            PatchProperty::Transforms => "Transforms",
+            PatchProperty::InstallDate => "InstallDate",
+            PatchProperty::Uninstallable => "Uninstallable",
+            PatchProperty::State => "State",
+            PatchProperty::DisplayName => "DisplayName",
+            PatchProperty::MoreInfoUrl => "MoreInfoURL",
+        }
+    }
+}
+
+/// Enumerates patches for the current user and per-machine installations. Returned from [`patches()`].
+pub struct Patches {
+    product_code: Option<Vec<u16>>,
+    context: InstallContext,
+    state: PatchState,
+    index: u32,
+    done: bool,
+}
+
+/// Enumerates patches in the given states applied to one or all products in the given contexts.
+///
+/// Pass `None` for the `product_code` to enumerate patches for all products.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{patches, InstallContext, PatchState};
+///
+/// for patch in patches(None, InstallContext::ALL, PatchState::APPLIED)? {
+///     let patch = patch?;
+///     println!("{} applied to {}", patch.patch_code, patch.product_code);
+/// }
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub fn patches(
+    product_code: Option<Guid>,
+    context: InstallContext,
+    state: PatchState,
+) -> Result<Patches> {
+    let product_code = match product_code {
+        Some(product_code) => Some(ffi::to_wide(&product_code)?),
+        None => None,
+    };
+
+    Ok(Patches {
+        product_code,
+        context,
+        state,
+        index: 0,
+        done: false,
+    })
+}
+
+impl Iterator for Patches {
+    type Item = Result<PatchInfo>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut patch_code = [0u16; ffi::GUID_LEN];
+        let mut product_code = [0u16; ffi::GUID_LEN];
+        let mut context = 0i32;
+
+        let sid = unsafe {
+            ffi::get_string(|sid, sid_len| {
+                ffi::MsiEnumPatchesEx(
+                    self.product_code
+                        .as_ref()
+                        .map_or(std::ptr::null(), |product_code| product_code.as_ptr()),
+                    std::ptr::null(),
+                    self.context.bits(),
+                    self.state.bits(),
+                    self.index,
+                    patch_code.as_mut_ptr(),
+                    product_code.as_mut_ptr(),
+                    &mut context,
+                    sid,
+                    sid_len,
+                )
+            })
+        };
+
+        let sid = match sid {
+            Ok(sid) => sid,
+            Err(err) => {
+                self.done = true;
+                if err.is(ffi::ERROR_NO_MORE_ITEMS) {
+                    return None;
+                }
+                return Some(Err(err));
+            }
+        };
+        self.index += 1;
+
+        let info = Guid::from_terminated(&patch_code).and_then(|patch_code| {
+            Ok(PatchInfo {
+                patch_code,
+                product_code: Guid::from_terminated(&product_code)?,
+                context: InstallContext::from_bits_retain(context as u32),
+                sid: (!sid.is_empty()).then_some(sid),
+            })
+        });
+        Some(info)
+    }
+}
+
+repr_enum! {
+    /// The type of installation passed to [`remove_patches()`].
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub enum InstallType: u32 {
+        /// A normal installation.
+        Default = 0,
+        /// An administrative installation.
+        NetworkImage = 1,
+        /// A single instance of a product installed as one of multiple instances.
+        SingleInstance = 2,
+    }
+}
+
+/// Applies one or more patch packages in a single transaction.
+///
+/// Pass `None` for the `product_code` to apply the patches to all products they target,
+/// and any `properties` e.g., `REBOOT=ReallySuppress` to apply to each product.
+///
+/// Each patch package is checked to exist before any are applied, so that error identifies the missing patch package.
+/// Any error returned by the installer applies to the whole transaction and does not identify a patch;
+/// call [`patch_sequence()`] first to get the status of each patch for an installed product.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{apply_patches, PatchSpec};
+///
+/// let patches = [PatchSpec::new("update1.msp"), PatchSpec::new("update2.msp")];
+/// apply_patches(&patches, None, Some("REBOOT=ReallySuppress"))?;
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[track_caller]
+pub fn apply_patches(
+    patches: &[PatchSpec],
+    product_code: Option<Guid>,
+    properties: Option<&str>,
+) -> Result<()> {
+    for patch in patches {
+        let path = patch.path();
+        if !path.as_os_str().is_empty() && !path.is_file() {
+            return Err(Error::new(
+                ErrorKind::ErrorCode(
+                    NonZeroU32::new(ffi::ERROR_PATCH_PACKAGE_OPEN_FAILED).unwrap(),
+                ),
+                format!("patch package not found: {}", path.display()),
+            ));
+        }
+    }
+
+    let patches = join(patches.iter().map(|patch| patch.path().as_os_str()))?;
+    unsafe {
+        let patches = ffi::to_wide_os(&patches)?;
+        let product_code = match product_code {
+            Some(product_code) => Some(ffi::to_wide(&product_code)?),
+            None => None,
+        };
+        let properties = match properties {
+            Some(properties) => Some(ffi::to_wide(properties)?),
+            None => None,
+        };
+
+        let ret = ffi::MsiApplyMultiplePatches(
+            patches.as_ptr(),
+            product_code
+                .as_ref()
+                .map_or(std::ptr::null(), |product_code| product_code.as_ptr()),
+            properties
+                .as_ref()
+                .map_or(std::ptr::null(), |properties| properties.as_ptr()),
+        );
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+
+        Ok(())
+    }
+}
+
+/// Removes one or more patches from a product in a single transaction.
+///
+/// Each patch is identified by its patch code GUID or the full path to its patch package.
+/// Pass [`InstallType::SingleInstance`] to remove the patches only from the instance of a product
+/// with the given `product_code`, and any `properties` e.g., `REBOOT=ReallySuppress` to apply.
+///
+/// Any error returned by the installer applies to the whole transaction and does not identify a patch;
+/// call [`patches()`] first to check that each patch is applied to the product.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{remove_patches, InstallType};
+///
+/// remove_patches(
+///     &["{FEDCBA98-7654-3210-FEDC-BA9876543210}"],
+///     "{01234567-89AB-CDEF-0123-456789ABCDEF}".parse()?,
+///     InstallType::SingleInstance,
+///     None,
+/// )?;
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[track_caller]
+pub fn remove_patches(
+    patches: &[&str],
+    product_code: Guid,
+    uninstall_type: InstallType,
+    properties: Option<&str>,
+) -> Result<()> {
+    let patches = join(patches.iter().map(OsStr::new))?;
+    unsafe {
+        let patches = ffi::to_wide_os(&patches)?;
+        let product_code = ffi::to_wide(&product_code)?;
+        let properties = match properties {
+            Some(properties) => Some(ffi::to_wide(properties)?),
+            None => None,
+        };
+
+        let ret = ffi::MsiRemovePatches(
+            patches.as_ptr(),
+            product_code.as_ptr(),
+            uninstall_type,
+            properties
+                .as_ref()
+                .map_or(std::ptr::null(), |properties| properties.as_ptr()),
+        );
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+
+        Ok(())
+    }
+}
+
+/// Determines which patches apply to a package (.msi) and in what order, without installing anything.
+///
+/// Applicable patches are returned first in the order they would be applied, followed by any patches that do not apply.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{applicable_patches, PatchData};
+///
+/// let patches = [PatchData::File("update1.msp".into()), PatchData::File("update2.msp".into())];
+/// for result in applicable_patches("example.msi", &patches)? {
+///     println!("{:?}: {:?}", result.patch, result.order);
+/// }
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[track_caller]
+pub fn applicable_patches(
+    package: impl AsRef<Path>,
+    patches: &[PatchData],
+) -> Result<Vec<PatchApplicability>> {
+    let package = ffi::to_wide_os(package.as_ref().as_os_str())?;
+    sequence(patches, |count, infos| unsafe {
+        ffi::MsiDetermineApplicablePatches(package.as_ptr(), count, infos)
+    })
+}
+
+/// Determines which patches apply to an installed product and in what order, without installing anything.
+///
+/// The product must be installed in a single `context` e.g., [`InstallContext::MACHINE`]; combinations of contexts
+/// like [`InstallContext::ALL`] return an error with [`ErrorKind::DataConversion`].
+/// Pass `None` for the `user_sid` to use the current user, which is required for [`InstallContext::MACHINE`].
+/// Applicable patches are returned first in the order they would be applied, followed by any patches that do not apply.
+#[track_caller]
+pub fn patch_sequence(
+    product_code: impl AsRef<str>,
+    user_sid: Option<&str>,
+    context: InstallContext,
+    patches: &[PatchData],
+) -> Result<Vec<PatchApplicability>> {
+    if context.bits().count_ones() != 1 {
+        return Err(Error::new(
+            ErrorKind::DataConversion,
+            format!("a single install context is required: {context:?}"),
+        ));
+    }
+
+    let product_code = ffi::to_wide(product_code.as_ref())?;
+    let user_sid = match user_sid {
+        Some(user_sid) => Some(ffi::to_wide(user_sid)?),
+        None => None,
+    };
+    sequence(patches, |count, infos| unsafe {
+        ffi::MsiDeterminePatchSequence(
+            product_code.as_ptr(),
+            user_sid
+                .as_ref()
+                .map_or(std::ptr::null(), |user_sid| user_sid.as_ptr()),
+            context.bits() as i32,
+            count,
+            infos,
+        )
+    })
+}
+
+#[track_caller]
+fn sequence<F>(patches: &[PatchData], f: F) -> Result<Vec<PatchApplicability>>
+where
+    F: FnOnce(u32, *mut ffi::MSIPATCHSEQUENCEINFOW) -> u32,
+{
+    let data = patches
+        .iter()
+        .map(|patch| match patch {
+            PatchData::File(path) | PatchData::XmlPath(path) => ffi::to_wide_os(path.as_os_str()),
+            PatchData::XmlBlob(xml) => ffi::to_wide(xml),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut infos: Vec<_> = patches
+        .iter()
+        .zip(&data)
+        .map(|(patch, data)| ffi::MSIPATCHSEQUENCEINFOW {
+            szPatchData: data.as_ptr(),
+            ePatchDataType: match patch {
+                PatchData::File(_) => 0,
+                PatchData::XmlPath(_) => 1,
+                PatchData::XmlBlob(_) => 2,
+            },
+            dwOrder: u32::MAX,
+            uStatus: 0,
+        })
+        .collect();
+
+    let ret = f(infos.len() as u32, infos.as_mut_ptr());
+    if ret != ffi::ERROR_SUCCESS {
+        return Err(Error::from_last_error(ret));
+    }
+
+    let mut results: Vec<_> = patches
+        .iter()
+        .zip(&infos)
+        .map(|(patch, info)| PatchApplicability {
+            patch: patch.clone(),
+            // dwOrder is -1 if the patch does not apply.
+            order: (info.dwOrder != u32::MAX).then_some(info.dwOrder),
+            status: info.uStatus,
+        })
+        .collect();
+    results.sort_by_key(|result| result.order.unwrap_or(u32::MAX));
+
+    Ok(results)
+}
+
+/// Joins patch codes or package paths with semicolons after checking each one.
+fn join<'a>(patches: impl IntoIterator<Item = &'a OsStr>) -> Result<OsString> {
+    let mut joined = OsString::new();
+    for (i, patch) in patches.into_iter().enumerate() {
+        if patch.is_empty() || patch.to_string_lossy().contains(';') {
+            return Err(Error::new(
+                ErrorKind::DataConversion,
+                format!("invalid patch: {:?}", patch),
+            ));
+        }
+
+        if i > 0 {
+            joined.push(";");
+        }
+        joined.push(patch);
+    }
+
+    if joined.is_empty() {
+        return Err(Error::new(
+            ErrorKind::DataConversion,
+            "at least one patch is required",
+        ));
+    }
+
+    Ok(joined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patch_sequence_single_context() {
+        for context in [InstallContext::ALL, InstallContext::empty()] {
+            let err = patch_sequence("{FEDCBA98-7654-3210-FEDC-BA9876543210}", None, context, &[])
+                .unwrap_err();
+            assert_eq!(&ErrorKind::DataConversion, err.kind());
+        }
+    }
+
+    #[test]
+    fn join_patches() {
+        let joined =
+            join(["a.msp", "{FEDCBA98-7654-3210-FEDC-BA9876543210}"].map(OsStr::new)).unwrap();
+        assert_eq!("a.msp;{FEDCBA98-7654-3210-FEDC-BA9876543210}", joined);
+    }
+
+    #[test]
+    fn join_invalid() {
+        let err = join([]).unwrap_err();
+        assert_eq!(&ErrorKind::DataConversion, err.kind());
+
+        let err = join([OsStr::new("a.msp;b.msp")]).unwrap_err();
+        assert_eq!(&ErrorKind::DataConversion, err.kind());
+        assert_eq!("invalid patch: \"a.msp;b.msp\"", err.to_string());
+    }
+
+    #[test]
+    fn apply_patches_missing() {
+        let err = apply_patches(&[PatchSpec::new("missing.msp")], None, None).unwrap_err();
+        assert_eq!(Some(ffi::ERROR_PATCH_PACKAGE_OPEN_FAILED), err.code());
+        assert_eq!("patch package not found: missing.msp", err.to_string());
+    }
+}
@@ -0,0 +1,80 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi;
+use crate::{Database, Error, Result};
+
+/// Displays dialogs and billboards authored in a [`Database`] without running an installation.
+///
+/// Get a [`UiPreview`] from [`Database::ui_preview()`]. The database remains open while the [`UiPreview`] is alive.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::prelude::*;
+///
+/// # fn example(session: &Session) -> Result<()> {
+/// let preview = session.database().ui_preview()?;
+/// preview.dialog("WelcomeDlg")?;
+/// preview.billboard("Billboard", "Welcome")?;
+/// preview.hide_dialog()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct UiPreview {
+    h: ffi::PMSIHANDLE<ffi::kind::Preview>,
+    _database: Database,
+}
+
+impl UiPreview {
+    /// Displays the named dialog from the `Dialog` table, replacing any dialog already displayed.
+    pub fn dialog(&self, name: &str) -> Result<()> {
+        let name = ffi::to_wide(name)?;
+        self.show_dialog(name.as_ptr())
+    }
+
+    /// Removes any dialog displayed by [`UiPreview::dialog()`].
+    pub fn hide_dialog(&self) -> Result<()> {
+        self.show_dialog(std::ptr::null())
+    }
+
+    /// Displays the named billboard from the `Billboard` table in the named billboard control
+    /// on the dialog displayed by [`UiPreview::dialog()`].
+    pub fn billboard(&self, control: &str, billboard: &str) -> Result<()> {
+        let control = ffi::to_wide(control)?;
+        let billboard = ffi::to_wide(billboard)?;
+        self.show_billboard(control.as_ptr(), billboard.as_ptr())
+    }
+
+    /// Removes any billboard displayed in the named billboard control.
+    pub fn hide_billboard(&self, control: &str) -> Result<()> {
+        let control = ffi::to_wide(control)?;
+        self.show_billboard(control.as_ptr(), std::ptr::null())
+    }
+
+    #[track_caller]
+    pub(crate) fn from_handle(h: ffi::PreviewHandle, database: Database) -> Self {
+        UiPreview {
+            h: h.to_owned(),
+            _database: database,
+        }
+    }
+
+    fn show_dialog(&self, name: ffi::LPCWSTR) -> Result<()> {
+        let ret = unsafe { ffi::MsiPreviewDialog(*self.h, name) };
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+
+        Ok(())
+    }
+
+    fn show_billboard(&self, control: ffi::LPCWSTR, billboard: ffi::LPCWSTR) -> Result<()> {
+        let ret = unsafe { ffi::MsiPreviewBillboard(*self.h, control, billboard) };
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,780 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi;
+use crate::{Error, ErrorKind, Guid, InstallContext, InstallLevel, InstallState, Result, Session};
+use std::{
+    fmt::{Display, Write},
+    ops::Deref,
+    path::PathBuf,
+    str::FromStr,
+};
+
+flags! {
+    /// What to reinstall when repairing a product with [`reinstall_product()`] or a feature with [`reinstall_feature()`].
+    pub struct ReinstallMode: u32 {
+        /// Reinstall only if the file is missing (`p`).
+        const FILE_MISSING = 1 << 1;
+        /// Reinstall if the file is missing or an older version (`o`).
+        const FILE_OLDER_VERSION = 1 << 2;
+        /// Reinstall if the file is missing, or an equal or older version (`e`).
+        const FILE_EQUAL_VERSION = 1 << 3;
+        /// Reinstall if the file is missing or a different version (`d`).
+        const FILE_EXACT = 1 << 4;
+        /// Reinstall if the file is missing or its checksum does not match (`c`).
+        const FILE_VERIFY = 1 << 5;
+        /// Reinstall all files regardless of version (`a`).
+        const FILE_REPLACE = 1 << 6;
+        /// Rewrite all required per-machine registry entries (`m`).
+        const MACHINE_DATA = 1 << 7;
+        /// Rewrite all required per-user registry entries (`u`).
+        const USER_DATA = 1 << 8;
+        /// Reinstall all shortcuts and re-cache all icons (`s`).
+        const SHORTCUT = 1 << 9;
+        /// Run from the source package and re-cache the local package (`v`).
+        const PACKAGE = 1 << 10;
+    }
+}
+
+impl ReinstallMode {
+    /// The typical repair mode equivalent to `REINSTALLMODE=omus`: reinstall missing or older files (`o`),
+    /// rewrite per-machine (`m`) and per-user (`u`) registry entries, and reinstall shortcuts (`s`).
+    pub const OMUS: Self = Self(
+        Self::FILE_OLDER_VERSION.0 | Self::MACHINE_DATA.0 | Self::USER_DATA.0 | Self::SHORTCUT.0,
+    );
+
+    const LETTERS: [(char, Self); 10] = [
+        ('p', Self::FILE_MISSING),
+        ('o', Self::FILE_OLDER_VERSION),
+        ('e', Self::FILE_EQUAL_VERSION),
+        ('d', Self::FILE_EXACT),
+        ('c', Self::FILE_VERIFY),
+        ('a', Self::FILE_REPLACE),
+        ('m', Self::MACHINE_DATA),
+        ('u', Self::USER_DATA),
+        ('s', Self::SHORTCUT),
+        ('v', Self::PACKAGE),
+    ];
+}
+
+impl Display for ReinstallMode {
+    /// Formats the mode as letters for the `REINSTALLMODE` property e.g., `omus`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Self::LETTERS
+            .iter()
+            .filter(|(_, mode)| self.contains(*mode))
+            .try_for_each(|(letter, _)| f.write_char(*letter))
+    }
+}
+
+impl FromStr for ReinstallMode {
+    type Err = Error;
+
+    /// Parses letters from the `REINSTALLMODE` property e.g., `omus`, ignoring case.
+    fn from_str(s: &str) -> Result<Self> {
+        s.chars().try_fold(Self::empty(), |modes, c| {
+            let letter = c.to_ascii_lowercase();
+            match Self::LETTERS.iter().find(|(l, _)| *l == letter) {
+                Some((_, mode)) => Ok(modes | *mode),
+                None => Err(Error::new(
+                    ErrorKind::DataConversion,
+                    format!("invalid REINSTALLMODE letter: {c}"),
+                )),
+            }
+        })
+    }
+}
+
+/// How a component or feature is installed or repaired on demand e.g., by [`provide_component()`](crate::provide_component).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum InstallMode {
+    /// Install the component or feature if it is not installed locally, and repair it if it is broken.
+    #[default]
+    Default,
+    /// Only provide the component if it is already installed, without installing or repairing anything.
+    Existing,
+    /// Like [`InstallMode::Existing`], but without checking that the component's key path exists.
+    NoDetection,
+    /// Like [`InstallMode::Default`], but fail if the source would have to be resolved.
+    NoSourceResolution,
+    /// Skip checking that the components of a feature are installed when passed to [`use_feature()`](crate::use_feature).
+    NoDetectionAny,
+    /// Reinstall the component or feature as the [`ReinstallMode`] selects.
+    Reinstall(ReinstallMode),
+}
+
+impl From<InstallMode> for u32 {
+    fn from(value: InstallMode) -> Self {
+        match value {
+            InstallMode::Default => 0,
+            InstallMode::Existing => -1i32 as u32,
+            InstallMode::NoDetection => -2i32 as u32,
+            InstallMode::NoSourceResolution => -3i32 as u32,
+            InstallMode::NoDetectionAny => -4i32 as u32,
+            InstallMode::Reinstall(mode) => mode.bits(),
+        }
+    }
+}
+
+/// A [`Session`] for an installed product opened with [`Product::open()`], which is closed when dropped.
+///
+/// Use it like any other [`Session`] to evaluate conditions or, after running costing actions, query feature states
+/// e.g., in maintenance tools that need to inspect how a product was installed.
+/// [`Product::property()`] reads values authored in the product's `Property` table.
+pub struct Product {
+    product_code: Guid,
+    session: Session,
+    _h: ffi::PMSIHANDLE<ffi::kind::Session>,
+}
+
+impl Product {
+    /// Opens an installed product by its `ProductCode` e.g., `{01234567-89AB-CDEF-0123-456789ABCDEF}`.
+    ///
+    /// This is meant for tools and tests. Custom actions should use the [`Session`] they are passed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use msica::Product;
+    ///
+    /// let product = Product::open("{01234567-89AB-CDEF-0123-456789ABCDEF}")?;
+    /// println!("{}", product.property("ProductVersion")?);
+    ///
+    /// for action in ["CostInitialize", "FileCost", "CostFinalize"] {
+    ///     product.do_action(Some(action))?;
+    /// }
+    /// let (installed, _) = product.feature_state("Complete")?;
+    /// println!("Complete: {installed:?}");
+    /// # Ok::<(), msica::Error>(())
+    /// ```
+    #[track_caller]
+    pub fn open(product_code: impl AsRef<str>) -> Result<Self> {
+        let product_code = Guid::parse(product_code.as_ref())?;
+        unsafe {
+            let mut h = ffi::SessionHandle::null();
+            let wide = ffi::to_wide(&product_code)?;
+            let ret = ffi::MsiOpenProduct(wide.as_ptr(), &mut h);
+            if ret != ffi::ERROR_SUCCESS {
+                return Err(Error::from_last_error(ret));
+            }
+
+            Ok(Product {
+                product_code,
+                session: Session::from_handle(h),
+                _h: h.to_owned(),
+            })
+        }
+    }
+
+    /// Gets the `ProductCode` of the product.
+    pub fn product_code(&self) -> Guid {
+        self.product_code
+    }
+
+    /// Gets the value of a property authored in the product's `Property` table, or an empty string if undefined.
+    ///
+    /// Unlike [`Session::property()`], this does not return values set on the command line or by actions.
+    pub fn property(&self, name: &str) -> Result<String> {
+        unsafe {
+            let name = ffi::to_wide(name)?;
+            ffi::get_string(|value, value_len| {
+                ffi::MsiGetProductProperty(self.session.h, name.as_ptr(), value, value_len)
+            })
+        }
+    }
+
+    /// Gets the title, description, and attributes of a feature of the product.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use msica::Product;
+    ///
+    /// let product = Product::open("{01234567-89AB-CDEF-0123-456789ABCDEF}")?;
+    /// let info = product.feature_info("Documentation")?;
+    /// println!("{}: {}", info.title, info.description);
+    /// # Ok::<(), msica::Error>(())
+    /// ```
+    pub fn feature_info(&self, feature: &str) -> Result<FeatureInfo> {
+        let feature = ffi::to_wide(feature)?;
+        let mut attributes = 0u32;
+        let [title, description] = ffi::get_strings(
+            |[(title, title_len), (description, description_len)]| unsafe {
+                ffi::MsiGetFeatureInfo(
+                    self.session.h,
+                    feature.as_ptr(),
+                    &mut attributes,
+                    title,
+                    title_len,
+                    description,
+                    description_len,
+                )
+            },
+        )?;
+
+        Ok(FeatureInfo {
+            title,
+            description,
+            attributes: InstallFeatureAttributes::from_bits_retain(attributes),
+        })
+    }
+
+    /// Gets the registered user information for the product.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use msica::{Product, UserInfoState};
+    ///
+    /// let product = Product::open("{01234567-89AB-CDEF-0123-456789ABCDEF}")?;
+    /// let info = product.user_info()?;
+    /// if info.state == UserInfoState::Present {
+    ///     println!("registered to {} ({})", info.user_name, info.organization);
+    /// }
+    /// # Ok::<(), msica::Error>(())
+    /// ```
+    pub fn user_info(&self) -> Result<UserInfo> {
+        let product_code = ffi::to_wide(&self.product_code)?;
+        let mut state = 0;
+        let [user_name, organization, product_id] = ffi::get_strings(
+            |[(user_name, user_name_len), (organization, organization_len), (product_id, product_id_len)]| {
+                state = unsafe {
+                    ffi::MsiGetUserInfo(
+                        product_code.as_ptr(),
+                        user_name,
+                        user_name_len,
+                        organization,
+                        organization_len,
+                        product_id,
+                        product_id_len,
+                    )
+                };
+                match state {
+                    // USERINFOSTATE_MOREDATA
+                    -3 => ffi::ERROR_MORE_DATA,
+                    // USERINFOSTATE_INVALIDARG
+                    -2 => ffi::ERROR_INVALID_PARAMETER,
+                    _ => ffi::ERROR_SUCCESS,
+                }
+            },
+        )?;
+
+        let state = UserInfoState::try_from(state)?;
+        if state != UserInfoState::Present {
+            return Ok(UserInfo {
+                state,
+                ..Default::default()
+            });
+        }
+
+        Ok(UserInfo {
+            user_name,
+            organization,
+            product_id,
+            state,
+        })
+    }
+
+    /// Gets registered information about the product e.g., `InstalledProductName`, `VersionString`, or `LocalPackage`.
+    ///
+    /// See [`ProductInfo::query()`] to get common information without opening the product.
+    pub fn info(&self, property: &str) -> Result<String> {
+        product_info(&ffi::to_wide(&self.product_code)?, property)
+    }
+}
+
+impl Deref for Product {
+    type Target = Session;
+
+    fn deref(&self) -> &Self::Target {
+        &self.session
+    }
+}
+
+flags! {
+    /// How a feature may be installed, returned from [`Product::feature_info()`].
+    pub struct InstallFeatureAttributes: u32 {
+        /// Install the feature locally by default.
+        const FAVOR_LOCAL = 1 << 0;
+        /// Install the feature to run from source by default.
+        const FAVOR_SOURCE = 1 << 1;
+        /// Install the feature in the same state as its parent.
+        const FOLLOW_PARENT = 1 << 2;
+        /// Advertise the feature by default.
+        const FAVOR_ADVERTISE = 1 << 3;
+        /// The feature cannot be advertised.
+        const DISALLOW_ADVERTISE = 1 << 4;
+        /// The feature is not advertised on platforms that do not support advertising.
+        const NO_UNSUPPORTED_ADVERTISE = 1 << 5;
+    }
+}
+
+/// The title, description, and attributes of a feature returned from [`Product::feature_info()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeatureInfo {
+    /// The title of the feature.
+    pub title: String,
+    /// The description of the feature.
+    pub description: String,
+    /// How the feature may be installed.
+    pub attributes: InstallFeatureAttributes,
+}
+
+/// Registered user information for a product returned from [`Product::user_info()`].
+///
+/// The user information is empty unless the [`state`](UserInfo::state) is [`UserInfoState::Present`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct UserInfo {
+    /// The registered user name.
+    pub user_name: String,
+    /// The registered organization.
+    pub organization: String,
+    /// The product ID.
+    pub product_id: String,
+    /// Whether the user information is registered.
+    pub state: UserInfoState,
+}
+
+repr_enum! {
+    /// Whether user information is registered for a product.
+    #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+    pub enum UserInfoState: i32 {
+        /// The product is not installed or advertised.
+        #[default]
+        Unknown = -1,
+        /// The user information has not been registered.
+        Absent = 0,
+        /// The user information is registered.
+        Present = 1,
+    }
+}
+
+/// Common registered information about an installed product returned from [`ProductInfo::query()`].
+///
+/// Information that is not registered for the product is empty.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ProductInfo {
+    /// The installed product name (`InstalledProductName`).
+    pub name: String,
+    /// The product version (`VersionString`).
+    pub version: String,
+    /// The publisher of the product (`Publisher`).
+    pub publisher: String,
+    /// The installation location (`InstallLocation`).
+    pub install_location: PathBuf,
+    /// The path to the locally cached package (`LocalPackage`).
+    pub package_path: PathBuf,
+    /// The product language (`Language`).
+    pub language: Option<u16>,
+    /// The date the product was installed or last serviced in the form `YYYYMMDD` (`InstallDate`).
+    pub install_date: String,
+}
+
+impl ProductInfo {
+    /// Gets common registered information about an installed product.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use msica::{installed_products, ProductInfo};
+    ///
+    /// for product_code in installed_products() {
+    ///     let info = ProductInfo::query(product_code?)?;
+    ///     println!("{} {} ({})", info.name, info.version, info.publisher);
+    /// }
+    /// # Ok::<(), msica::Error>(())
+    /// ```
+    pub fn query(product_code: impl AsRef<str>) -> Result<Self> {
+        let product_code = ffi::to_wide(product_code.as_ref())?;
+        Self::collect(|property| product_info(&product_code, property))
+    }
+
+    /// Gets common registered information about a product installed in a single `context` for a user.
+    ///
+    /// Pass `None` for the `sid` to use the current user, which is required for [`InstallContext::MACHINE`].
+    pub fn query_ex(
+        product_code: impl AsRef<str>,
+        sid: Option<&str>,
+        context: InstallContext,
+    ) -> Result<Self> {
+        let product_code = ffi::to_wide(product_code.as_ref())?;
+        let sid = match sid {
+            Some(sid) => Some(ffi::to_wide(sid)?),
+            None => None,
+        };
+        Self::collect(|property| unsafe {
+            let property = ffi::to_wide(property)?;
+            ffi::get_string(|value, value_len| {
+                ffi::MsiGetProductInfoEx(
+                    product_code.as_ptr(),
+                    sid.as_ref().map_or(std::ptr::null(), |sid| sid.as_ptr()),
+                    context.bits() as i32,
+                    property.as_ptr(),
+                    value,
+                    value_len,
+                )
+            })
+        })
+    }
+
+    fn collect(product_info: impl Fn(&str) -> Result<String>) -> Result<Self> {
+        let info = |property| match product_info(property) {
+            Err(err) if err.is(ffi::ERROR_UNKNOWN_PROPERTY) => Ok(String::new()),
+            result => result,
+        };
+
+        Ok(Self {
+            name: info("InstalledProductName")?,
+            version: info("VersionString")?,
+            publisher: info("Publisher")?,
+            install_location: info("InstallLocation")?.into(),
+            package_path: info("LocalPackage")?.into(),
+            language: info("Language")?.parse().ok(),
+            install_date: info("InstallDate")?,
+        })
+    }
+}
+
+fn product_info(product_code: &[u16], property: &str) -> Result<String> {
+    unsafe {
+        let property = ffi::to_wide(property)?;
+        ffi::get_string(|value, value_len| {
+            ffi::MsiGetProductInfo(product_code.as_ptr(), property.as_ptr(), value, value_len)
+        })
+    }
+}
+
+/// Enumerates the product codes of products installed for the current user or per-machine. Returned from [`installed_products()`].
+pub struct Products {
+    index: u32,
+    done: bool,
+}
+
+/// Enumerates the product codes of products installed for the current user or per-machine.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{installed_products, Product};
+///
+/// for product_code in installed_products() {
+///     let product = Product::open(product_code?)?;
+///     println!("{}", product.property("ProductName")?);
+/// }
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub fn installed_products() -> Products {
+    Products {
+        index: 0,
+        done: false,
+    }
+}
+
+impl Iterator for Products {
+    type Item = Result<Guid>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let mut product_code = [0u16; ffi::GUID_LEN];
+        let ret = unsafe { ffi::MsiEnumProducts(self.index, product_code.as_mut_ptr()) };
+        match ret {
+            ffi::ERROR_SUCCESS => {
+                self.index += 1;
+                Some(Guid::from_terminated(&product_code))
+            }
+            ffi::ERROR_NO_MORE_ITEMS => {
+                self.done = true;
+                None
+            }
+            _ => {
+                self.done = true;
+                Some(Err(Error::from_error_code(ret)))
+            }
+        }
+    }
+}
+
+/// Shows the product's user registration dialogs to collect and register user information.
+///
+/// Products that defer registration until first run can call this, then read the result with [`Product::user_info()`].
+#[track_caller]
+pub fn collect_user_info(product_code: impl AsRef<str>) -> Result<()> {
+    unsafe {
+        let product_code = ffi::to_wide(product_code.as_ref())?;
+
+        let ret = ffi::MsiCollectUserInfo(product_code.as_ptr());
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+
+        Ok(())
+    }
+}
+
+/// Gets the installed state of a feature of a product installed for the current user or per-machine.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{feature_state, InstallState};
+///
+/// let state = feature_state("{01234567-89AB-CDEF-0123-456789ABCDEF}", "Documentation")?;
+/// if state == InstallState::Local {
+///     println!("documentation is installed");
+/// }
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub fn feature_state(product_code: impl AsRef<str>, feature: &str) -> Result<InstallState> {
+    unsafe {
+        let product_code = ffi::to_wide(product_code.as_ref())?;
+        let feature = ffi::to_wide(feature)?;
+
+        InstallState::from_result(ffi::MsiQueryFeatureState(
+            product_code.as_ptr(),
+            feature.as_ptr(),
+        ))
+    }
+}
+
+/// Gets the installed state of a feature of a product installed in a single `context` for a user.
+///
+/// Pass `None` for the `sid` to use the current user, which is required for [`InstallContext::MACHINE`].
+pub fn feature_state_ex(
+    product_code: impl AsRef<str>,
+    sid: Option<&str>,
+    context: InstallContext,
+    feature: &str,
+) -> Result<InstallState> {
+    unsafe {
+        let product_code = ffi::to_wide(product_code.as_ref())?;
+        let sid = match sid {
+            Some(sid) => Some(ffi::to_wide(sid)?),
+            None => None,
+        };
+        let feature = ffi::to_wide(feature)?;
+        let mut state = 0i32;
+
+        let ret = ffi::MsiQueryFeatureStateEx(
+            product_code.as_ptr(),
+            sid.as_ref().map_or(std::ptr::null(), |sid| sid.as_ptr()),
+            context.bits() as i32,
+            feature.as_ptr(),
+            &mut state,
+        );
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_error_code(ret));
+        }
+
+        InstallState::try_from(state)
+    }
+}
+
+/// Increments the usage count of a feature and gets its installed state.
+///
+/// Applications can call this before using a feature to detect whether it is broken, and then repair it
+/// e.g., with [`reinstall_feature()`]. Pass [`InstallMode::NoDetectionAny`] to skip checking the feature's components.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{use_feature, InstallMode, InstallState};
+///
+/// let state = use_feature("{01234567-89AB-CDEF-0123-456789ABCDEF}", "Documentation", InstallMode::Default)?;
+/// if state == InstallState::Broken {
+///     println!("documentation needs to be repaired");
+/// }
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub fn use_feature(
+    product_code: impl AsRef<str>,
+    feature: &str,
+    mode: InstallMode,
+) -> Result<InstallState> {
+    unsafe {
+        let product_code = ffi::to_wide(product_code.as_ref())?;
+        let feature = ffi::to_wide(feature)?;
+
+        InstallState::from_result(ffi::MsiUseFeatureEx(
+            product_code.as_ptr(),
+            feature.as_ptr(),
+            mode.into(),
+            0,
+        ))
+    }
+}
+
+/// Installs or removes a single feature of an installed product.
+///
+/// Pass [`InstallState::Local`], [`InstallState::Source`], or [`InstallState::Default`] to install the feature,
+/// [`InstallState::Advertised`] to advertise it, or [`InstallState::Absent`] to remove it.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{configure_feature, InstallState};
+///
+/// configure_feature("{01234567-89AB-CDEF-0123-456789ABCDEF}", "Documentation", InstallState::Absent)?;
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[track_caller]
+pub fn configure_feature(
+    product_code: impl AsRef<str>,
+    feature: &str,
+    state: InstallState,
+) -> Result<()> {
+    unsafe {
+        let product_code = ffi::to_wide(product_code.as_ref())?;
+        let feature = ffi::to_wide(feature)?;
+
+        let ret = ffi::MsiConfigureFeature(product_code.as_ptr(), feature.as_ptr(), state);
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+
+        Ok(())
+    }
+}
+
+/// Installs, repairs, or removes an installed product.
+///
+/// The `install_level` determines which features are installed when `state` is [`InstallState::Default`],
+/// and any `command_line` property assignments e.g., `REBOOT=ReallySuppress` are applied.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{configure_product, InstallLevel, InstallState};
+///
+/// configure_product(
+///     "{01234567-89AB-CDEF-0123-456789ABCDEF}",
+///     InstallLevel::DEFAULT,
+///     InstallState::Absent,
+///     Some("REBOOT=ReallySuppress"),
+/// )?;
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[track_caller]
+pub fn configure_product(
+    product_code: impl AsRef<str>,
+    install_level: InstallLevel,
+    state: InstallState,
+    command_line: Option<&str>,
+) -> Result<()> {
+    unsafe {
+        let product_code = ffi::to_wide(product_code.as_ref())?;
+        let command_line = match command_line {
+            Some(command_line) => Some(ffi::to_wide(command_line)?),
+            None => None,
+        };
+
+        let ret = ffi::MsiConfigureProductEx(
+            product_code.as_ptr(),
+            install_level,
+            state,
+            command_line
+                .as_ref()
+                .map_or(std::ptr::null(), |command_line| command_line.as_ptr()),
+        );
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+
+        Ok(())
+    }
+}
+
+/// Repairs a single feature of an installed product by reinstalling what the [`ReinstallMode`] selects.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{reinstall_feature, ReinstallMode};
+///
+/// reinstall_feature("{01234567-89AB-CDEF-0123-456789ABCDEF}", "Complete", ReinstallMode::FILE_MISSING)?;
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[track_caller]
+pub fn reinstall_feature(
+    product_code: impl AsRef<str>,
+    feature: &str,
+    mode: ReinstallMode,
+) -> Result<()> {
+    unsafe {
+        let product_code = ffi::to_wide(product_code.as_ref())?;
+        let feature = ffi::to_wide(feature)?;
+
+        let ret = ffi::MsiReinstallFeature(product_code.as_ptr(), feature.as_ptr(), mode.bits());
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+
+        Ok(())
+    }
+}
+
+/// Repairs an installed product by reinstalling what the [`ReinstallMode`] selects.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{reinstall_product, ReinstallMode};
+///
+/// reinstall_product(
+///     "{01234567-89AB-CDEF-0123-456789ABCDEF}",
+///     ReinstallMode::OMUS | ReinstallMode::FILE_VERIFY,
+/// )?;
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[track_caller]
+pub fn reinstall_product(product_code: impl AsRef<str>, mode: ReinstallMode) -> Result<()> {
+    unsafe {
+        let product_code = ffi::to_wide(product_code.as_ref())?;
+
+        let ret = ffi::MsiReinstallProduct(product_code.as_ptr(), mode.bits());
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn install_mode_into_u32() {
+        assert_eq!(0, u32::from(InstallMode::default()));
+        assert_eq!(0xffff_fffd, u32::from(InstallMode::NoSourceResolution));
+        assert_eq!(
+            0x384,
+            u32::from(InstallMode::Reinstall(ReinstallMode::OMUS))
+        );
+    }
+
+    #[test]
+    fn reinstall_mode_omus() {
+        assert_eq!(0x384, ReinstallMode::OMUS.bits());
+        assert_eq!(
+            "ReinstallMode(FILE_OLDER_VERSION | MACHINE_DATA | USER_DATA | SHORTCUT)",
+            format!("{:?}", ReinstallMode::OMUS)
+        );
+    }
+
+    #[test]
+    fn reinstall_mode_letters() {
+        assert_eq!("omus", ReinstallMode::OMUS.to_string());
+        assert_eq!(ReinstallMode::OMUS, "OMUS".parse().unwrap());
+        assert_eq!(
+            ReinstallMode::FILE_MISSING | ReinstallMode::PACKAGE,
+            "vp".parse().unwrap()
+        );
+        assert_eq!("", ReinstallMode::empty().to_string());
+        assert!("omx".parse::<ReinstallMode>().is_err());
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi;
+use crate::Session;
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    time::{Duration, Instant},
+};
+
+type Samples = Vec<(Cow<'static, str>, Duration)>;
+
+thread_local! {
+    static SAMPLES: RefCell<Option<Samples>> = const { RefCell::new(None) };
+}
+
+/// Measures how long `f` takes if a [`Profiler`] is active on the current thread.
+pub(crate) fn measure<T>(name: impl FnOnce() -> Cow<'static, str>, f: impl FnOnce() -> T) -> T {
+    if !SAMPLES.with(|samples| samples.borrow().is_some()) {
+        return f();
+    }
+
+    let start = Instant::now();
+    let value = f();
+    record(name(), start.elapsed());
+    value
+}
+
+fn record(name: Cow<'static, str>, duration: Duration) {
+    SAMPLES.with(|samples| {
+        if let Some(samples) = samples.borrow_mut().as_mut() {
+            samples.push((name, duration));
+        }
+    });
+}
+
+/// Records how long actions, view executions, and user-marked scopes take
+/// and writes a summary table to the installation log when dropped.
+///
+/// While a [`Profiler`] is alive, calls on the same thread to [`Session::do_action()`] and
+/// [`View::execute()`](crate::View::execute) are measured automatically.
+/// Call [`Profiler::scope()`] to measure your own code.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::prelude::*;
+/// use msica::Profiler;
+/// const ERROR_SUCCESS: u32 = 0;
+///
+/// #[no_mangle]
+/// pub extern "C" fn MyCustomAction(session: Session) -> u32 {
+///     let profiler = Profiler::new(&session);
+///     {
+///         let _scope = profiler.scope("read properties");
+///         let _ = session.property("ProductName");
+///     }
+///     ERROR_SUCCESS
+/// }
+/// ```
+pub struct Profiler {
+    h: ffi::SessionHandle,
+    previous: Option<Samples>,
+}
+
+impl Profiler {
+    /// Creates a [`Profiler`] that writes a summary to the installation log for the given [`Session`].
+    pub fn new(session: &Session) -> Self {
+        let previous = SAMPLES.with(|samples| samples.borrow_mut().replace(Vec::new()));
+        Self {
+            h: session.h,
+            previous,
+        }
+    }
+
+    /// Measures the time until the returned [`ProfileScope`] is dropped.
+    pub fn scope(&self, name: impl Into<Cow<'static, str>>) -> ProfileScope<'_> {
+        ProfileScope {
+            _profiler: self,
+            name: Some(name.into()),
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Drop for Profiler {
+    fn drop(&mut self) {
+        let samples = SAMPLES
+            .with(|samples| samples.replace(self.previous.take()))
+            .unwrap_or_default();
+        if samples.is_empty() {
+            return;
+        }
+
+        let session = Session::from_handle(self.h);
+        for line in summarize(samples) {
+            session.info(line);
+        }
+    }
+}
+
+/// A user-marked scope created by [`Profiler::scope()`] that is measured until dropped.
+pub struct ProfileScope<'a> {
+    _profiler: &'a Profiler,
+    name: Option<Cow<'static, str>>,
+    start: Instant,
+}
+
+impl Drop for ProfileScope<'_> {
+    fn drop(&mut self) {
+        if let Some(name) = self.name.take() {
+            record(name, self.start.elapsed());
+        }
+    }
+}
+
+fn summarize(samples: Samples) -> Vec<String> {
+    // Aggregate by name in the order first seen.
+    let mut rows: Vec<(Cow<'static, str>, usize, Duration, Duration)> = Vec::new();
+    for (name, duration) in samples {
+        match rows.iter_mut().find(|row| row.0 == name) {
+            Some(row) => {
+                row.1 += 1;
+                row.2 += duration;
+                row.3 = row.3.max(duration);
+            }
+            None => rows.push((name, 1, duration, duration)),
+        }
+    }
+
+    let width = rows
+        .iter()
+        .map(|row| row.0.len())
+        .chain(std::iter::once("Name".len()))
+        .max()
+        .unwrap_or_default();
+
+    let mut lines = vec![
+        "msica: profile summary".to_owned(),
+        format!(
+            "{:<width$}  {:>8}  {:>12}  {:>12}",
+            "Name", "Count", "Total (ms)", "Max (ms)"
+        ),
+    ];
+    for (name, count, total, max) in rows {
+        lines.push(format!(
+            "{:<width$}  {:>8}  {:>12.3}  {:>12.3}",
+            name,
+            count,
+            total.as_secs_f64() * 1000.0,
+            max.as_secs_f64() * 1000.0,
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarize_aggregates() {
+        let lines = summarize(vec![
+            ("InstallFiles".into(), Duration::from_millis(2)),
+            ("scope".into(), Duration::from_millis(1)),
+            ("InstallFiles".into(), Duration::from_millis(4)),
+        ]);
+        assert_eq!(
+            lines,
+            vec![
+                "msica: profile summary",
+                "Name             Count    Total (ms)      Max (ms)",
+                "InstallFiles         2         6.000         4.000",
+                "scope                1         1.000         1.000",
+            ]
+        );
+    }
+
+    #[test]
+    fn measure_inactive() {
+        assert_eq!(measure(|| "test".into(), || 42), 42);
+        assert!(SAMPLES.with(|samples| samples.borrow().is_none()));
+    }
+}
@@ -2,10 +2,11 @@
 // Licensed under the MIT License. See LICENSE.txt in the project root for license information.
 
 use crate::ffi;
-use crate::{Error, Result};
-use std::{ffi::CString, fmt::Display};
+use crate::{Error, ErrorKind, Result};
+use std::{fmt::Display, path::Path};
 
 /// A field in a [`Record`].
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Field {
     /// A string field in a [`Record`].
     StringData(String),
@@ -18,9 +19,12 @@ pub enum Field {
 }
 
 /// A collection of [`Field`] containing strings, integers, and byte streams.
-#[derive(Debug)]
+///
+/// Cloning a [`Record`] is cheap and shares the same underlying record, so fields set through one clone
+/// are visible through all of them. The record is closed when the last clone is dropped.
+#[derive(Clone, Debug)]
 pub struct Record {
-    pub(crate) h: ffi::PMSIHANDLE,
+    pub(crate) h: ffi::SharedHandle<ffi::kind::Record>,
 }
 
 impl Record {
@@ -30,14 +34,17 @@ impl Record {
     pub fn new(field_count: u32) -> Self {
         unsafe {
             let h = ffi::MsiCreateRecord(field_count);
-            Record { h: h.to_owned() }
+            Record {
+                h: h.to_owned().into(),
+            }
         }
     }
 
     /// Creates a [`Record`] with optional text in field 0, with additional fields
     /// containing strings, integers, and byte streams.
     ///
-    /// Field indices are 1-based.
+    /// Field indices are 1-based. Call [`Record::validate_template()`] to check that the template
+    /// does not reference fields that were not passed.
     ///
     /// # Example
     ///
@@ -54,10 +61,12 @@ impl Record {
     pub fn with_fields(text: Option<&str>, fields: Vec<Field>) -> Result<Self> {
         unsafe {
             let h = ffi::MsiCreateRecord(fields.len() as u32);
-            let record = Record { h: h.to_owned() };
+            let record = Record {
+                h: h.to_owned().into(),
+            };
 
             if let Some(text) = text {
-                record.set_string_data(0, Some(text))?;
+                record.set_text(text)?;
             }
 
             for (i, field) in fields.iter().enumerate() {
@@ -100,37 +109,112 @@ impl Record {
     /// ```
     pub fn format_text(&self) -> Result<String> {
         unsafe {
-            let mut value_len = 0u32;
-            let value = CString::default();
-
-            let mut ret = ffi::MsiFormatRecord(
-                ffi::MSIHANDLE::null(),
-                *self.h,
-                value.as_ptr() as ffi::LPSTR,
-                &mut value_len as *mut u32,
-            );
-            if ret != ffi::ERROR_MORE_DATA {
-                return Err(Error::from_error_code(ret));
-            }
+            ffi::get_string(|value, value_len| {
+                ffi::MsiFormatRecord(ffi::SessionHandle::null(), *self.h, value, value_len)
+            })
+        }
+    }
 
-            let mut value_len = value_len + 1u32;
-            let mut value: Vec<u8> = vec![0; value_len as usize];
+    /// Formats the template string in field 0 like [`Record::format_text()`] and writes it to `w`
+    /// without allocating an intermediate [`String`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use msica::{Field, Record};
+    ///
+    /// let record = Record::with_fields(Some("this is [1]"), vec![Field::IntegerData(1)])?;
+    /// let mut text = String::from("message: ");
+    /// record.format_to(&mut text)?;
+    /// assert_eq!(text, "message: this is 1");
+    /// # Ok::<(), msica::Error>(())
+    /// ```
+    pub fn format_to<W: std::fmt::Write + ?Sized>(&self, w: &mut W) -> Result<()> {
+        unsafe {
+            ffi::get_string_to(w, |value, value_len| {
+                ffi::MsiFormatRecord(ffi::SessionHandle::null(), *self.h, value, value_len)
+            })
+        }
+    }
 
-            ret = ffi::MsiFormatRecord(
-                ffi::MSIHANDLE::null(),
-                *self.h,
-                value.as_mut_ptr() as ffi::LPSTR,
-                &mut value_len as *mut u32,
-            );
-            if ret != ffi::ERROR_SUCCESS {
-                return Err(Error::from_error_code(ret));
-            }
+    /// Gets the template string in field 0, or an empty string if not set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use msica::Record;
+    ///
+    /// let record = Record::try_from("this is [1]")?;
+    /// assert_eq!(record.text()?, "this is [1]");
+    /// # Ok::<(), msica::Error>(())
+    /// ```
+    pub fn text(&self) -> Result<String> {
+        self.string_data(0)
+    }
 
-            value.truncate(value_len as usize);
-            let text = String::from_utf8(value)?;
+    /// Sets the template string in field 0, which is formatted with the remaining fields by [`Record::format_text()`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use msica::{Field, Record};
+    ///
+    /// let record = Record::with_fields(None, vec![Field::IntegerData(1)])?;
+    /// record.set_text("this is [1]")?;
+    /// assert_eq!(record.format_text()?, "this is 1");
+    /// # Ok::<(), msica::Error>(())
+    /// ```
+    pub fn set_text(&self, text: &str) -> Result<()> {
+        self.set_string_data(0, Some(text))
+    }
 
-            Ok(text)
+    /// Checks that each field referenced by the template string in field 0 e.g., `[3]`, is within the count of fields.
+    ///
+    /// Windows Installer formats a reference to a missing field as an empty string, so a template that does not match
+    /// its fields would otherwise only be noticed in formatted text at install time. References to properties,
+    /// files, and other values are not checked.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use msica::{Field, Record};
+    ///
+    /// let record = Record::with_fields(
+    ///     Some("this is [1] [3]"),
+    ///     vec![Field::IntegerData(1), Field::StringData("example".to_owned())],
+    /// )?;
+    /// assert!(record.validate_template().is_err());
+    /// # Ok::<(), msica::Error>(())
+    /// ```
+    pub fn validate_template(&self) -> Result<()> {
+        let template = self.text()?;
+        let field_count = self.field_count();
+
+        let mut start = None;
+        for (i, c) in template.char_indices() {
+            match c {
+                '[' => start = Some(i + 1),
+                ']' => {
+                    if let Some(field) = start
+                        .take()
+                        .map(|start| &template[start..i])
+                        .filter(|s| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()))
+                    {
+                        if field.parse::<u32>().map_or(true, |n| n > field_count) {
+                            return Err(Error::new(
+                                ErrorKind::DataConversion,
+                                format!(
+                                    "template references field [{field}] but the record has {field_count} fields"
+                                ),
+                            ));
+                        }
+                    }
+                }
+                _ => {}
+            }
         }
+
+        Ok(())
     }
 
     /// Gets a string field from a [`Record`].
@@ -151,36 +235,49 @@ impl Record {
     /// ```
     pub fn string_data(&self, field: u32) -> Result<String> {
         unsafe {
-            let mut value_len = 0u32;
-            let value = CString::default();
-
-            let mut ret = ffi::MsiRecordGetString(
-                *self.h,
-                field,
-                value.as_ptr() as ffi::LPSTR,
-                &mut value_len as *mut u32,
-            );
-            if ret != ffi::ERROR_MORE_DATA {
-                return Err(Error::from_error_code(ret));
-            }
-
-            let mut value_len = value_len + 1u32;
-            let mut value: Vec<u8> = vec![0; value_len as usize];
-
-            ret = ffi::MsiRecordGetString(
-                *self.h,
-                field,
-                value.as_mut_ptr() as ffi::LPSTR,
-                &mut value_len as *mut u32,
-            );
-            if ret != ffi::ERROR_SUCCESS {
-                return Err(Error::from_error_code(ret));
-            }
+            ffi::get_string(|value, value_len| {
+                ffi::MsiRecordGetString(*self.h, field, value, value_len)
+            })
+        }
+    }
 
-            value.truncate(value_len as usize);
-            let text = String::from_utf8(value)?;
+    /// Gets a string field from a [`Record`],
+    /// replacing any invalid data with [`U+FFFD REPLACEMENT CHARACTER`](char::REPLACEMENT_CHARACTER).
+    ///
+    /// Field indices are 1-based, though you can get a template string from field 0.
+    pub fn string_data_lossy(&self, field: u32) -> Result<String> {
+        unsafe {
+            ffi::get_string_with(ffi::Decoding::Lossy, |value, value_len| {
+                ffi::MsiRecordGetString(*self.h, field, value, value_len)
+            })
+        }
+    }
 
-            Ok(text)
+    /// Gets a string field from a [`Record`] into an existing buffer, which is cleared first.
+    ///
+    /// Reuse the same buffer when reading many fields to avoid allocating a new [`String`] for each.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use msica::{Field, Record};
+    ///
+    /// let record = Record::with_fields(
+    ///     None,
+    ///     vec![Field::StringData("a".to_owned()), Field::StringData("b".to_owned())],
+    /// )?;
+    /// let mut value = String::new();
+    /// for field in 1..=record.field_count() {
+    ///     record.string_data_into(field, &mut value)?;
+    ///     println!("{value}");
+    /// }
+    /// # Ok::<(), msica::Error>(())
+    /// ```
+    pub fn string_data_into(&self, field: u32, buf: &mut String) -> Result<()> {
+        unsafe {
+            ffi::get_string_into(buf, |value, value_len| {
+                ffi::MsiRecordGetString(*self.h, field, value, value_len)
+            })
         }
     }
 
@@ -200,11 +297,7 @@ impl Record {
     /// ```
     pub fn set_string_data(&self, field: u32, value: Option<&str>) -> Result<()> {
         unsafe {
-            // TODO: Return result containing NulError if returned.
-            let value = match value {
-                Some(s) => CString::new(s)?,
-                None => CString::default(),
-            };
+            let value = ffi::to_wide_opt(value)?;
 
             let ret = ffi::MsiRecordSetString(*self.h, field, value.as_ptr());
             if ret != ffi::ERROR_SUCCESS {
@@ -273,6 +366,22 @@ impl Record {
         todo!()
     }
 
+    /// Sets a stream field in a [`Record`] to the contents of the file at `path`.
+    ///
+    /// Field indices are 1-based.
+    pub fn set_stream_file(&self, field: u32, path: impl AsRef<Path>) -> Result<()> {
+        unsafe {
+            let path = ffi::to_wide_os(path.as_ref().as_os_str())?;
+
+            let ret = ffi::MsiRecordSetStream(*self.h, field, path.as_ptr());
+            if ret != ffi::ERROR_SUCCESS {
+                return Err(Error::from_error_code(ret));
+            }
+
+            Ok(())
+        }
+    }
+
     /// Gets whether a field is null in a [`Record`].
     ///
     /// Field indices are 1-based.
@@ -289,20 +398,101 @@ impl Record {
         unsafe { ffi::MsiRecordIsNull(*self.h, field).as_bool() }
     }
 
-    pub(crate) fn from_handle(h: ffi::MSIHANDLE) -> Self {
-        Record { h: h.to_owned() }
+    /// Compares field values like [`PartialEq`], but considers stream fields equal if both records have a stream
+    /// in the same field.
+    pub fn eq_ignore_streams(&self, other: &Record) -> bool {
+        self.eq_fields(other, true)
+    }
+
+    fn eq_fields(&self, other: &Record, ignore_streams: bool) -> bool {
+        if self.as_raw() == other.as_raw() {
+            return true;
+        }
+
+        let field_count = self.field_count();
+        field_count == other.field_count()
+            && (0..=field_count).all(|i| match (self.field_value(i), other.field_value(i)) {
+                (Some(value), Some(other)) => value == other,
+                (None, None) => ignore_streams,
+                _ => false,
+            })
+    }
+
+    /// Gets the value of a field to compare, or `None` if it is a stream that cannot be read as a string.
+    fn field_value(&self, field: u32) -> Option<Field> {
+        if self.is_null(field) {
+            return Some(Field::Null);
+        }
+        match self.integer_data(field) {
+            Some(value) if field > 0 => Some(Field::IntegerData(value)),
+            _ => self.string_data(field).ok().map(Field::StringData),
+        }
+    }
+
+    /// Creates a [`Record`] from a raw handle, taking ownership of it.
+    ///
+    /// The handle is closed when the [`Record`] is dropped.
+    ///
+    /// # Safety
+    ///
+    /// The handle must be a valid, open record handle that is not closed elsewhere.
+    #[track_caller]
+    pub unsafe fn from_raw(h: ffi::MSIHANDLE) -> Self {
+        Self::from_handle(ffi::RecordHandle::from_raw(h))
+    }
+
+    /// Consumes the [`Record`] and returns the raw handle without closing it.
+    ///
+    /// The caller is responsible for closing the handle e.g., by passing it to [`Record::from_raw()`].
+    /// If the [`Record`] has been cloned and other clones are still alive, or the record is owned by the installer
+    /// e.g., when passed to an [`ExternalUi`](crate::ExternalUi) handler, the [`Record`] is returned instead.
+    pub fn into_raw(self) -> std::result::Result<ffi::MSIHANDLE, Self> {
+        self.h.try_into_raw().map_err(|h| Self { h })
+    }
+
+    /// Gets the raw handle without transferring ownership.
+    pub fn as_raw(&self) -> ffi::MSIHANDLE {
+        **self.h
+    }
+
+    #[track_caller]
+    pub(crate) fn from_handle(h: ffi::RecordHandle) -> Self {
+        Record {
+            h: h.to_owned().into(),
+        }
+    }
+
+    /// Wraps a record owned by the installer, which is never closed by the [`Record`] or any of its clones.
+    pub(crate) fn from_borrowed(h: ffi::RecordHandle) -> Self {
+        Record {
+            h: ffi::SharedHandle::borrowed(h),
+        }
     }
 }
 
+/// Creates a value from the fields of a [`Record`] e.g., a row fetched from a [`View`](crate::View).
+pub trait FromRecord: Sized {
+    /// Creates a value from the fields of the `record`.
+    fn from_record(record: &Record) -> Result<Self>;
+}
+
+/// Creates a [`Record`] from a value e.g., to insert a row with [`View::modify()`](crate::View::modify).
+pub trait ToRecord {
+    /// Creates a [`Record`] with fields from the value.
+    fn to_record(&self) -> Result<Record>;
+}
+
 impl TryFrom<&str> for Record {
     type Error = crate::Error;
     fn try_from(s: &str) -> std::result::Result<Self, Self::Error> {
         unsafe {
             let h = ffi::MsiCreateRecord(0u32);
-            let s = CString::new(s)?;
+            let s = ffi::to_wide(s)?;
             ffi::MsiRecordSetString(h, 0, s.as_ptr());
 
-            Ok(Record { h: h.to_owned() })
+            Ok(Record {
+                h: h.to_owned().into(),
+            })
         }
     }
 }
@@ -312,14 +502,27 @@ impl TryFrom<String> for Record {
     fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
         unsafe {
             let h = ffi::MsiCreateRecord(0u32);
-            let s = CString::new(s)?;
+            let s = ffi::to_wide(&s)?;
             ffi::MsiRecordSetString(h, 0, s.as_ptr());
 
-            Ok(Record { h: h.to_owned() })
+            Ok(Record {
+                h: h.to_owned().into(),
+            })
         }
     }
 }
 
+/// Compares the template in field 0 and all other fields.
+///
+/// Windows Installer does not expose the type of a field, so a string field that can be read as an integer
+/// is equal to an integer field with the same value, and an empty string field is equal to a null field.
+/// Stream fields are never equal unless both are the same record; use [`Record::eq_ignore_streams()`] to skip them.
+impl PartialEq for Record {
+    fn eq(&self, other: &Self) -> bool {
+        self.eq_fields(other, false)
+    }
+}
+
 impl Display for Record {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = self.format_text().unwrap_or_else(|_| "(record)".to_owned());
@@ -330,12 +533,13 @@ impl Display for Record {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::Result;
+    use crate::{ErrorKind, Result};
 
     #[test]
     fn try_from_str() -> Result<()> {
         let record = Record::try_from("test")?;
         assert_eq!(record.string_data(0)?, "test");
+        assert_eq!(record.text()?, "test");
         Ok(())
     }
 
@@ -346,6 +550,17 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn set_text() -> Result<()> {
+        let record = Record::new(1);
+        assert_eq!(record.text()?, "");
+
+        record.set_text("[1]")?;
+        assert_eq!(record.text()?, "[1]");
+        assert_eq!(record.string_data(0)?, "[1]");
+        Ok(())
+    }
+
     #[test]
     fn set_string_data_null() -> Result<()> {
         let record = Record::with_fields(None, vec![Field::StringData("test".to_owned())])?;
@@ -357,6 +572,108 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn interior_nul() {
+        let error = Record::try_from("t\0est").unwrap_err();
+        assert_eq!(&ErrorKind::DataConversion, error.kind());
+
+        let error = Record::with_fields(Some("t\0est"), vec![]).unwrap_err();
+        assert_eq!(&ErrorKind::DataConversion, error.kind());
+
+        let record = Record::new(1);
+        let error = record.set_string_data(1, Some("t\0est")).unwrap_err();
+        assert_eq!(&ErrorKind::DataConversion, error.kind());
+    }
+
+    #[test]
+    fn raw_round_trip() -> Result<()> {
+        let record = Record::with_fields(None, vec![Field::IntegerData(1)])?;
+        let h = record.into_raw().expect("expected unique record");
+        assert!(!h.is_null());
+
+        let record = unsafe { Record::from_raw(h) };
+        assert_eq!(record.as_raw(), h);
+        assert_eq!(record.integer_data(1), Some(1));
+
+        let clone = record.clone();
+        let record = record.into_raw().expect_err("expected shared record");
+        drop(clone);
+        let h = record.into_raw().expect("expected unique record");
+        drop(unsafe { Record::from_raw(h) });
+        Ok(())
+    }
+
+    #[test]
+    fn clone_shares_fields() -> Result<()> {
+        let record = Record::new(1);
+        let clone = record.clone();
+        clone.set_integer_data(1, 42)?;
+        assert_eq!(record.integer_data(1), Some(42));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "test-util")]
+    fn does_not_leak() -> Result<()> {
+        use crate::test_util::live_handles;
+
+        let before = live_handles();
+        {
+            let record = Record::with_fields(Some("[1]"), vec![Field::IntegerData(1)])?;
+            assert_eq!(live_handles(), before + 1);
+            assert_eq!(record.format_text()?, "1");
+        }
+        assert_eq!(live_handles(), before);
+        Ok(())
+    }
+
+    #[test]
+    fn validate_template() -> Result<()> {
+        let record = Record::with_fields(
+            Some(r"[1] [[2]] [\[] [INSTALLDIR] {[2]}"),
+            vec![Field::IntegerData(1), Field::Null],
+        )?;
+        assert!(record.validate_template().is_ok());
+
+        let record = Record::with_fields(Some("[1] [3]"), vec![Field::IntegerData(1)])?;
+        let error = record.validate_template().unwrap_err();
+        assert_eq!(&ErrorKind::DataConversion, error.kind());
+        assert!(error.to_string().contains("[3]"));
+
+        assert!(Record::new(0).validate_template().is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn eq() -> Result<()> {
+        let fields = || {
+            vec![
+                Field::StringData("text".to_owned()),
+                Field::IntegerData(1),
+                Field::Null,
+            ]
+        };
+        let record = Record::with_fields(Some("[1]"), fields())?;
+        assert_eq!(record, record.clone());
+        assert_eq!(record, Record::with_fields(Some("[1]"), fields())?);
+        assert!(record.eq_ignore_streams(&Record::with_fields(Some("[1]"), fields())?));
+
+        assert_ne!(record, Record::with_fields(Some("[2]"), fields())?);
+        assert_ne!(
+            record,
+            Record::with_fields(Some("[1]"), fields()[..2].to_vec())?
+        );
+
+        let other = Record::with_fields(Some("[1]"), fields())?;
+        other.set_integer_data(2, 2)?;
+        assert_ne!(record, other);
+
+        let other = Record::with_fields(Some("[1]"), fields())?;
+        other.set_string_data(3, Some(""))?;
+        assert_eq!(record, other);
+        Ok(())
+    }
+
     #[test]
     fn integer_data_from_string() -> Result<()> {
         let record = Record::with_fields(None, vec![Field::StringData("test".to_owned())])?;
@@ -0,0 +1,365 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::tables::{integer, optional_string, optional_string_field, string_field, Row};
+use crate::{Error, ErrorKind, Field, FromRecord, Record, Result, ToRecord};
+use std::fmt::{self, Display};
+
+repr_enum! {
+    /// The `Root` column of the `Registry` and `RemoveRegistry` tables.
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub enum RegistryRoot: i32 {
+        /// `HKEY_CURRENT_USER` for a per-user installation, or `HKEY_LOCAL_MACHINE` for a per-machine installation.
+        UserOrMachine = -1,
+        /// `HKEY_CLASSES_ROOT`.
+        ClassesRoot = 0,
+        /// `HKEY_CURRENT_USER`.
+        CurrentUser = 1,
+        /// `HKEY_LOCAL_MACHINE`.
+        LocalMachine = 2,
+        /// `HKEY_USERS`.
+        Users = 3,
+    }
+}
+
+/// How the strings of a [`RegistryValue::MultiString`] are combined with an existing value.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum MultiStringMode {
+    /// Replace any existing value.
+    #[default]
+    Replace,
+    /// Append the strings to any existing value.
+    Append,
+    /// Prepend the strings to any existing value.
+    Prepend,
+}
+
+/// A value in the `Value` column of the `Registry` table, which encodes the registry value type.
+///
+/// Values are formatted when installed, so properties e.g., `[INSTALLDIR]` are resolved.
+/// Convert a value to the `Value` column with [`ToString`] and parse it with [`RegistryValue::parse()`].
+///
+/// # Example
+///
+/// ```
+/// use msica::RegistryValue;
+///
+/// assert_eq!("#42", RegistryValue::Integer(42).to_string());
+/// assert_eq!(RegistryValue::ExpandString("%TEMP%".to_owned()), RegistryValue::parse("#%%TEMP%")?);
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum RegistryValue {
+    /// A `REG_SZ` string.
+    String(String),
+    /// A `REG_EXPAND_SZ` string, written as `#%value`.
+    ExpandString(String),
+    /// A `REG_DWORD` integer, written as `#value`.
+    Integer(i32),
+    /// A `REG_BINARY` value, written as `#x` followed by hexadecimal digits.
+    Binary(Vec<u8>),
+    /// A `REG_MULTI_SZ` value, written as strings separated by `[~]`.
+    MultiString(Vec<String>, MultiStringMode),
+}
+
+const MULTI_STRING_SEPARATOR: &str = "[~]";
+
+impl RegistryValue {
+    /// Parses a value from the `Value` column of the `Registry` table.
+    pub fn parse(value: &str) -> Result<Self> {
+        if let Some(hex) = value
+            .strip_prefix("#x")
+            .or_else(|| value.strip_prefix("#X"))
+        {
+            return parse_hex(hex).map(Self::Binary);
+        }
+        if let Some(value) = value.strip_prefix("#%") {
+            return Ok(Self::ExpandString(value.to_owned()));
+        }
+        if let Some(value) = value.strip_prefix("##") {
+            return Ok(Self::String(format!("#{value}")));
+        }
+        if let Some(value) = value.strip_prefix('#') {
+            return value.parse().map(Self::Integer).map_err(|_| {
+                Error::new(
+                    ErrorKind::DataConversion,
+                    format!("invalid registry integer: #{value}"),
+                )
+            });
+        }
+        if value.contains(MULTI_STRING_SEPARATOR) {
+            let (value, mode) = match (
+                value.strip_prefix(MULTI_STRING_SEPARATOR),
+                value.strip_suffix(MULTI_STRING_SEPARATOR),
+            ) {
+                (Some(rest), Some(_)) => (
+                    rest.strip_suffix(MULTI_STRING_SEPARATOR)
+                        .unwrap_or_default(),
+                    MultiStringMode::Replace,
+                ),
+                (Some(rest), None) => (rest, MultiStringMode::Append),
+                (None, Some(rest)) => (rest, MultiStringMode::Prepend),
+                (None, None) => (value, MultiStringMode::Replace),
+            };
+            let values = match value.is_empty() {
+                true => Vec::new(),
+                false => value
+                    .split(MULTI_STRING_SEPARATOR)
+                    .map(str::to_owned)
+                    .collect(),
+            };
+            return Ok(Self::MultiString(values, mode));
+        }
+
+        Ok(Self::String(value.to_owned()))
+    }
+}
+
+impl Display for RegistryValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::String(value) if value.starts_with('#') => write!(f, "#{value}"),
+            Self::String(value) => f.write_str(value),
+            Self::ExpandString(value) => write!(f, "#%{value}"),
+            Self::Integer(value) => write!(f, "#{value}"),
+            Self::Binary(value) => {
+                f.write_str("#x")?;
+                value.iter().try_for_each(|b| write!(f, "{b:02X}"))
+            }
+            Self::MultiString(values, mode) => {
+                if *mode != MultiStringMode::Prepend {
+                    f.write_str(MULTI_STRING_SEPARATOR)?;
+                }
+                f.write_str(&values.join(MULTI_STRING_SEPARATOR))?;
+                if *mode != MultiStringMode::Append {
+                    f.write_str(MULTI_STRING_SEPARATOR)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn parse_hex(hex: &str) -> Result<Vec<u8>> {
+    let invalid = || {
+        Error::new(
+            ErrorKind::DataConversion,
+            format!("invalid registry binary value: #x{hex}"),
+        )
+    };
+
+    // An odd number of digits is padded with a leading zero.
+    let mut digits = String::with_capacity(hex.len() + 1);
+    if hex.len() % 2 == 1 {
+        digits.push('0');
+    }
+    digits.push_str(hex);
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            digits
+                .get(i..i + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+                .ok_or_else(invalid)
+        })
+        .collect()
+}
+
+/// A row in the `Registry` table.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::prelude::*;
+/// use msica::RegistryRow;
+///
+/// # fn example(session: &Session) -> Result<()> {
+/// for row in session.database().rows::<RegistryRow>()? {
+///     println!("{:?}\\{}", row.root, row.key);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RegistryRow {
+    /// The primary key.
+    pub registry: String,
+    /// The registry root.
+    pub root: RegistryRoot,
+    /// The formatted registry key.
+    pub key: String,
+    /// The formatted value name, or `None` for the default value.
+    ///
+    /// A name of `+`, `-`, or `*` with no value creates or removes the key itself.
+    pub name: Option<String>,
+    /// The value, or `None` to only create the key.
+    pub value: Option<RegistryValue>,
+    /// The component that installs the row.
+    pub component: String,
+}
+
+impl Row for RegistryRow {
+    const TABLE: &'static str = "Registry";
+    const COLUMNS: &'static [&'static str] =
+        &["Registry", "Root", "Key", "Name", "Value", "Component_"];
+}
+
+impl FromRecord for RegistryRow {
+    fn from_record(record: &Record) -> Result<Self> {
+        let value = record.string_data(5)?;
+        Ok(Self {
+            registry: record.string_data(1)?,
+            root: RegistryRoot::try_from(integer(record, 2)?)?,
+            key: record.string_data(3)?,
+            name: optional_string(record, 4)?,
+            value: match value.is_empty() {
+                true => None,
+                false => Some(RegistryValue::parse(&value)?),
+            },
+            component: record.string_data(6)?,
+        })
+    }
+}
+
+impl ToRecord for RegistryRow {
+    fn to_record(&self) -> Result<Record> {
+        let value = self.value.as_ref().map(ToString::to_string);
+        Record::with_fields(
+            None,
+            vec![
+                string_field(&self.registry),
+                Field::IntegerData(self.root.into()),
+                string_field(&self.key),
+                optional_string_field(self.name.as_deref()),
+                optional_string_field(value.as_deref()),
+                string_field(&self.component),
+            ],
+        )
+    }
+}
+
+/// A row in the `RemoveRegistry` table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RemoveRegistryRow {
+    /// The primary key.
+    pub remove_registry: String,
+    /// The registry root.
+    pub root: RegistryRoot,
+    /// The formatted registry key.
+    pub key: String,
+    /// The formatted value name, or `None` for the default value. A name of `-` removes the key.
+    pub name: Option<String>,
+    /// The component that removes the registry value when installed.
+    pub component: String,
+}
+
+impl Row for RemoveRegistryRow {
+    const TABLE: &'static str = "RemoveRegistry";
+    const COLUMNS: &'static [&'static str] =
+        &["RemoveRegistry", "Root", "Key", "Name", "Component_"];
+}
+
+impl FromRecord for RemoveRegistryRow {
+    fn from_record(record: &Record) -> Result<Self> {
+        Ok(Self {
+            remove_registry: record.string_data(1)?,
+            root: RegistryRoot::try_from(integer(record, 2)?)?,
+            key: record.string_data(3)?,
+            name: optional_string(record, 4)?,
+            component: record.string_data(5)?,
+        })
+    }
+}
+
+impl ToRecord for RemoveRegistryRow {
+    fn to_record(&self) -> Result<Record> {
+        Record::with_fields(
+            None,
+            vec![
+                string_field(&self.remove_registry),
+                Field::IntegerData(self.root.into()),
+                string_field(&self.key),
+                optional_string_field(self.name.as_deref()),
+                string_field(&self.component),
+            ],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(
+            RegistryValue::String("text".to_owned()),
+            RegistryValue::parse("text").unwrap()
+        );
+        assert_eq!(
+            RegistryValue::String("#1".to_owned()),
+            RegistryValue::parse("##1").unwrap()
+        );
+        assert_eq!(
+            RegistryValue::Integer(-5),
+            RegistryValue::parse("#-5").unwrap()
+        );
+        assert_eq!(
+            RegistryValue::Binary(vec![0x0a, 0xff]),
+            RegistryValue::parse("#xAff").unwrap()
+        );
+        assert_eq!(
+            RegistryValue::Binary(vec![0x0a, 0xbc]),
+            RegistryValue::parse("#XABC").unwrap()
+        );
+        assert!(RegistryValue::parse("#one").is_err());
+        assert!(RegistryValue::parse("#xZZ").is_err());
+    }
+
+    #[test]
+    fn parse_multi_strings() {
+        let values = vec!["a".to_owned(), "b".to_owned()];
+        assert_eq!(
+            RegistryValue::MultiString(values.clone(), MultiStringMode::Replace),
+            RegistryValue::parse("[~]a[~]b[~]").unwrap()
+        );
+        assert_eq!(
+            RegistryValue::MultiString(values.clone(), MultiStringMode::Replace),
+            RegistryValue::parse("a[~]b").unwrap()
+        );
+        assert_eq!(
+            RegistryValue::MultiString(values.clone(), MultiStringMode::Append),
+            RegistryValue::parse("[~]a[~]b").unwrap()
+        );
+        assert_eq!(
+            RegistryValue::MultiString(values, MultiStringMode::Prepend),
+            RegistryValue::parse("a[~]b[~]").unwrap()
+        );
+    }
+
+    #[test]
+    fn format_values() {
+        for value in [
+            "text",
+            "##1",
+            "#%%TEMP%",
+            "#42",
+            "#x0AFF",
+            "[~]a[~]b[~]",
+            "[~]a[~]b",
+            "a[~]b[~]",
+        ] {
+            assert_eq!(value, RegistryValue::parse(value).unwrap().to_string());
+        }
+    }
+
+    #[test]
+    fn root_try_from() {
+        assert_eq!(
+            RegistryRoot::UserOrMachine,
+            RegistryRoot::try_from(-1).unwrap()
+        );
+        assert!(RegistryRoot::try_from(4).is_err());
+    }
+}
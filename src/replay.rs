@@ -0,0 +1,720 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(feature = "replay")]
+//! Records every call custom action logic makes on a session during a real installation, and replays them
+//! against the same logic in tests to reproduce issues deterministically.
+//!
+//! A [`RecordingSession`] wraps any [`SessionOps`] e.g., a [`Session`](crate::Session), and records property reads,
+//! messages, actions, and view operations along with their results. Save the recording to a file and load it
+//! into a [`ReplaySession`], which returns the recorded results in the same order.
+//!
+//! A [`ReplaySession`] panics if the logic under test makes a different call than was recorded, since the logic
+//! is no longer doing what it did during the installation.
+//!
+//! Requires the `replay` feature.
+//!
+//! # Example
+//!
+//! Record calls from a custom action entry point:
+//!
+//! ```no_run
+//! use msica::prelude::*;
+//! use msica::replay::RecordingSession;
+//! use msica::SessionOps;
+//!
+//! fn schedule<S: SessionOps>(session: &S) -> Result<()> {
+//!     let dir = session.property("INSTALLDIR")?;
+//!     session.do_deferred_action("MyDeferredCustomAction", &dir)
+//! }
+//!
+//! #[no_mangle]
+//! pub extern "C" fn MyCustomAction(session: Session) -> u32 {
+//!     let session = RecordingSession::new(session);
+//!     let result = schedule(&session);
+//!     let _ = session.save(r"C:\Temp\MyCustomAction.replay");
+//!     result.into_ca_result(&session.into_inner())
+//! }
+//! ```
+//!
+//! Then replay them in a test:
+//!
+//! ```no_run
+//! # use msica::prelude::*;
+//! # use msica::SessionOps;
+//! # fn schedule<S: SessionOps>(session: &S) -> Result<()> { Ok(()) }
+//! use msica::replay::ReplaySession;
+//!
+//! let session = ReplaySession::load(r"C:\Temp\MyCustomAction.replay")?;
+//! schedule(&session)?;
+//! assert!(session.is_finished());
+//! # Ok::<(), msica::Error>(())
+//! ```
+
+use crate::{
+    codes, DatabaseOps, Error, ErrorKind, Field, MessageType, ModifyMode, Record, Result, RunMode,
+    SessionOps, ViewOps,
+};
+use std::{cell::RefCell, collections::VecDeque, fmt::Display, fs, path::Path, rc::Rc};
+
+/// A call made on a session, database, or view.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Call {
+    Database,
+    DoAction(Option<String>),
+    DoDeferredAction(String, String),
+    Message(MessageType, Vec<Field>),
+    Mode(RunMode),
+    Property(String),
+    SetProperty(String, Option<String>),
+    OpenView(String),
+    Execute(Option<Vec<Field>>),
+    Fetch,
+    Modify(ModifyMode, Vec<Field>),
+}
+
+/// The result of a [`Call`]. Errors are recorded as their error code.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Outcome {
+    Unit(std::result::Result<(), u32>),
+    Bool(bool),
+    Integer(i32),
+    String(std::result::Result<String, u32>),
+    Record(Option<Vec<Field>>),
+}
+
+type Interaction = (Call, Outcome);
+
+/// Gets the error code to record, or `ERROR_FUNCTION_FAILED` for errors without a code.
+fn error_code(err: &Error) -> u32 {
+    err.code().unwrap_or(codes::ERROR_FUNCTION_FAILED)
+}
+
+fn unit(result: &Result<()>) -> Outcome {
+    Outcome::Unit(result.as_ref().map(|_| ()).map_err(error_code))
+}
+
+fn from_code<T>(result: std::result::Result<T, u32>) -> Result<T> {
+    result.map_err(Error::from_error_code)
+}
+
+/// Reads all fields of a record including the template in field 0.
+fn fields(record: &Record) -> Vec<Field> {
+    (0..=record.field_count())
+        .map(|i| {
+            if record.is_null(i) {
+                return Field::Null;
+            }
+            match record.integer_data(i) {
+                Some(value) if i > 0 => Field::IntegerData(value),
+                _ => Field::StringData(record.string_data_lossy(i).unwrap_or_default()),
+            }
+        })
+        .collect()
+}
+
+fn to_record(fields: &[Field]) -> Result<Record> {
+    let text = match fields.first() {
+        Some(Field::StringData(s)) => Some(s.as_str()),
+        _ => None,
+    };
+    Record::with_fields(text, fields.iter().skip(1).cloned().collect())
+}
+
+/// Records calls made on a [`SessionOps`] and the databases and views opened from it.
+pub struct RecordingSession<S> {
+    session: S,
+    log: Rc<RefCell<Vec<Interaction>>>,
+}
+
+impl<S: SessionOps> RecordingSession<S> {
+    /// Creates a [`RecordingSession`] that records calls made on the `session`.
+    pub fn new(session: S) -> Self {
+        Self {
+            session,
+            log: Rc::default(),
+        }
+    }
+
+    /// Gets the recording as text.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for interaction in self.log.borrow().iter() {
+            write_interaction(&mut text, interaction);
+        }
+        text
+    }
+
+    /// Saves the recording to a file that can be loaded by [`ReplaySession::load()`].
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, self.to_text())?;
+        Ok(())
+    }
+
+    /// Gets the session that calls are recorded from.
+    pub fn into_inner(self) -> S {
+        self.session
+    }
+
+    fn record<T>(&self, call: Call, result: T, outcome: impl FnOnce(&T) -> Outcome) -> T {
+        let outcome = outcome(&result);
+        self.log.borrow_mut().push((call, outcome));
+        result
+    }
+}
+
+impl<S: SessionOps> SessionOps for RecordingSession<S> {
+    type Database = RecordingDatabase<S::Database>;
+
+    fn database(&self) -> Self::Database {
+        self.log
+            .borrow_mut()
+            .push((Call::Database, Outcome::Unit(Ok(()))));
+        RecordingDatabase {
+            database: self.session.database(),
+            log: self.log.clone(),
+        }
+    }
+
+    fn do_action(&self, action: Option<&str>) -> Result<()> {
+        let call = Call::DoAction(action.map(str::to_owned));
+        self.record(call, self.session.do_action(action), unit)
+    }
+
+    fn do_deferred_action(&self, action: &str, custom_action_data: impl Display) -> Result<()> {
+        let custom_action_data = custom_action_data.to_string();
+        let call = Call::DoDeferredAction(action.to_owned(), custom_action_data.clone());
+        let result = self.session.do_deferred_action(action, custom_action_data);
+        self.record(call, result, unit)
+    }
+
+    fn message(&self, kind: MessageType, record: &Record) -> i32 {
+        let call = Call::Message(kind, fields(record));
+        self.record(call, self.session.message(kind, record), |i| {
+            Outcome::Integer(*i)
+        })
+    }
+
+    fn mode(&self, mode: RunMode) -> bool {
+        self.record(Call::Mode(mode), self.session.mode(mode), |b| {
+            Outcome::Bool(*b)
+        })
+    }
+
+    fn property(&self, name: &str) -> Result<String> {
+        let call = Call::Property(name.to_owned());
+        self.record(call, self.session.property(name), |result| {
+            Outcome::String(result.as_ref().cloned().map_err(error_code))
+        })
+    }
+
+    fn set_property(&self, name: &str, value: Option<&str>) -> Result<()> {
+        let call = Call::SetProperty(name.to_owned(), value.map(str::to_owned));
+        self.record(call, self.session.set_property(name, value), unit)
+    }
+}
+
+/// A database opened from a [`RecordingSession`].
+pub struct RecordingDatabase<D> {
+    database: D,
+    log: Rc<RefCell<Vec<Interaction>>>,
+}
+
+impl<D: DatabaseOps> DatabaseOps for RecordingDatabase<D> {
+    type View = RecordingView<D::View>;
+
+    fn open_view(&self, sql: &str) -> Result<Self::View> {
+        let result = self.database.open_view(sql);
+        let outcome = Outcome::Unit(result.as_ref().map(|_| ()).map_err(error_code));
+        self.log
+            .borrow_mut()
+            .push((Call::OpenView(sql.to_owned()), outcome));
+        Ok(RecordingView {
+            view: result?,
+            log: self.log.clone(),
+        })
+    }
+}
+
+/// A view opened from a [`RecordingDatabase`].
+pub struct RecordingView<V> {
+    view: V,
+    log: Rc<RefCell<Vec<Interaction>>>,
+}
+
+impl<V: ViewOps> ViewOps for RecordingView<V> {
+    fn execute(&self, record: Option<Record>) -> Result<()> {
+        let call = Call::Execute(record.as_ref().map(fields));
+        let result = self.view.execute(record);
+        self.log.borrow_mut().push((call, unit(&result)));
+        result
+    }
+
+    fn modify(&self, mode: ModifyMode, record: &Record) -> Result<()> {
+        let call = Call::Modify(mode, fields(record));
+        let result = self.view.modify(mode, record);
+        self.log.borrow_mut().push((call, unit(&result)));
+        result
+    }
+}
+
+impl<V: ViewOps> Iterator for RecordingView<V> {
+    type Item = Record;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = self.view.next();
+        let outcome = Outcome::Record(record.as_ref().map(fields));
+        self.log.borrow_mut().push((Call::Fetch, outcome));
+        record
+    }
+}
+
+/// Replays calls recorded by a [`RecordingSession`], returning the recorded results in the same order.
+///
+/// Panics if a call is made that does not match the next recorded call.
+#[derive(Clone, Debug)]
+pub struct ReplaySession {
+    interactions: Rc<RefCell<VecDeque<Interaction>>>,
+}
+
+impl ReplaySession {
+    /// Loads a recording saved by [`RecordingSession::save()`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_text(&fs::read_to_string(path)?)
+    }
+
+    /// Parses a recording returned by [`RecordingSession::to_text()`].
+    pub fn from_text(text: &str) -> Result<Self> {
+        let interactions = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(i, line)| {
+                read_interaction(line).ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::DataConversion,
+                        format!("invalid recording on line {}", i + 1),
+                    )
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            interactions: Rc::new(RefCell::new(interactions)),
+        })
+    }
+
+    /// Gets whether all recorded calls have been replayed.
+    pub fn is_finished(&self) -> bool {
+        self.interactions.borrow().is_empty()
+    }
+
+    #[track_caller]
+    fn replay(&self, call: Call) -> Outcome {
+        replay(&self.interactions, call)
+    }
+}
+
+#[track_caller]
+fn replay(interactions: &RefCell<VecDeque<Interaction>>, call: Call) -> Outcome {
+    match interactions.borrow_mut().pop_front() {
+        Some((recorded, outcome)) if recorded == call => outcome,
+        Some((recorded, _)) => panic!("replay diverged: expected {recorded:?}, got {call:?}"),
+        None => panic!("replay diverged: no more calls recorded, got {call:?}"),
+    }
+}
+
+#[track_caller]
+fn unexpected(call: &str, outcome: Outcome) -> ! {
+    panic!("invalid recording: unexpected result for {call}: {outcome:?}")
+}
+
+impl SessionOps for ReplaySession {
+    type Database = ReplayDatabase;
+
+    fn database(&self) -> Self::Database {
+        self.replay(Call::Database);
+        ReplayDatabase {
+            interactions: self.interactions.clone(),
+        }
+    }
+
+    fn do_action(&self, action: Option<&str>) -> Result<()> {
+        match self.replay(Call::DoAction(action.map(str::to_owned))) {
+            Outcome::Unit(result) => from_code(result),
+            outcome => unexpected("do_action", outcome),
+        }
+    }
+
+    fn do_deferred_action(&self, action: &str, custom_action_data: impl Display) -> Result<()> {
+        let call = Call::DoDeferredAction(action.to_owned(), custom_action_data.to_string());
+        match self.replay(call) {
+            Outcome::Unit(result) => from_code(result),
+            outcome => unexpected("do_deferred_action", outcome),
+        }
+    }
+
+    fn message(&self, kind: MessageType, record: &Record) -> i32 {
+        match self.replay(Call::Message(kind, fields(record))) {
+            Outcome::Integer(i) => i,
+            outcome => unexpected("message", outcome),
+        }
+    }
+
+    fn mode(&self, mode: RunMode) -> bool {
+        match self.replay(Call::Mode(mode)) {
+            Outcome::Bool(b) => b,
+            outcome => unexpected("mode", outcome),
+        }
+    }
+
+    fn property(&self, name: &str) -> Result<String> {
+        match self.replay(Call::Property(name.to_owned())) {
+            Outcome::String(result) => from_code(result),
+            outcome => unexpected("property", outcome),
+        }
+    }
+
+    fn set_property(&self, name: &str, value: Option<&str>) -> Result<()> {
+        match self.replay(Call::SetProperty(name.to_owned(), value.map(str::to_owned))) {
+            Outcome::Unit(result) => from_code(result),
+            outcome => unexpected("set_property", outcome),
+        }
+    }
+}
+
+/// A database opened from a [`ReplaySession`].
+#[derive(Clone, Debug)]
+pub struct ReplayDatabase {
+    interactions: Rc<RefCell<VecDeque<Interaction>>>,
+}
+
+impl DatabaseOps for ReplayDatabase {
+    type View = ReplayView;
+
+    fn open_view(&self, sql: &str) -> Result<Self::View> {
+        match replay(&self.interactions, Call::OpenView(sql.to_owned())) {
+            Outcome::Unit(result) => from_code(result).map(|_| ReplayView {
+                interactions: self.interactions.clone(),
+            }),
+            outcome => unexpected("open_view", outcome),
+        }
+    }
+}
+
+/// A view opened from a [`ReplayDatabase`].
+#[derive(Debug)]
+pub struct ReplayView {
+    interactions: Rc<RefCell<VecDeque<Interaction>>>,
+}
+
+impl ViewOps for ReplayView {
+    fn execute(&self, record: Option<Record>) -> Result<()> {
+        let call = Call::Execute(record.as_ref().map(fields));
+        match replay(&self.interactions, call) {
+            Outcome::Unit(result) => from_code(result),
+            outcome => unexpected("execute", outcome),
+        }
+    }
+
+    fn modify(&self, mode: ModifyMode, record: &Record) -> Result<()> {
+        match replay(&self.interactions, Call::Modify(mode, fields(record))) {
+            Outcome::Unit(result) => from_code(result),
+            outcome => unexpected("modify", outcome),
+        }
+    }
+}
+
+impl Iterator for ReplayView {
+    type Item = Record;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match replay(&self.interactions, Call::Fetch) {
+            Outcome::Record(fields) => fields.map(|fields| {
+                to_record(&fields).expect("invalid recording: could not create record")
+            }),
+            outcome => unexpected("fetch", outcome),
+        }
+    }
+}
+
+// Each interaction is written on a line as tab-separated tokens: the call and its arguments, `=>`, then the outcome.
+// Strings are prefixed with `'` and escaped, integers are prefixed with `#`, lists are prefixed with `*` and their length,
+// errors are prefixed with `!`, and `-` is null or `None`.
+
+fn write_interaction(text: &mut String, (call, outcome): &Interaction) {
+    let mut tokens: Vec<String> = Vec::new();
+    let string = |s: &str| {
+        let mut token = String::from("'");
+        for c in s.chars() {
+            match c {
+                '\\' => token.push_str(r"\\"),
+                '\t' => token.push_str(r"\t"),
+                '\r' => token.push_str(r"\r"),
+                '\n' => token.push_str(r"\n"),
+                c => token.push(c),
+            }
+        }
+        token
+    };
+    let optional = |s: &Option<String>| s.as_deref().map_or_else(|| "-".to_owned(), string);
+    let integer = |i: i64| format!("#{i}");
+    let list = |tokens: &mut Vec<String>, fields: &[Field]| {
+        tokens.push(format!("*{}", fields.len()));
+        tokens.extend(fields.iter().map(|field| match field {
+            Field::StringData(s) => string(s),
+            Field::IntegerData(i) => integer(*i as i64),
+            Field::Null => "-".to_owned(),
+        }));
+    };
+
+    match call {
+        Call::Database => tokens.push("database".to_owned()),
+        Call::DoAction(action) => tokens.extend(["do_action".to_owned(), optional(action)]),
+        Call::DoDeferredAction(action, data) => tokens.extend([
+            "do_deferred_action".to_owned(),
+            string(action),
+            string(data),
+        ]),
+        Call::Message(kind, fields) => {
+            tokens.extend(["message".to_owned(), integer(u32::from(*kind) as i64)]);
+            list(&mut tokens, fields);
+        }
+        Call::Mode(mode) => tokens.extend(["mode".to_owned(), integer(u32::from(*mode) as i64)]),
+        Call::Property(name) => tokens.extend(["property".to_owned(), string(name)]),
+        Call::SetProperty(name, value) => {
+            tokens.extend(["set_property".to_owned(), string(name), optional(value)])
+        }
+        Call::OpenView(sql) => tokens.extend(["open_view".to_owned(), string(sql)]),
+        Call::Execute(fields) => {
+            tokens.push("execute".to_owned());
+            match fields {
+                Some(fields) => list(&mut tokens, fields),
+                None => tokens.push("-".to_owned()),
+            }
+        }
+        Call::Fetch => tokens.push("fetch".to_owned()),
+        Call::Modify(mode, fields) => {
+            tokens.extend(["modify".to_owned(), integer(u32::from(*mode) as i64)]);
+            list(&mut tokens, fields);
+        }
+    }
+
+    tokens.push("=>".to_owned());
+    match outcome {
+        Outcome::Unit(Ok(())) => tokens.push("ok".to_owned()),
+        Outcome::Bool(b) => tokens.push(integer(*b as i64)),
+        Outcome::Integer(i) => tokens.push(integer(*i as i64)),
+        Outcome::String(Ok(s)) => tokens.push(string(s)),
+        Outcome::Record(Some(fields)) => list(&mut tokens, fields),
+        Outcome::Record(None) => tokens.push("-".to_owned()),
+        Outcome::Unit(Err(code)) | Outcome::String(Err(code)) => tokens.push(format!("!{code}")),
+    }
+
+    text.push_str(&tokens.join("\t"));
+    text.push('\n');
+}
+
+/// Reads tokens written by [`write_interaction()`].
+struct Tokens<'a>(std::str::Split<'a, char>);
+
+impl Tokens<'_> {
+    fn next(&mut self) -> Option<&str> {
+        self.0.next()
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let token = self.next()?.strip_prefix('\'')?;
+        let mut s = String::with_capacity(token.len());
+        let mut chars = token.chars();
+        while let Some(c) = chars.next() {
+            s.push(match c {
+                '\\' => match chars.next()? {
+                    't' => '\t',
+                    'r' => '\r',
+                    'n' => '\n',
+                    c => c,
+                },
+                c => c,
+            });
+        }
+        Some(s)
+    }
+
+    fn optional(&mut self) -> Option<Option<String>> {
+        match self.peek_none() {
+            true => Some(None),
+            false => self.string().map(Some),
+        }
+    }
+
+    fn integer(&mut self) -> Option<i32> {
+        self.next()?.strip_prefix('#')?.parse().ok()
+    }
+
+    fn list(&mut self) -> Option<Vec<Field>> {
+        let len: usize = self.next()?.strip_prefix('*')?.parse().ok()?;
+        (0..len)
+            .map(|_| {
+                let token = self.0.clone().next()?;
+                match token.chars().next()? {
+                    '\'' => self.string().map(Field::StringData),
+                    '#' => self.integer().map(Field::IntegerData),
+                    '-' => self.next().map(|_| Field::Null),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Consumes the next token if it is `-`.
+    fn peek_none(&mut self) -> bool {
+        if self.0.clone().next() == Some("-") {
+            self.next();
+            return true;
+        }
+        false
+    }
+
+    fn result<T>(
+        &mut self,
+        ok: impl FnOnce(&mut Self) -> Option<T>,
+    ) -> Option<std::result::Result<T, u32>> {
+        match self.0.clone().next()?.strip_prefix('!') {
+            Some(code) => {
+                let code = code.parse().ok()?;
+                self.next();
+                Some(Err(code))
+            }
+            None => ok(self).map(Ok),
+        }
+    }
+}
+
+fn read_interaction(line: &str) -> Option<Interaction> {
+    let mut tokens = Tokens(line.split('\t'));
+    let enum_value = |i: i32| u32::try_from(i).ok();
+    let call = match tokens.next()? {
+        "database" => Call::Database,
+        "do_action" => Call::DoAction(tokens.optional()?),
+        "do_deferred_action" => Call::DoDeferredAction(tokens.string()?, tokens.string()?),
+        "message" => {
+            let kind = MessageType::try_from(enum_value(tokens.integer()?)?).ok()?;
+            Call::Message(kind, tokens.list()?)
+        }
+        "mode" => Call::Mode(RunMode::try_from(enum_value(tokens.integer()?)?).ok()?),
+        "property" => Call::Property(tokens.string()?),
+        "set_property" => Call::SetProperty(tokens.string()?, tokens.optional()?),
+        "open_view" => Call::OpenView(tokens.string()?),
+        "execute" => match tokens.peek_none() {
+            true => Call::Execute(None),
+            false => Call::Execute(Some(tokens.list()?)),
+        },
+        "fetch" => Call::Fetch,
+        "modify" => {
+            let mode = ModifyMode::try_from(enum_value(tokens.integer()?)?).ok()?;
+            Call::Modify(mode, tokens.list()?)
+        }
+        _ => return None,
+    };
+
+    if tokens.next()? != "=>" {
+        return None;
+    }
+    let outcome = match &call {
+        Call::Message(..) => Outcome::Integer(tokens.integer()?),
+        Call::Mode(_) => Outcome::Bool(tokens.integer()? != 0),
+        Call::Property(_) => Outcome::String(tokens.result(|tokens| tokens.string())?),
+        Call::Fetch => match tokens.peek_none() {
+            true => Outcome::Record(None),
+            false => Outcome::Record(Some(tokens.list()?)),
+        },
+        _ => Outcome::Unit(tokens.result(|tokens| (tokens.next()? == "ok").then_some(()))?),
+    };
+
+    // Ignore lines with extra tokens, which were not written by write_interaction().
+    match tokens.next() {
+        Some(_) => None,
+        None => Some((call, outcome)),
+    }
+}
+
+#[cfg(all(test, feature = "test-util"))]
+mod tests {
+    use super::*;
+    use crate::testing::{MockDatabase, MockSession};
+
+    fn schedule<S: SessionOps>(session: &S) -> Result<Vec<String>> {
+        if session.mode(RunMode::Scheduled) {
+            return Ok(Vec::new());
+        }
+
+        let database = session.database();
+        let view = database.open_view("SELECT `Value` FROM `Property` WHERE `Property` = ?")?;
+        view.execute(Some(Record::with_fields(
+            None,
+            vec![Field::StringData("Features".to_owned())],
+        )?))?;
+        let features: Vec<String> = view
+            .map(|record| record.string_data(1))
+            .collect::<Result<_>>()?;
+
+        let dir = session.property("INSTALLDIR")?;
+        session.set_property("FEATURES", Some(&features.join(";")))?;
+        session.do_deferred_action("Deferred", format!("{dir}\t{}", features.join(";")))?;
+        Ok(features)
+    }
+
+    #[test]
+    fn record_replay() {
+        let database = MockDatabase::new();
+        database.create_table("Property", &["Property", "Value"]);
+        database
+            .insert(
+                "Property",
+                vec![
+                    Field::StringData("Features".to_owned()),
+                    Field::StringData("A,B".to_owned()),
+                ],
+            )
+            .unwrap();
+        let session = RecordingSession::new(
+            MockSession::new()
+                .with_property("INSTALLDIR", "C:\\Example\\")
+                .with_database(database),
+        );
+
+        let expected = schedule(&session).unwrap();
+        let text = session.to_text();
+        assert!(text.starts_with("mode\t#16\t=>\t#0\n"), "{text}");
+
+        let replay = ReplaySession::from_text(&text).unwrap();
+        assert_eq!(expected, schedule(&replay).unwrap());
+        assert!(replay.is_finished());
+
+        let mut rewritten = String::new();
+        for line in text.lines() {
+            write_interaction(&mut rewritten, &read_interaction(line).unwrap());
+        }
+        assert_eq!(text, rewritten);
+    }
+
+    #[test]
+    fn replay_errors() {
+        let replay =
+            ReplaySession::from_text("property\t'MISSING\t=>\t!1627\ndo_action\t-\t=>\tok\n")
+                .unwrap();
+        assert_eq!(
+            Some(codes::ERROR_FUNCTION_FAILED),
+            replay.property("MISSING").unwrap_err().code()
+        );
+        assert!(replay.do_action(None).is_ok());
+        assert!(ReplaySession::from_text("unknown\t=>\tok").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "replay diverged")]
+    fn replay_diverged() {
+        let replay = ReplaySession::from_text("property\t'A\t=>\t'1\n").unwrap();
+        let _ = replay.property("B");
+    }
+}
@@ -0,0 +1,377 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{install_product, set_internal_ui};
+use crate::{
+    ExternalUi, InstallUiLevel, InstallUiModifiers, LogModes, MessageResult, MessageType, Record,
+    Result,
+};
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+type ProgressHandler = Box<dyn FnMut(Progress) + Send>;
+
+/// Installs a package on a worker thread and reports progress, log messages, and errors as [`InstallEvent`]s.
+///
+/// Only one installation can run in a process at a time, and the external UI handler and internal UI level
+/// are set for the whole process while the installation runs.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{InstallEvent, Installer};
+///
+/// let installation = Installer::run("example.msi")
+///     .property("INSTALLDIR", r"C:\Program Files\Example")
+///     .on_progress(|progress| println!("{:.0}%", progress.percent()))
+///     .start();
+///
+/// for event in installation.events() {
+///     if let InstallEvent::ActionStart { description, .. } = event {
+///         println!("{description}");
+///     }
+/// }
+/// installation.wait()?;
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub struct Installer {
+    package: PathBuf,
+    properties: Vec<(String, String)>,
+    ui_level: InstallUiLevel,
+    ui_modifiers: InstallUiModifiers,
+    on_progress: Option<ProgressHandler>,
+}
+
+impl Installer {
+    /// Creates an [`Installer`] for the package at `path`. Call [`Installer::start()`] to begin the installation.
+    pub fn run(path: impl Into<PathBuf>) -> Self {
+        Self {
+            package: path.into(),
+            properties: Vec::new(),
+            ui_level: InstallUiLevel::None,
+            ui_modifiers: InstallUiModifiers::empty(),
+            on_progress: None,
+        }
+    }
+
+    /// Sets a public property for the installation.
+    pub fn property(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.properties.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets public properties for the installation.
+    pub fn properties<I, K, V>(mut self, properties: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.properties.extend(
+            properties
+                .into_iter()
+                .map(|(name, value)| (name.into(), value.into())),
+        );
+        self
+    }
+
+    /// Sets how much of its own UI the installer shows. The default is [`InstallUiLevel::None`] with no modifiers.
+    pub fn ui_level(mut self, level: InstallUiLevel, modifiers: InstallUiModifiers) -> Self {
+        self.ui_level = level;
+        self.ui_modifiers = modifiers;
+        self
+    }
+
+    /// Calls `f` on the worker thread whenever progress changes, in addition to sending [`InstallEvent::Progress`].
+    pub fn on_progress<F>(mut self, f: F) -> Self
+    where
+        F: FnMut(Progress) + Send + 'static,
+    {
+        self.on_progress = Some(Box::new(f));
+        self
+    }
+
+    /// Starts the installation on a worker thread.
+    pub fn start(self) -> Installation {
+        let (sender, events) = mpsc::channel();
+        let canceled = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let canceled = canceled.clone();
+            thread::spawn(move || self.install(sender, canceled))
+        };
+
+        Installation {
+            events,
+            canceled,
+            thread,
+        }
+    }
+
+    fn install(self, sender: Sender<InstallEvent>, canceled: Arc<AtomicBool>) -> Result<()> {
+        let Installer {
+            package,
+            properties,
+            ui_level,
+            ui_modifiers,
+            mut on_progress,
+        } = self;
+
+        let mut progress = ProgressTracker::default();
+        let filter = LogModes::FATAL_EXIT
+            | LogModes::ERROR
+            | LogModes::WARNING
+            | LogModes::INFO
+            | LogModes::ACTION_START
+            | LogModes::PROGRESS;
+        let _ui = ExternalUi::new(filter, move |kind, record| {
+            if canceled.load(Ordering::Relaxed) {
+                return MessageResult::Cancel;
+            }
+
+            let event = match kind {
+                MessageType::Progress => {
+                    let value = progress.update(
+                        record.integer_data(1).unwrap_or_default(),
+                        record.integer_data(2).unwrap_or_default(),
+                        record.integer_data(3).unwrap_or_default(),
+                    );
+                    match value {
+                        Some(value) => {
+                            if let Some(f) = on_progress.as_mut() {
+                                f(value);
+                            }
+                            InstallEvent::Progress(value)
+                        }
+                        None => return MessageResult::None,
+                    }
+                }
+                MessageType::ActionStart => InstallEvent::ActionStart {
+                    name: record.string_data(1).unwrap_or_default(),
+                    description: record.string_data(2).unwrap_or_default(),
+                },
+                MessageType::FatalExit | MessageType::Error => {
+                    InstallEvent::Error(format_text(record))
+                }
+                MessageType::Warning => InstallEvent::Warning(format_text(record)),
+                MessageType::Info => InstallEvent::Log(format_text(record)),
+                _ => return MessageResult::None,
+            };
+
+            // The receiver may have been dropped if the caller is only waiting for the result.
+            let _ = sender.send(event);
+            MessageResult::None
+        })?;
+
+        let (previous_level, previous_modifiers) = set_internal_ui(ui_level, ui_modifiers, None)?;
+        let result = install_product(&package, Some(&command_line(&properties)));
+        let _ = set_internal_ui(previous_level, previous_modifiers, None);
+
+        result
+    }
+}
+
+/// An installation started with [`Installer::start()`].
+pub struct Installation {
+    events: Receiver<InstallEvent>,
+    canceled: Arc<AtomicBool>,
+    thread: JoinHandle<Result<()>>,
+}
+
+impl Installation {
+    /// Gets the events sent by the installation. Iteration ends when the installation completes.
+    pub fn events(&self) -> &Receiver<InstallEvent> {
+        &self.events
+    }
+
+    /// Requests that the installation be canceled the next time it sends a message, which rolls back any changes.
+    pub fn cancel(&self) {
+        self.canceled.store(true, Ordering::Relaxed);
+    }
+
+    /// Waits for the installation to complete and returns its result.
+    ///
+    /// Returns an error with [`ErrorKind::ErrorCode`](crate::ErrorKind::ErrorCode) containing
+    /// `ERROR_INSTALL_USEREXIT` if the installation was canceled.
+    pub fn wait(self) -> Result<()> {
+        match self.thread.join() {
+            Ok(result) => result,
+            Err(err) => std::panic::resume_unwind(err),
+        }
+    }
+}
+
+/// An event sent by an [`Installation`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum InstallEvent {
+    /// An action started.
+    ActionStart {
+        /// The name of the action.
+        name: String,
+        /// The localized description of the action.
+        description: String,
+    },
+    /// Overall progress changed.
+    Progress(Progress),
+    /// An informational message was written to the log.
+    Log(String),
+    /// A warning occurred.
+    Warning(String),
+    /// An error occurred.
+    Error(String),
+}
+
+/// Progress of the current phase of an installation.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Progress {
+    /// The number of ticks completed.
+    pub completed: u64,
+    /// The total number of ticks.
+    pub total: u64,
+}
+
+impl Progress {
+    /// Gets the percentage of ticks completed from 0 to 100.
+    pub fn percent(&self) -> f64 {
+        match self.total {
+            0 => 0.0,
+            total => self.completed as f64 * 100.0 / total as f64,
+        }
+    }
+}
+
+/// Tracks progress from `INSTALLMESSAGE_PROGRESS` messages.
+#[derive(Debug, Default)]
+struct ProgressTracker {
+    progress: Progress,
+    backward: bool,
+}
+
+impl ProgressTracker {
+    /// Updates progress from the first three fields of a progress message,
+    /// and returns the current progress if it changed.
+    fn update(&mut self, kind: i32, value: i32, direction: i32) -> Option<Progress> {
+        let ticks = value.max(0) as u64;
+        let progress = &mut self.progress;
+        match kind {
+            // Reset
+            0 => {
+                self.backward = direction == 1;
+                progress.total = ticks;
+                progress.completed = match self.backward {
+                    true => ticks,
+                    false => 0,
+                };
+            }
+            // Report
+            2 => {
+                progress.completed = match self.backward {
+                    true => progress.completed.saturating_sub(ticks),
+                    false => progress.completed.saturating_add(ticks).min(progress.total),
+                };
+            }
+            // Add to the total
+            3 => progress.total = progress.total.saturating_add(ticks),
+            _ => return None,
+        }
+
+        Some(*progress)
+    }
+}
+
+/// Formats the text of a message, or returns an empty string if it could not be formatted.
+fn format_text(record: &Record) -> String {
+    record.format_text().unwrap_or_default()
+}
+
+/// Joins property assignments into a command line, quoting values.
+fn command_line(properties: &[(String, String)]) -> String {
+    properties
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, value.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_line_quotes_values() {
+        let properties = vec![
+            (
+                "INSTALLDIR".to_owned(),
+                r"C:\Program Files\Example".to_owned(),
+            ),
+            ("MESSAGE".to_owned(), r#"say "hi""#.to_owned()),
+        ];
+        assert_eq!(
+            r#"INSTALLDIR="C:\Program Files\Example" MESSAGE="say ""hi""""#,
+            command_line(&properties)
+        );
+        assert_eq!("", command_line(&[]));
+    }
+
+    #[test]
+    fn progress_tracker() {
+        let mut tracker = ProgressTracker::default();
+        assert_eq!(
+            Some(Progress {
+                completed: 0,
+                total: 100
+            }),
+            tracker.update(0, 100, 0)
+        );
+        assert_eq!(None, tracker.update(1, 10, 1));
+        assert_eq!(
+            Some(Progress {
+                completed: 25,
+                total: 100
+            }),
+            tracker.update(2, 25, 0)
+        );
+        assert_eq!(
+            Some(Progress {
+                completed: 25,
+                total: 150
+            }),
+            tracker.update(3, 50, 0)
+        );
+        assert_eq!(
+            Some(Progress {
+                completed: 150,
+                total: 150
+            }),
+            tracker.update(2, 200, 0)
+        );
+
+        // Rollback
+        tracker.update(0, 40, 1);
+        assert_eq!(
+            Some(Progress {
+                completed: 30,
+                total: 40
+            }),
+            tracker.update(2, 10, 0)
+        );
+    }
+
+    #[test]
+    fn progress_percent() {
+        assert_eq!(0.0, Progress::default().percent());
+        let progress = Progress {
+            completed: 1,
+            total: 4,
+        };
+        assert_eq!(25.0, progress.percent());
+    }
+}
@@ -1,12 +1,34 @@
 // Copyright 2024 Heath Stewart.
 // Licensed under the MIT License. See LICENSE.txt in the project root for license information.
 
-use crate::ffi;
-use crate::{Database, Error, Record, Result};
-use std::ffi::CString;
+mod cached;
+mod sync;
+
+use crate::{ffi, profiler};
+use crate::{
+    ColumnInfo, Database, Error, Field, InstallFeatureAttributes, InstallState, Record, Result,
+};
+use std::{
+    ffi::{OsStr, OsString},
+    fmt::Display,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+pub use cached::CachedSession;
+pub use sync::SyncSession;
 
 /// A Windows Installer session passed to custom actions.
 ///
+/// # Thread safety
+///
+/// A [`Session`] and other types using Windows Installer handles like [`Database`], [`View`](crate::View),
+/// and [`Record`] must not be used from multiple threads at the same time. A [`Session`] or [`View`](crate::View)
+/// may be moved to another thread, but cannot be shared. Because clones share the same handle,
+/// [`Database`] and [`Record`] cannot be moved to another thread either.
+///
+/// To write messages or report progress from worker threads, create a [`SyncSession`].
+///
 /// # Example
 ///
 /// ```no_run
@@ -25,10 +47,16 @@ use std::ffi::CString;
 /// ```
 #[repr(transparent)]
 pub struct Session {
-    h: ffi::MSIHANDLE,
+    pub(crate) h: ffi::SessionHandle,
+    _marker: ffi::NotSync,
 }
 
 impl Session {
+    /// Returns a [`CachedSession`] that caches property values after they are first read.
+    pub fn cached(&self) -> CachedSession<'_> {
+        CachedSession::new(self)
+    }
+
     /// Returns the active database for the installation. This function returns a read-only [`Database`].
     pub fn database(&self) -> Database {
         unsafe {
@@ -44,11 +72,12 @@ impl Session {
     /// call [`Session::do_deferred_action()`].
     pub fn do_action(&self, action: Option<&str>) -> Result<()> {
         unsafe {
-            let action = match action {
-                Some(s) => CString::new(s)?,
-                None => CString::default(),
-            };
-            let ret = ffi::MsiDoAction(self.h, action.as_ptr());
+            let name = action;
+            let action = ffi::to_wide_opt(action)?;
+            let ret = profiler::measure(
+                || format!("Session::do_action({})", name.unwrap_or_default()).into(),
+                || ffi::MsiDoAction(self.h, action.as_ptr()),
+            );
             if ret != ffi::ERROR_SUCCESS {
                 return Err(Error::from_error_code(ret));
             }
@@ -59,6 +88,10 @@ impl Session {
 
     /// Sets custom action data and schedules a deferred custom action.
     ///
+    /// The `custom_action_data` may be any value that can be formatted e.g., a string or integer.
+    /// Returns an error if the data could not be set or the action could not be scheduled e.g., if it is not authored
+    /// in the `CustomAction` table.
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -67,10 +100,13 @@ impl Session {
     ///
     /// #[no_mangle]
     /// pub extern "C" fn MyCustomAction(session: Session) -> u32 {
-    ///     for i in 0..5 {
-    ///         session.do_deferred_action("MyDeferredCustomAction", &i.to_string());
-    ///     }
-    ///     ERROR_SUCCESS
+    ///     let schedule = || -> Result<()> {
+    ///         for i in 0..5 {
+    ///             session.do_deferred_action("MyDeferredCustomAction", i)?;
+    ///         }
+    ///         Ok(())
+    ///     };
+    ///     schedule().into_ca_result(&session)
     /// }
     ///
     /// #[no_mangle]
@@ -81,16 +117,118 @@ impl Session {
     ///     ERROR_SUCCESS
     /// }
     /// ```
-    pub fn do_deferred_action(&self, action: &str, custom_action_data: &str) -> Result<()> {
-        self.set_property(action, Some(custom_action_data))?;
+    pub fn do_deferred_action(&self, action: &str, custom_action_data: impl Display) -> Result<()> {
+        self.set_property(action, Some(&custom_action_data.to_string()))?;
         self.do_action(Some(action))
     }
 
+    /// Gets the installed state and requested action state of a feature in the `Feature` table.
+    ///
+    /// Call after `CostFinalize` has evaluated feature conditions.
+    pub fn feature_state(&self, feature: &str) -> Result<(InstallState, InstallState)> {
+        unsafe {
+            let feature = ffi::to_wide(feature)?;
+            let (mut installed, mut action) = (0i32, 0i32);
+
+            let ret =
+                ffi::MsiGetFeatureState(self.h, feature.as_ptr(), &mut installed, &mut action);
+            if ret != ffi::ERROR_SUCCESS {
+                return Err(Error::from_error_code(ret));
+            }
+
+            Ok((
+                InstallState::try_from(installed)?,
+                InstallState::try_from(action)?,
+            ))
+        }
+    }
+
+    /// Formats the template string in field 0 of a [`Record`] with the remaining fields,
+    /// resolving property references e.g., `[INSTALLDIR]` within the [`Session`].
+    ///
+    /// See [`Record::format_text()`] to format a [`Record`] without a [`Session`].
+    pub fn format_record(&self, record: &Record) -> Result<String> {
+        unsafe {
+            ffi::get_string(|value, value_len| {
+                ffi::MsiFormatRecord(self.h, *record.h, value, value_len)
+            })
+        }
+    }
+
+    /// Formats a [`Record`] like [`Session::format_record()`] and writes it to `w`
+    /// without allocating an intermediate [`String`].
+    pub fn format_record_to<W: std::fmt::Write + ?Sized>(
+        &self,
+        record: &Record,
+        w: &mut W,
+    ) -> Result<()> {
+        unsafe {
+            ffi::get_string_to(w, |value, value_len| {
+                ffi::MsiFormatRecord(self.h, *record.h, value, value_len)
+            })
+        }
+    }
+
     /// The numeric language ID used by the current install session.
     pub fn language(&self) -> u16 {
         unsafe { ffi::MsiGetLanguage(self.h) }
     }
 
+    /// Writes a column-aligned rendering of a table to the installation log
+    /// as [`MessageType::Info`] messages, which is useful to verify temporary rows added by custom actions.
+    ///
+    /// Pass `Some(limit)` to write no more than `limit` rows. Stream columns are not read.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use msica::prelude::*;
+    ///
+    /// # fn example(session: &Session) -> Result<()> {
+    /// session.log_table("InstallExecuteSequence", Some(20))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn log_table(&self, table: &str, limit: Option<usize>) -> Result<()> {
+        let database = self.database();
+        let mut view = database.open_view(&format!("SELECT * FROM `{}`", table))?;
+        let names = view.column_info(ColumnInfo::Names)?;
+        let types = view.column_info(ColumnInfo::Types)?;
+
+        let mut columns = Vec::new();
+        let mut kinds = Vec::new();
+        for i in 1..=names.field_count() {
+            columns.push(names.string_data(i)?);
+            kinds.push(types.string_data(i)?.chars().next().unwrap_or('s'));
+        }
+
+        view.execute(None)?;
+        let mut rows = Vec::new();
+        for record in view.by_ref().take(limit.unwrap_or(usize::MAX)) {
+            let mut row = Vec::with_capacity(kinds.len());
+            for (i, kind) in (1u32..).zip(&kinds) {
+                let value = match kind {
+                    _ if record.is_null(i) => String::new(),
+                    'i' | 'I' => record
+                        .integer_data(i)
+                        .map(|value| value.to_string())
+                        .unwrap_or_default(),
+                    'v' | 'V' => "(stream)".to_owned(),
+                    _ => record.string_data(i)?,
+                };
+                row.push(value);
+            }
+            rows.push(row);
+        }
+        view.close();
+
+        for line in format_table(table, &columns, &rows) {
+            self.info(line);
+        }
+
+        Ok(())
+    }
+
     /// Processes a [`Record`] within the [`Session`].
     pub fn message(&self, kind: MessageType, record: &Record) -> i32 {
         unsafe { ffi::MsiProcessMessage(self.h, kind, *record.h) }
@@ -125,56 +263,148 @@ impl Session {
     /// Gets the value of the named property, or an empty string if undefined.
     pub fn property(&self, name: &str) -> Result<String> {
         unsafe {
-            // TODO: Return result containing NulError if returned.
-            let name = CString::new(name)?;
+            let name = ffi::to_wide(name)?;
+            ffi::get_string(|value, value_len| {
+                ffi::MsiGetProperty(self.h, name.as_ptr(), value, value_len)
+            })
+        }
+    }
+
+    /// Gets the value of the named property, or an empty string if undefined,
+    /// replacing any invalid data with [`U+FFFD REPLACEMENT CHARACTER`](char::REPLACEMENT_CHARACTER).
+    pub fn property_lossy(&self, name: &str) -> Result<String> {
+        unsafe {
+            let name = ffi::to_wide(name)?;
+            ffi::get_string_with(ffi::Decoding::Lossy, |value, value_len| {
+                ffi::MsiGetProperty(self.h, name.as_ptr(), value, value_len)
+            })
+        }
+    }
 
-            let mut value_len = 0u32;
-            let value = CString::default();
+    /// Gets the value of the named property into an existing buffer, which is cleared first.
+    /// The buffer is left empty if the property is undefined.
+    ///
+    /// Reuse the same buffer when reading many properties to avoid allocating a new [`String`] for each.
+    pub fn property_into(&self, name: &str, buf: &mut String) -> Result<()> {
+        unsafe {
+            let name = ffi::to_wide(name)?;
+            ffi::get_string_into(buf, |value, value_len| {
+                ffi::MsiGetProperty(self.h, name.as_ptr(), value, value_len)
+            })
+        }
+    }
 
-            let mut ret = ffi::MsiGetProperty(
-                self.h,
-                name.as_ptr(),
-                value.as_ptr() as ffi::LPSTR,
-                &mut value_len as *mut u32,
-            );
-            if ret != ffi::ERROR_MORE_DATA {
+    /// Gets the value of the named property as an [`OsString`], or an empty string if undefined.
+    ///
+    /// Unlike [`Session::property()`], this does not fail if the value is not valid Unicode.
+    pub fn property_os(&self, name: &str) -> Result<OsString> {
+        unsafe {
+            let name = ffi::to_wide(name)?;
+            let value = ffi::get_wide(|value, value_len| {
+                ffi::MsiGetProperty(self.h, name.as_ptr(), value, value_len)
+            })?;
+
+            Ok(ffi::from_wide_os(&value))
+        }
+    }
+
+    /// Sets the value of the named property to an [`OsStr`]. Pass `None` to clear the field.
+    pub fn set_property_os(&self, name: &str, value: Option<&OsStr>) -> Result<()> {
+        unsafe {
+            let name = ffi::to_wide(name)?;
+            let value = ffi::to_wide_os(value.unwrap_or_default())?;
+
+            let ret = ffi::MsiSetProperty(self.h, name.as_ptr(), value.as_ptr());
+            if ret != ffi::ERROR_SUCCESS {
                 return Err(Error::from_error_code(ret));
             }
 
-            let mut value_len = value_len + 1u32;
-            let mut value: Vec<u8> = vec![0; value_len as usize];
+            Ok(())
+        }
+    }
 
-            ret = ffi::MsiGetProperty(
-                self.h,
-                name.as_ptr(),
-                value.as_mut_ptr() as ffi::LPSTR,
-                &mut value_len as *mut u32,
-            );
+    /// Gets the full source path for a folder in the `Directory` table.
+    ///
+    /// Call after `CostFinalize` has resolved directories.
+    pub fn source_path(&self, folder: &str) -> Result<PathBuf> {
+        unsafe {
+            let folder = ffi::to_wide(folder)?;
+            let value = ffi::get_wide(|value, value_len| {
+                ffi::MsiGetSourcePath(self.h, folder.as_ptr(), value, value_len)
+            })?;
+
+            Ok(ffi::from_wide_os(&value).into())
+        }
+    }
+
+    /// Gets the full target path for a folder in the `Directory` table.
+    ///
+    /// Call after `CostFinalize` has resolved directories.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use msica::prelude::*;
+    ///
+    /// # fn example(session: &Session) -> Result<()> {
+    /// let config = session.target_path("INSTALLDIR")?.join("config.json");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn target_path(&self, folder: &str) -> Result<PathBuf> {
+        unsafe {
+            let folder = ffi::to_wide(folder)?;
+            let value = ffi::get_wide(|value, value_len| {
+                ffi::MsiGetTargetPath(self.h, folder.as_ptr(), value, value_len)
+            })?;
+
+            Ok(ffi::from_wide_os(&value).into())
+        }
+    }
+
+    /// Sets the full target path for a folder in the `Directory` table.
+    pub fn set_target_path(&self, folder: &str, path: impl AsRef<Path>) -> Result<()> {
+        unsafe {
+            let folder = ffi::to_wide(folder)?;
+            let path = ffi::to_wide_os(path.as_ref().as_os_str())?;
+
+            let ret = ffi::MsiSetTargetPath(self.h, folder.as_ptr(), path.as_ptr());
             if ret != ffi::ERROR_SUCCESS {
                 return Err(Error::from_error_code(ret));
             }
 
-            value.truncate(value_len as usize);
-            let text = String::from_utf8(value)?;
+            Ok(())
+        }
+    }
+
+    /// Sets the attributes of a feature at runtime e.g., from a custom action that runs before `CostFinalize`.
+    ///
+    /// Pass [`FeatureAttributes`](crate::FeatureAttributes) from the `Feature` table or [`InstallFeatureAttributes`].
+    pub fn set_feature_attributes(
+        &self,
+        feature: &str,
+        attributes: impl Into<InstallFeatureAttributes>,
+    ) -> Result<()> {
+        unsafe {
+            let feature = ffi::to_wide(feature)?;
+
+            let ret =
+                ffi::MsiSetFeatureAttributes(self.h, feature.as_ptr(), attributes.into().bits());
+            if ret != ffi::ERROR_SUCCESS {
+                return Err(Error::from_error_code(ret));
+            }
 
-            Ok(text)
+            Ok(())
         }
     }
 
     /// Sets the value of the named property. Pass `None` to clear the field.
     pub fn set_property(&self, name: &str, value: Option<&str>) -> Result<()> {
         unsafe {
-            let name = CString::new(name)?;
-            let value = match value {
-                Some(s) => CString::new(s)?,
-                None => CString::default(),
-            };
-
-            let ret = ffi::MsiSetProperty(
-                self.h,
-                name.as_ptr() as ffi::LPCSTR,
-                value.as_ptr() as ffi::LPCSTR,
-            );
+            let name = ffi::to_wide(name)?;
+            let value = ffi::to_wide_opt(value)?;
+
+            let ret = ffi::MsiSetProperty(self.h, name.as_ptr(), value.as_ptr());
             if ret != ffi::ERROR_SUCCESS {
                 return Err(Error::from_error_code(ret));
             }
@@ -182,52 +412,176 @@ impl Session {
             Ok(())
         }
     }
+
+    /// Writes text to the installation log as a [`MessageType::Info`] message.
+    pub(crate) fn info(&self, text: impl Into<String>) {
+        // Pass text as a field so that square brackets are not formatted.
+        if let Ok(record) = Record::with_fields(Some("[1]"), vec![Field::StringData(text.into())]) {
+            self.message(MessageType::Info, &record);
+        }
+    }
+
+    /// Creates a [`Session`] from a raw handle e.g., one passed to a custom action written in C.
+    ///
+    /// The [`Session`] does not own the handle and will not close it.
+    ///
+    /// # Safety
+    ///
+    /// The handle must be a valid install session handle that remains open while the [`Session`] is used.
+    pub unsafe fn from_raw(h: ffi::MSIHANDLE) -> Self {
+        Self::from_handle(ffi::SessionHandle::from_raw(h))
+    }
+
+    /// Consumes the [`Session`] and returns the raw handle.
+    pub fn into_raw(self) -> ffi::MSIHANDLE {
+        *self.h
+    }
+
+    /// Gets the raw handle without transferring ownership.
+    pub fn as_raw(&self) -> ffi::MSIHANDLE {
+        *self.h
+    }
+
+    pub(crate) fn from_handle(h: ffi::SessionHandle) -> Self {
+        Session {
+            h,
+            _marker: PhantomData,
+        }
+    }
 }
 
-/// Message types that can be processed by a custom action.
-#[repr(u32)]
-pub enum MessageType {
-    Error = 0x0100_0000,
-    Warning = 0x0200_0000,
-    User = 0x0300_0000,
-    Info = 0x0400_0000,
-    Progress = 0x0a00_0000,
-    CommonData = 0x0b00_0000,
+fn format_table(table: &str, columns: &[String], rows: &[Vec<String>]) -> Vec<String> {
+    let mut widths: Vec<usize> = columns
+        .iter()
+        .map(|column| column.chars().count())
+        .collect();
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+
+    let format_row = |values: &[String]| {
+        values
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:<width$}", value, width = width))
+            .collect::<Vec<_>>()
+            .join(" | ")
+            .trim_end()
+            .to_owned()
+    };
+
+    let mut lines = Vec::with_capacity(rows.len() + 3);
+    lines.push(format!("Table: {} ({} rows)", table, rows.len()));
+    lines.push(format_row(columns));
+    lines.push(
+        widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<_>>()
+            .join("-+-"),
+    );
+    lines.extend(rows.iter().map(|row| format_row(row)));
+    lines
+}
+
+repr_enum! {
+    /// Message types that can be processed by a custom action or received by an [`ExternalUi`](crate::ExternalUi) handler.
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub enum MessageType: u32 {
+        FatalExit = 0,
+        Error = 0x0100_0000,
+        Warning = 0x0200_0000,
+        User = 0x0300_0000,
+        Info = 0x0400_0000,
+        FilesInUse = 0x0500_0000,
+        ResolveSource = 0x0600_0000,
+        OutOfDiskSpace = 0x0700_0000,
+        ActionStart = 0x0800_0000,
+        ActionData = 0x0900_0000,
+        Progress = 0x0a00_0000,
+        CommonData = 0x0b00_0000,
+        Initialize = 0x0c00_0000,
+        Terminate = 0x0d00_0000,
+        ShowDialog = 0x0e00_0000,
+        RmFilesInUse = 0x1900_0000,
+        InstallStart = 0x1a00_0000,
+        InstallEnd = 0x1b00_0000,
+    }
 }
 
-/// Run modes passed to [`Session::mode()`].
-#[repr(u32)]
-pub enum RunMode {
-    /// Administrative mode install, else product install.
-    Admin = 0,
-    /// Advertise mode of install.
-    Advertise = 1,
-    ///Maintenance mode database loaded.
-    Maintenance = 2,
-    /// Rollback is enabled.
-    RollbackEnabled = 3,
-    /// Log file is active.
-    LogEnabled = 4,
-    /// Executing or spooling operations.
-    Operations = 5,
-    /// Reboot is needed.
-    RebootAtEnd = 6,
-    /// Reboot is needed to continue installation
-    RebootNow = 7,
-    /// Installing files from cabinets and files using Media table.
-    Cabinet = 8,
-    /// Source files use only short file names.
-    SourceShortNames = 9,
-    /// Target files are to use only short file names.
-    TargetShortNames = 10,
-    /// Operating system is Windows 98/95.
-    Windows9x = 12,
-    /// Operating system supports advertising of products.
-    ZawEnabled = 13,
-    /// Deferred custom action called from install script execution.
-    Scheduled = 16,
-    /// Deferred custom action called from rollback execution script.
-    Rollback = 17,
-    /// Deferred custom action called from commit execution script.
-    Commit = 18,
+repr_enum! {
+    /// Run modes passed to [`Session::mode()`].
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub enum RunMode: u32 {
+        /// Administrative mode install, else product install.
+        Admin = 0,
+        /// Advertise mode of install.
+        Advertise = 1,
+        ///Maintenance mode database loaded.
+        Maintenance = 2,
+        /// Rollback is enabled.
+        RollbackEnabled = 3,
+        /// Log file is active.
+        LogEnabled = 4,
+        /// Executing or spooling operations.
+        Operations = 5,
+        /// Reboot is needed.
+        RebootAtEnd = 6,
+        /// Reboot is needed to continue installation
+        RebootNow = 7,
+        /// Installing files from cabinets and files using Media table.
+        Cabinet = 8,
+        /// Source files use only short file names.
+        SourceShortNames = 9,
+        /// Target files are to use only short file names.
+        TargetShortNames = 10,
+        /// Operating system is Windows 98/95.
+        Windows9x = 12,
+        /// Operating system supports advertising of products.
+        ZawEnabled = 13,
+        /// Deferred custom action called from install script execution.
+        Scheduled = 16,
+        /// Deferred custom action called from rollback execution script.
+        Rollback = 17,
+        /// Deferred custom action called from commit execution script.
+        Commit = 18,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_mode_try_from() {
+        assert!(matches!(RunMode::try_from(17), Ok(RunMode::Rollback)));
+        assert_eq!(u32::from(RunMode::Commit), 18);
+
+        let Err(error) = RunMode::try_from(11) else {
+            panic!("expected error");
+        };
+        assert_eq!(&crate::ErrorKind::DataConversion, error.kind());
+        assert_eq!("unknown RunMode value: 0xb", error.to_string());
+    }
+
+    #[test]
+    fn format_table_aligns_columns() {
+        let columns = vec!["Action".to_owned(), "Sequence".to_owned()];
+        let rows = vec![
+            vec!["CostInitialize".to_owned(), "800".to_owned()],
+            vec!["MyAction".to_owned(), String::new()],
+        ];
+        assert_eq!(
+            format_table("InstallExecuteSequence", &columns, &rows),
+            vec![
+                "Table: InstallExecuteSequence (2 rows)",
+                "Action         | Sequence",
+                "---------------+---------",
+                "CostInitialize | 800",
+                "MyAction       |",
+            ]
+        );
+    }
 }
@@ -0,0 +1,94 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{Result, Session};
+use std::{cell::RefCell, collections::HashMap, ops::Deref};
+
+/// A [`Session`] that caches property values after they are first read.
+///
+/// Properties set through [`CachedSession::set_property()`] update the cache, and actions run through
+/// [`CachedSession::do_action()`] clear it since they may change any property. Changes made any other way
+/// e.g., through [`Session::set_property_os()`] are not tracked; call [`CachedSession::invalidate()`]
+/// or [`CachedSession::clear()`] afterward.
+///
+/// Create one with [`Session::cached()`].
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::prelude::*;
+///
+/// # fn example(session: &Session) -> Result<()> {
+/// let session = session.cached();
+/// for name in ["a.txt", "b.txt"] {
+///     // INSTALLDIR is only read from the session once.
+///     let path = format!("{}{}", session.property("INSTALLDIR")?, name);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct CachedSession<'a> {
+    session: &'a Session,
+    properties: RefCell<HashMap<String, String>>,
+}
+
+impl<'a> CachedSession<'a> {
+    pub(crate) fn new(session: &'a Session) -> Self {
+        Self {
+            session,
+            properties: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Gets the value of the named property, or an empty string if undefined.
+    ///
+    /// The value is read from the session only if it was not already cached.
+    pub fn property(&self, name: &str) -> Result<String> {
+        if let Some(value) = self.properties.borrow().get(name) {
+            return Ok(value.clone());
+        }
+
+        let value = self.session.property(name)?;
+        self.properties
+            .borrow_mut()
+            .insert(name.to_owned(), value.clone());
+
+        Ok(value)
+    }
+
+    /// Sets the value of the named property and updates the cache. Pass `None` to clear the field.
+    pub fn set_property(&self, name: &str, value: Option<&str>) -> Result<()> {
+        // Invalidate first in case setting the property fails after changing it.
+        self.invalidate(name);
+        self.session.set_property(name, value)?;
+        self.properties
+            .borrow_mut()
+            .insert(name.to_owned(), value.unwrap_or_default().to_owned());
+
+        Ok(())
+    }
+
+    /// Runs the specified action like [`Session::do_action()`] and clears the cache.
+    pub fn do_action(&self, action: Option<&str>) -> Result<()> {
+        self.clear();
+        self.session.do_action(action)
+    }
+
+    /// Removes the named property from the cache so it is read from the session again.
+    pub fn invalidate(&self, name: &str) {
+        self.properties.borrow_mut().remove(name);
+    }
+
+    /// Removes all properties from the cache.
+    pub fn clear(&self) {
+        self.properties.borrow_mut().clear();
+    }
+}
+
+impl Deref for CachedSession<'_> {
+    type Target = Session;
+
+    fn deref(&self) -> &Self::Target {
+        self.session
+    }
+}
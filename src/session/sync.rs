@@ -0,0 +1,79 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{wca, MessageType, Record, Result, Session};
+use std::sync::{Mutex, MutexGuard};
+
+/// A [`Session`] that can be shared with worker threads to write messages and report progress.
+///
+/// Calls are serialized so that the underlying [`Session`] is never used from multiple threads at the same time.
+/// Only writing messages and reporting progress are supported; all other work with the [`Session`]
+/// should be done on the thread that called the custom action.
+///
+/// A [`SyncSession`] must not be used after the custom action returns.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::prelude::*;
+/// use msica::SyncSession;
+/// const ERROR_SUCCESS: u32 = 0;
+///
+/// #[no_mangle]
+/// pub extern "C" fn MyCustomAction(session: Session) -> u32 {
+///     let session = SyncSession::new(&session);
+///     std::thread::scope(|scope| {
+///         for i in 0..4 {
+///             let session = &session;
+///             scope.spawn(move || session.log(&format!("worker {i} started")));
+///         }
+///     });
+///     ERROR_SUCCESS
+/// }
+/// ```
+pub struct SyncSession {
+    session: Mutex<Session>,
+}
+
+impl SyncSession {
+    /// Creates a [`SyncSession`] for the given [`Session`].
+    pub fn new(session: &Session) -> Self {
+        Self {
+            session: Mutex::new(Session::from_handle(session.h)),
+        }
+    }
+
+    /// Processes a [`Record`] within the [`Session`] like [`Session::message()`].
+    pub fn message(&self, kind: MessageType, record: &Record) -> i32 {
+        self.lock().message(kind, record)
+    }
+
+    /// Writes text to the installation log. Square brackets in the text are not formatted.
+    pub fn log(&self, text: &str) {
+        self.lock().info(text);
+    }
+
+    /// Increments the progress bar by `ticks` like [`wca::progress_message()`].
+    ///
+    /// Returns an error with [`ErrorKind::ErrorCode`](crate::ErrorKind::ErrorCode) containing
+    /// `ERROR_INSTALL_USEREXIT` if the user canceled.
+    pub fn progress(&self, ticks: i32) -> Result<()> {
+        wca::progress_message(&*self.lock(), ticks, false)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Session> {
+        // The session is not left in an inconsistent state by a panicking thread.
+        self.session.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SyncSession>();
+    }
+}
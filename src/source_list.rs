@@ -0,0 +1,228 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi;
+use crate::{Error, InstallContext, Result};
+
+/// MSICODE_PRODUCT
+const CODE_PRODUCT: u32 = 0;
+/// MSICODE_PATCH
+const CODE_PATCH: u32 = 0x4000_0000;
+
+/// The sources registered for an installed product or patch, from which files are installed or repaired.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{InstallContext, SourceList};
+///
+/// let sources = SourceList::product("{01234567-89AB-CDEF-0123-456789ABCDEF}", None, InstallContext::MACHINE)?;
+/// sources.add_media_disk(1, Some("DISK1"), Some("Example Disk 1"))?;
+/// for disk in sources.media_disks() {
+///     println!("{:?}", disk?);
+/// }
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub struct SourceList {
+    code: Vec<u16>,
+    sid: Option<Vec<u16>>,
+    context: InstallContext,
+    options: u32,
+}
+
+impl SourceList {
+    /// Gets the source list of a product installed in a single `context` for a user.
+    ///
+    /// Pass `None` for the `sid` to use the current user, which is required for [`InstallContext::MACHINE`].
+    pub fn product(
+        product_code: impl AsRef<str>,
+        sid: Option<&str>,
+        context: InstallContext,
+    ) -> Result<Self> {
+        Self::new(product_code.as_ref(), sid, context, CODE_PRODUCT)
+    }
+
+    /// Gets the source list of a patch applied in a single `context` for a user.
+    ///
+    /// Pass `None` for the `sid` to use the current user, which is required for [`InstallContext::MACHINE`].
+    pub fn patch(
+        patch_code: impl AsRef<str>,
+        sid: Option<&str>,
+        context: InstallContext,
+    ) -> Result<Self> {
+        Self::new(patch_code.as_ref(), sid, context, CODE_PATCH)
+    }
+
+    fn new(code: &str, sid: Option<&str>, context: InstallContext, options: u32) -> Result<Self> {
+        let sid = match sid {
+            Some(sid) => Some(ffi::to_wide(sid)?),
+            None => None,
+        };
+
+        Ok(Self {
+            code: ffi::to_wide(code)?,
+            sid,
+            context,
+            options,
+        })
+    }
+
+    /// Adds or updates a disk in the `Media` table with the given `disk_id`.
+    ///
+    /// Pass `None` to keep the existing volume label or disk prompt of a registered disk.
+    pub fn add_media_disk(
+        &self,
+        disk_id: u32,
+        volume_label: Option<&str>,
+        disk_prompt: Option<&str>,
+    ) -> Result<()> {
+        unsafe {
+            let volume_label = match volume_label {
+                Some(volume_label) => Some(ffi::to_wide(volume_label)?),
+                None => None,
+            };
+            let disk_prompt = match disk_prompt {
+                Some(disk_prompt) => Some(ffi::to_wide(disk_prompt)?),
+                None => None,
+            };
+
+            let ret = ffi::MsiSourceListAddMediaDisk(
+                self.code.as_ptr(),
+                self.sid_ptr(),
+                self.context.bits() as i32,
+                self.options,
+                disk_id,
+                volume_label
+                    .as_ref()
+                    .map_or(std::ptr::null(), |volume_label| volume_label.as_ptr()),
+                disk_prompt
+                    .as_ref()
+                    .map_or(std::ptr::null(), |disk_prompt| disk_prompt.as_ptr()),
+            );
+            if ret != ffi::ERROR_SUCCESS {
+                return Err(Error::from_error_code(ret));
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Removes all registered disks.
+    pub fn clear_media_disks(&self) -> Result<()> {
+        unsafe {
+            // The disk ID is reserved and must be 0.
+            let ret = ffi::MsiSourceListClearMediaDisk(
+                self.code.as_ptr(),
+                self.sid_ptr(),
+                self.context.bits() as i32,
+                self.options,
+                0,
+            );
+            if ret != ffi::ERROR_SUCCESS {
+                return Err(Error::from_error_code(ret));
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Forces the installer to search the source list for a valid source the next time one is needed
+    /// e.g., after the last used source was moved or removed.
+    pub fn force_resolution(&self) -> Result<()> {
+        unsafe {
+            let ret = ffi::MsiSourceListForceResolutionEx(
+                self.code.as_ptr(),
+                self.sid_ptr(),
+                self.context.bits() as i32,
+                self.options,
+            );
+            if ret != ffi::ERROR_SUCCESS {
+                return Err(Error::from_error_code(ret));
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Enumerates the registered disks.
+    pub fn media_disks(&self) -> MediaDisks<'_> {
+        MediaDisks {
+            source_list: self,
+            index: 0,
+            done: false,
+        }
+    }
+
+    fn sid_ptr(&self) -> ffi::LPCWSTR {
+        self.sid
+            .as_ref()
+            .map_or(std::ptr::null(), |sid| sid.as_ptr())
+    }
+}
+
+/// A disk registered in a [`SourceList`], returned from [`SourceList::media_disks()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MediaDisk {
+    /// The `DiskId` in the `Media` table.
+    pub disk_id: u32,
+    /// The volume label of the disk.
+    pub volume_label: String,
+    /// The prompt shown when the disk needs to be inserted.
+    pub disk_prompt: String,
+}
+
+/// Enumerates the disks registered in a [`SourceList`]. Returned from [`SourceList::media_disks()`].
+pub struct MediaDisks<'a> {
+    source_list: &'a SourceList,
+    index: u32,
+    done: bool,
+}
+
+impl Iterator for MediaDisks<'_> {
+    type Item = Result<MediaDisk>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let source_list = self.source_list;
+        let mut disk_id = 0u32;
+        let mut ret = ffi::ERROR_SUCCESS;
+
+        let disk = ffi::get_strings(
+            |[(volume_label, volume_label_len), (disk_prompt, disk_prompt_len)]| {
+                ret = unsafe {
+                    ffi::MsiSourceListEnumMediaDisks(
+                        source_list.code.as_ptr(),
+                        source_list.sid_ptr(),
+                        source_list.context.bits() as i32,
+                        source_list.options,
+                        self.index,
+                        &mut disk_id,
+                        volume_label,
+                        volume_label_len,
+                        disk_prompt,
+                        disk_prompt_len,
+                    )
+                };
+                ret
+            },
+        );
+        match ret {
+            // Move past a disk even if its strings could not be decoded.
+            ffi::ERROR_SUCCESS => self.index += 1,
+            ffi::ERROR_NO_MORE_ITEMS => {
+                self.done = true;
+                return None;
+            }
+            _ => self.done = true,
+        }
+
+        Some(disk.map(|[volume_label, disk_prompt]| MediaDisk {
+            disk_id,
+            volume_label,
+            disk_prompt,
+        }))
+    }
+}
@@ -0,0 +1,391 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Names of standard actions, top-level actions, and sequence tables.
+//!
+//! Use these instead of string literals when scheduling actions or writing conditions
+//! so that misspelled names fail to compile.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use msica::prelude::*;
+//! use msica::standard_actions::{self, INSTALL_EXECUTE_SEQUENCE};
+//!
+//! # fn example(session: &Session) -> Result<()> {
+//! let query = format!(
+//!     "SELECT `Sequence` FROM `{INSTALL_EXECUTE_SEQUENCE}` WHERE `Action` = '{}'",
+//!     standard_actions::INSTALL_FILES,
+//! );
+//! session.database().open_view(&query)?;
+//! # Ok(())
+//! # }
+//! ```
+
+/// Ensures the registry has enough free space for the installation.
+pub const ALLOCATE_REGISTRY_SPACE: &str = "AllocateRegistrySpace";
+
+/// Searches for files, directories, and registry values to set properties from the `AppSearch` table.
+pub const APP_SEARCH: &str = "AppSearch";
+
+/// Binds executables to the DLLs they import.
+pub const BIND_IMAGE: &str = "BindImage";
+
+/// Searches for qualifying products in the `CCPSearch` table.
+pub const CCP_SEARCH: &str = "CCPSearch";
+
+/// Ends costing and determines the final target directories and feature states.
+pub const COST_FINALIZE: &str = "CostFinalize";
+
+/// Starts costing.
+pub const COST_INITIALIZE: &str = "CostInitialize";
+
+/// Creates empty folders from the `CreateFolder` table.
+pub const CREATE_FOLDERS: &str = "CreateFolders";
+
+/// Creates shortcuts from the `Shortcut` table.
+pub const CREATE_SHORTCUTS: &str = "CreateShortcuts";
+
+/// Deletes services from the `ServiceControl` table.
+pub const DELETE_SERVICES: &str = "DeleteServices";
+
+/// Disables rollback for the rest of the installation.
+pub const DISABLE_ROLLBACK: &str = "DisableRollback";
+
+/// Duplicates installed files from the `DuplicateFile` table.
+pub const DUPLICATE_FILES: &str = "DuplicateFiles";
+
+/// Runs the execute sequence, on the server if possible.
+pub const EXECUTE_ACTION: &str = "ExecuteAction";
+
+/// Computes the disk space required by files.
+pub const FILE_COST: &str = "FileCost";
+
+/// Detects related products from the `Upgrade` table.
+pub const FIND_RELATED_PRODUCTS: &str = "FindRelatedProducts";
+
+/// Prompts the user to restart during the installation and resumes afterward.
+pub const FORCE_REBOOT: &str = "ForceReboot";
+
+/// Copies the package to an administrative installation point.
+pub const INSTALL_ADMIN_PACKAGE: &str = "InstallAdminPackage";
+
+/// Runs the installation script generated so far.
+pub const INSTALL_EXECUTE: &str = "InstallExecute";
+
+/// Runs the installation script generated so far a second time.
+pub const INSTALL_EXECUTE_AGAIN: &str = "InstallExecuteAgain";
+
+/// Copies files from the `File` table.
+pub const INSTALL_FILES: &str = "InstallFiles";
+
+/// Runs the installation script and ends the transaction.
+pub const INSTALL_FINALIZE: &str = "InstallFinalize";
+
+/// Starts the transaction and installation script.
+pub const INSTALL_INITIALIZE: &str = "InstallInitialize";
+
+/// Installs ODBC drivers, translators, and data sources.
+pub const INSTALL_ODBC: &str = "InstallODBC";
+
+/// Registers services from the `ServiceInstall` table.
+pub const INSTALL_SERVICES: &str = "InstallServices";
+
+/// Installs catalogs used by Windows File Protection on Windows Me.
+pub const INSTALL_SFP_CATALOG_FILE: &str = "InstallSFPCatalogFile";
+
+/// Verifies that enough disk space is available and files are not in use.
+pub const INSTALL_VALIDATE: &str = "InstallValidate";
+
+/// Installs private copies of shared components from the `IsolatedComponent` table.
+pub const ISOLATE_COMPONENTS: &str = "IsolateComponents";
+
+/// Evaluates conditions in the `LaunchCondition` table.
+pub const LAUNCH_CONDITIONS: &str = "LaunchConditions";
+
+/// Migrates the feature states of related products.
+pub const MIGRATE_FEATURE_STATES: &str = "MigrateFeatureStates";
+
+/// Moves or copies existing files from the `MoveFile` table.
+pub const MOVE_FILES: &str = "MoveFiles";
+
+/// Configures services from the `MsiServiceConfig` and `MsiServiceConfigFailureActions` tables.
+pub const MSI_CONFIGURE_SERVICES: &str = "MsiConfigureServices";
+
+/// Advertises assemblies from the `MsiAssembly` table.
+pub const MSI_PUBLISH_ASSEMBLIES: &str = "MsiPublishAssemblies";
+
+/// Unadvertises assemblies from the `MsiAssembly` table.
+pub const MSI_UNPUBLISH_ASSEMBLIES: &str = "MsiUnpublishAssemblies";
+
+/// Applies patches to installed files.
+pub const PATCH_FILES: &str = "PatchFiles";
+
+/// Registers and unregisters components.
+pub const PROCESS_COMPONENTS: &str = "ProcessComponents";
+
+/// Advertises qualified components from the `PublishComponent` table.
+pub const PUBLISH_COMPONENTS: &str = "PublishComponents";
+
+/// Advertises the installed states of features.
+pub const PUBLISH_FEATURES: &str = "PublishFeatures";
+
+/// Advertises the product.
+pub const PUBLISH_PRODUCT: &str = "PublishProduct";
+
+/// Registers COM classes from the `Class` table.
+pub const REGISTER_CLASS_INFO: &str = "RegisterClassInfo";
+
+/// Registers COM+ applications from the `Complus` table.
+pub const REGISTER_COM_PLUS: &str = "RegisterComPlus";
+
+/// Registers file extensions from the `Extension` table.
+pub const REGISTER_EXTENSION_INFO: &str = "RegisterExtensionInfo";
+
+/// Registers fonts from the `Font` table.
+pub const REGISTER_FONTS: &str = "RegisterFonts";
+
+/// Registers MIME types from the `MIME` table.
+pub const REGISTER_MIME_INFO: &str = "RegisterMIMEInfo";
+
+/// Registers the product and caches the package.
+pub const REGISTER_PRODUCT: &str = "RegisterProduct";
+
+/// Registers ProgIDs from the `ProgId` table.
+pub const REGISTER_PROG_ID_INFO: &str = "RegisterProgIdInfo";
+
+/// Registers type libraries from the `TypeLib` table.
+pub const REGISTER_TYPE_LIBRARIES: &str = "RegisterTypeLibraries";
+
+/// Registers user information.
+pub const REGISTER_USER: &str = "RegisterUser";
+
+/// Removes files duplicated by [`DUPLICATE_FILES`].
+pub const REMOVE_DUPLICATE_FILES: &str = "RemoveDuplicateFiles";
+
+/// Removes environment variables from the `Environment` table.
+pub const REMOVE_ENVIRONMENT_STRINGS: &str = "RemoveEnvironmentStrings";
+
+/// Uninstalls related products detected by [`FIND_RELATED_PRODUCTS`].
+pub const REMOVE_EXISTING_PRODUCTS: &str = "RemoveExistingProducts";
+
+/// Removes installed files and files from the `RemoveFile` table.
+pub const REMOVE_FILES: &str = "RemoveFiles";
+
+/// Removes empty folders from the `CreateFolder` table.
+pub const REMOVE_FOLDERS: &str = "RemoveFolders";
+
+/// Removes values from .ini files.
+pub const REMOVE_INI_VALUES: &str = "RemoveIniValues";
+
+/// Removes ODBC drivers, translators, and data sources.
+pub const REMOVE_ODBC: &str = "RemoveODBC";
+
+/// Removes registry values from the `Registry` and `RemoveRegistry` tables.
+pub const REMOVE_REGISTRY_VALUES: &str = "RemoveRegistryValues";
+
+/// Removes shortcuts from the `Shortcut` table.
+pub const REMOVE_SHORTCUTS: &str = "RemoveShortcuts";
+
+/// Determines the location of the source and sets `SourceDir`.
+pub const RESOLVE_SOURCE: &str = "ResolveSource";
+
+/// Searches for qualifying products in the `CCPSearch` table using file signatures.
+pub const RMCCP_SEARCH: &str = "RMCCPSearch";
+
+/// Prompts the user to restart after the installation completes.
+pub const SCHEDULE_REBOOT: &str = "ScheduleReboot";
+
+/// Registers modules from the `SelfReg` table.
+pub const SELF_REG_MODULES: &str = "SelfRegModules";
+
+/// Unregisters modules from the `SelfReg` table.
+pub const SELF_UNREG_MODULES: &str = "SelfUnregModules";
+
+/// Sets the target directories of ODBC drivers to those of existing drivers.
+pub const SET_ODBC_FOLDERS: &str = "SetODBCFolders";
+
+/// Starts services from the `ServiceControl` table.
+pub const START_SERVICES: &str = "StartServices";
+
+/// Stops services from the `ServiceControl` table.
+pub const STOP_SERVICES: &str = "StopServices";
+
+/// Unadvertises qualified components from the `PublishComponent` table.
+pub const UNPUBLISH_COMPONENTS: &str = "UnpublishComponents";
+
+/// Unadvertises features.
+pub const UNPUBLISH_FEATURES: &str = "UnpublishFeatures";
+
+/// Unregisters COM classes from the `Class` table.
+pub const UNREGISTER_CLASS_INFO: &str = "UnregisterClassInfo";
+
+/// Unregisters COM+ applications from the `Complus` table.
+pub const UNREGISTER_COM_PLUS: &str = "UnregisterComPlus";
+
+/// Unregisters file extensions from the `Extension` table.
+pub const UNREGISTER_EXTENSION_INFO: &str = "UnregisterExtensionInfo";
+
+/// Unregisters fonts from the `Font` table.
+pub const UNREGISTER_FONTS: &str = "UnregisterFonts";
+
+/// Unregisters MIME types from the `MIME` table.
+pub const UNREGISTER_MIME_INFO: &str = "UnregisterMIMEInfo";
+
+/// Unregisters ProgIDs from the `ProgId` table.
+pub const UNREGISTER_PROG_ID_INFO: &str = "UnregisterProgIdInfo";
+
+/// Unregisters type libraries from the `TypeLib` table.
+pub const UNREGISTER_TYPE_LIBRARIES: &str = "UnregisterTypeLibraries";
+
+/// Sets the `ProductID` property from the `PIDKEY` and `PIDTemplate` properties.
+pub const VALIDATE_PRODUCT_ID: &str = "ValidateProductID";
+
+/// Sets environment variables from the `Environment` table.
+pub const WRITE_ENVIRONMENT_STRINGS: &str = "WriteEnvironmentStrings";
+
+/// Writes values to .ini files from the `IniFile` table.
+pub const WRITE_INI_VALUES: &str = "WriteIniValues";
+
+/// Writes registry values from the `Registry` table.
+pub const WRITE_REGISTRY_VALUES: &str = "WriteRegistryValues";
+
+/// All standard actions.
+pub const ALL: &[&str] = &[
+    ALLOCATE_REGISTRY_SPACE,
+    APP_SEARCH,
+    BIND_IMAGE,
+    CCP_SEARCH,
+    COST_FINALIZE,
+    COST_INITIALIZE,
+    CREATE_FOLDERS,
+    CREATE_SHORTCUTS,
+    DELETE_SERVICES,
+    DISABLE_ROLLBACK,
+    DUPLICATE_FILES,
+    EXECUTE_ACTION,
+    FILE_COST,
+    FIND_RELATED_PRODUCTS,
+    FORCE_REBOOT,
+    INSTALL_ADMIN_PACKAGE,
+    INSTALL_EXECUTE,
+    INSTALL_EXECUTE_AGAIN,
+    INSTALL_FILES,
+    INSTALL_FINALIZE,
+    INSTALL_INITIALIZE,
+    INSTALL_ODBC,
+    INSTALL_SERVICES,
+    INSTALL_SFP_CATALOG_FILE,
+    INSTALL_VALIDATE,
+    ISOLATE_COMPONENTS,
+    LAUNCH_CONDITIONS,
+    MIGRATE_FEATURE_STATES,
+    MOVE_FILES,
+    MSI_CONFIGURE_SERVICES,
+    MSI_PUBLISH_ASSEMBLIES,
+    MSI_UNPUBLISH_ASSEMBLIES,
+    PATCH_FILES,
+    PROCESS_COMPONENTS,
+    PUBLISH_COMPONENTS,
+    PUBLISH_FEATURES,
+    PUBLISH_PRODUCT,
+    REGISTER_CLASS_INFO,
+    REGISTER_COM_PLUS,
+    REGISTER_EXTENSION_INFO,
+    REGISTER_FONTS,
+    REGISTER_MIME_INFO,
+    REGISTER_PRODUCT,
+    REGISTER_PROG_ID_INFO,
+    REGISTER_TYPE_LIBRARIES,
+    REGISTER_USER,
+    REMOVE_DUPLICATE_FILES,
+    REMOVE_ENVIRONMENT_STRINGS,
+    REMOVE_EXISTING_PRODUCTS,
+    REMOVE_FILES,
+    REMOVE_FOLDERS,
+    REMOVE_INI_VALUES,
+    REMOVE_ODBC,
+    REMOVE_REGISTRY_VALUES,
+    REMOVE_SHORTCUTS,
+    RESOLVE_SOURCE,
+    RMCCP_SEARCH,
+    SCHEDULE_REBOOT,
+    SELF_REG_MODULES,
+    SELF_UNREG_MODULES,
+    SET_ODBC_FOLDERS,
+    START_SERVICES,
+    STOP_SERVICES,
+    UNPUBLISH_COMPONENTS,
+    UNPUBLISH_FEATURES,
+    UNREGISTER_CLASS_INFO,
+    UNREGISTER_COM_PLUS,
+    UNREGISTER_EXTENSION_INFO,
+    UNREGISTER_FONTS,
+    UNREGISTER_MIME_INFO,
+    UNREGISTER_PROG_ID_INFO,
+    UNREGISTER_TYPE_LIBRARIES,
+    VALIDATE_PRODUCT_ID,
+    WRITE_ENVIRONMENT_STRINGS,
+    WRITE_INI_VALUES,
+    WRITE_REGISTRY_VALUES,
+];
+
+/// Top-level action that installs the product.
+pub const INSTALL: &str = "INSTALL";
+
+/// Top-level action that advertises the product.
+pub const ADVERTISE: &str = "ADVERTISE";
+
+/// Top-level action that creates an administrative installation point.
+pub const ADMIN: &str = "ADMIN";
+
+/// The execute sequence of an administrative installation.
+pub const ADMIN_EXECUTE_SEQUENCE: &str = "AdminExecuteSequence";
+
+/// The UI sequence of an administrative installation.
+pub const ADMIN_UI_SEQUENCE: &str = "AdminUISequence";
+
+/// The execute sequence when advertising.
+pub const ADVT_EXECUTE_SEQUENCE: &str = "AdvtExecuteSequence";
+
+/// The execute sequence of an installation.
+pub const INSTALL_EXECUTE_SEQUENCE: &str = "InstallExecuteSequence";
+
+/// The UI sequence of an installation.
+pub const INSTALL_UI_SEQUENCE: &str = "InstallUISequence";
+
+/// All sequence tables.
+pub const SEQUENCE_TABLES: &[&str] = &[
+    ADMIN_EXECUTE_SEQUENCE,
+    ADMIN_UI_SEQUENCE,
+    ADVT_EXECUTE_SEQUENCE,
+    INSTALL_EXECUTE_SEQUENCE,
+    INSTALL_UI_SEQUENCE,
+];
+
+/// Gets whether `name` is a standard action.
+///
+/// # Example
+///
+/// ```
+/// use msica::standard_actions;
+///
+/// assert!(standard_actions::is_standard_action("InstallFiles"));
+/// assert!(!standard_actions::is_standard_action("MyCustomAction"));
+/// ```
+pub fn is_standard_action(name: &str) -> bool {
+    ALL.contains(&name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_sorted() {
+        assert!(ALL
+            .windows(2)
+            .all(|w| w[0].to_lowercase() < w[1].to_lowercase()));
+        assert!(!is_standard_action(INSTALL));
+    }
+}
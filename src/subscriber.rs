@@ -0,0 +1,206 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(feature = "tracing")]
+use crate::{Field, MessageType, Record, Session, SyncSession};
+use std::fmt::{Debug, Write};
+use tracing_core::{
+    field::{self, Visit},
+    span, Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// A [`Layer`] that writes events and span open and close notifications to the installation log.
+///
+/// By default, all events are written as [`MessageType::Info`] so they only appear in the log.
+/// Call [`SessionLayer::with_error_message_type()`] or [`SessionLayer::with_warning_message_type()`]
+/// to process errors or warnings as other message types, which may display a message to the user.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::prelude::*;
+/// use msica::SessionLayer;
+/// use tracing_subscriber::prelude::*;
+/// const ERROR_SUCCESS: u32 = 0;
+///
+/// #[no_mangle]
+/// pub extern "C" fn MyCustomAction(session: Session) -> u32 {
+///     let subscriber = tracing_subscriber::registry().with(SessionLayer::new(&session));
+///     let _guard = tracing::subscriber::set_default(subscriber);
+///
+///     tracing::info!(product = "example", "installing");
+///     ERROR_SUCCESS
+/// }
+/// ```
+pub struct SessionLayer {
+    session: SyncSession,
+    error: MessageType,
+    warning: MessageType,
+}
+
+impl SessionLayer {
+    /// Creates a [`SessionLayer`] that writes to the installation log for the given [`Session`].
+    ///
+    /// The layer must not be used after the custom action returns.
+    pub fn new(session: &Session) -> Self {
+        Self {
+            session: SyncSession::new(session),
+            error: MessageType::Info,
+            warning: MessageType::Info,
+        }
+    }
+
+    /// Processes [`Level::ERROR`] events as the given [`MessageType`] e.g., [`MessageType::Error`].
+    pub fn with_error_message_type(self, kind: MessageType) -> Self {
+        Self {
+            error: kind,
+            ..self
+        }
+    }
+
+    /// Processes [`Level::WARN`] events as the given [`MessageType`] e.g., [`MessageType::Warning`].
+    pub fn with_warning_message_type(self, kind: MessageType) -> Self {
+        Self {
+            warning: kind,
+            ..self
+        }
+    }
+
+    fn message(&self, kind: MessageType, text: String) {
+        // Pass text as a field so that square brackets are not formatted.
+        if let Ok(record) = Record::with_fields(Some("[1]"), vec![Field::StringData(text)]) {
+            self.session.message(kind, &record);
+        }
+    }
+}
+
+impl<S> Layer<S> for SessionLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, _id: &span::Id, _ctx: Context<'_, S>) {
+        let mut visitor = Visitor::default();
+        attrs.record(&mut visitor);
+
+        let metadata = attrs.metadata();
+        let mut text = format!(
+            "{} {}: -> {}",
+            metadata.level(),
+            metadata.target(),
+            metadata.name()
+        );
+        visitor.write_to(&mut text);
+
+        self.message(MessageType::Info, text);
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = Visitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        let mut text = format!("{} {}:", metadata.level(), metadata.target());
+        visitor.write_to(&mut text);
+
+        let kind = match *metadata.level() {
+            Level::ERROR => self.error,
+            Level::WARN => self.warning,
+            _ => MessageType::Info,
+        };
+        self.message(kind, text);
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(&id) {
+            let metadata = span.metadata();
+            let text = format!(
+                "{} {}: <- {}",
+                metadata.level(),
+                metadata.target(),
+                metadata.name()
+            );
+            self.message(MessageType::Info, text);
+        }
+    }
+}
+
+#[derive(Default)]
+struct Visitor {
+    message: Option<String>,
+    fields: String,
+}
+
+impl Visitor {
+    fn write_to(&self, text: &mut String) {
+        if let Some(message) = &self.message {
+            let _ = write!(text, " {}", message);
+        }
+        text.push_str(&self.fields);
+    }
+}
+
+impl Visit for Visitor {
+    fn record_str(&mut self, field: &field::Field, value: &str) {
+        match field.name() {
+            "message" => self.message = Some(value.to_owned()),
+            name => {
+                let _ = write!(self.fields, " {}={:?}", name, value);
+            }
+        }
+    }
+
+    fn record_debug(&mut self, field: &field::Field, value: &dyn Debug) {
+        match field.name() {
+            "message" => self.message = Some(format!("{:?}", value)),
+            name => {
+                let _ = write!(self.fields, " {}={:?}", name, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_core::{callsite::Callsite, field::FieldSet, Kind, Metadata};
+
+    struct TestCallsite;
+    static CALLSITE: TestCallsite = TestCallsite;
+    static METADATA: Metadata<'static> = Metadata::new(
+        "test",
+        "msica",
+        Level::INFO,
+        None,
+        None,
+        None,
+        FieldSet::new(
+            &["message", "count"],
+            tracing_core::identify_callsite!(&CALLSITE),
+        ),
+        Kind::EVENT,
+    );
+
+    impl Callsite for TestCallsite {
+        fn set_interest(&self, _: tracing_core::Interest) {}
+        fn metadata(&self) -> &Metadata<'_> {
+            &METADATA
+        }
+    }
+
+    #[test]
+    fn visitor_writes_message_and_fields() {
+        let fields = METADATA.fields();
+        let message = fields.field("message").unwrap();
+        let count = fields.field("count").unwrap();
+
+        let mut visitor = Visitor::default();
+        visitor.record_debug(&message, &format_args!("installing"));
+        visitor.record_debug(&count, &2);
+        visitor.record_str(&count, "two");
+
+        let mut text = String::from("INFO msica:");
+        visitor.write_to(&mut text);
+        assert_eq!(text, r#"INFO msica: installing count=2 count="two""#);
+    }
+}
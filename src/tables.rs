@@ -0,0 +1,1030 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Typed rows of standard tables.
+//!
+//! Read all rows of a table with [`Database::rows()`] and insert rows with [`Database::insert_row()`],
+//! or convert between rows and [`Record`]s with [`FromRecord`] and [`ToRecord`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use msica::prelude::*;
+//! use msica::tables::FileRow;
+//!
+//! # fn example(session: &Session) -> Result<()> {
+//! for file in session.database().rows::<FileRow>()? {
+//!     println!("{} ({} bytes)", file.file_name, file.file_size);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    ComponentAttributes, ControlAttributes, CustomActionType, DialogAttributes, Error, ErrorKind,
+    FeatureAttributes, Field, FileAttributes, FromRecord, Record, Result, ToRecord,
+};
+use std::{fmt, str::FromStr};
+
+#[cfg(doc)]
+use crate::Database;
+
+pub use crate::{RegistryRow, RemoveRegistryRow};
+
+/// A row of a standard table.
+pub trait Row: FromRecord + ToRecord {
+    /// The name of the table.
+    const TABLE: &'static str;
+
+    /// The columns of the table in the order of the fields in a [`Record`].
+    const COLUMNS: &'static [&'static str];
+
+    /// Gets a query that selects all [`Row::COLUMNS`] from the [`Row::TABLE`].
+    fn query() -> String {
+        let columns = Self::COLUMNS
+            .iter()
+            .map(|column| format!("`{column}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("SELECT {} FROM `{}`", columns, Self::TABLE)
+    }
+}
+
+/// A row in the `File` table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FileRow {
+    /// The primary key.
+    pub file: String,
+    /// The component that installs the file.
+    pub component: String,
+    /// The file name, which may contain short and long names separated by `|`.
+    pub file_name: String,
+    /// The size of the file in bytes.
+    pub file_size: i32,
+    /// The version of a versioned file, or the `File` key of a companion file.
+    pub version: Option<String>,
+    /// A comma-separated list of languages.
+    pub language: Option<String>,
+    /// The file attributes.
+    pub attributes: FileAttributes,
+    /// The sequence of the file in the media.
+    pub sequence: i32,
+}
+
+impl Row for FileRow {
+    const TABLE: &'static str = "File";
+    const COLUMNS: &'static [&'static str] = &[
+        "File",
+        "Component_",
+        "FileName",
+        "FileSize",
+        "Version",
+        "Language",
+        "Attributes",
+        "Sequence",
+    ];
+}
+
+impl FromRecord for FileRow {
+    fn from_record(record: &Record) -> Result<Self> {
+        Ok(Self {
+            file: record.string_data(1)?,
+            component: record.string_data(2)?,
+            file_name: record.string_data(3)?,
+            file_size: integer(record, 4)?,
+            version: optional_string(record, 5)?,
+            language: optional_string(record, 6)?,
+            attributes: FileAttributes::from_bits_retain(
+                record.integer_data(7).unwrap_or_default() as u32,
+            ),
+            sequence: integer(record, 8)?,
+        })
+    }
+}
+
+impl ToRecord for FileRow {
+    fn to_record(&self) -> Result<Record> {
+        Record::with_fields(
+            None,
+            vec![
+                string_field(&self.file),
+                string_field(&self.component),
+                string_field(&self.file_name),
+                Field::IntegerData(self.file_size),
+                optional_string_field(self.version.as_deref()),
+                optional_string_field(self.language.as_deref()),
+                Field::IntegerData(self.attributes.bits() as i32),
+                Field::IntegerData(self.sequence),
+            ],
+        )
+    }
+}
+
+/// A row in the `Component` table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComponentRow {
+    /// The primary key.
+    pub component: String,
+    /// The component code GUID, or `None` if the component is not registered.
+    pub component_id: Option<String>,
+    /// The directory in which the component is installed.
+    pub directory: String,
+    /// The component attributes.
+    pub attributes: ComponentAttributes,
+    /// The condition that determines whether the component is installed.
+    pub condition: Option<String>,
+    /// The key of the resource that is the key path of the component, or `None` for the directory.
+    pub key_path: Option<String>,
+}
+
+impl Row for ComponentRow {
+    const TABLE: &'static str = "Component";
+    const COLUMNS: &'static [&'static str] = &[
+        "Component",
+        "ComponentId",
+        "Directory_",
+        "Attributes",
+        "Condition",
+        "KeyPath",
+    ];
+}
+
+impl FromRecord for ComponentRow {
+    fn from_record(record: &Record) -> Result<Self> {
+        Ok(Self {
+            component: record.string_data(1)?,
+            component_id: optional_string(record, 2)?,
+            directory: record.string_data(3)?,
+            attributes: ComponentAttributes::from_bits_retain(integer(record, 4)? as u32),
+            condition: optional_string(record, 5)?,
+            key_path: optional_string(record, 6)?,
+        })
+    }
+}
+
+impl ToRecord for ComponentRow {
+    fn to_record(&self) -> Result<Record> {
+        Record::with_fields(
+            None,
+            vec![
+                string_field(&self.component),
+                optional_string_field(self.component_id.as_deref()),
+                string_field(&self.directory),
+                Field::IntegerData(self.attributes.bits() as i32),
+                optional_string_field(self.condition.as_deref()),
+                optional_string_field(self.key_path.as_deref()),
+            ],
+        )
+    }
+}
+
+/// A row in the `Feature` table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeatureRow {
+    /// The primary key.
+    pub feature: String,
+    /// The parent feature, if any.
+    pub parent: Option<String>,
+    /// The localized title.
+    pub title: Option<String>,
+    /// The localized description.
+    pub description: Option<String>,
+    /// The display order and initial expansion state in the selection tree, or `None` to hide the feature.
+    pub display: Option<i32>,
+    /// The install level of the feature. Features with a level of 0 are disabled.
+    pub level: i32,
+    /// The directory that can be configured in the selection tree.
+    pub directory: Option<String>,
+    /// The feature attributes.
+    pub attributes: FeatureAttributes,
+}
+
+impl Row for FeatureRow {
+    const TABLE: &'static str = "Feature";
+    const COLUMNS: &'static [&'static str] = &[
+        "Feature",
+        "Feature_Parent",
+        "Title",
+        "Description",
+        "Display",
+        "Level",
+        "Directory_",
+        "Attributes",
+    ];
+}
+
+impl FromRecord for FeatureRow {
+    fn from_record(record: &Record) -> Result<Self> {
+        Ok(Self {
+            feature: record.string_data(1)?,
+            parent: optional_string(record, 2)?,
+            title: optional_string(record, 3)?,
+            description: optional_string(record, 4)?,
+            display: record.integer_data(5),
+            level: integer(record, 6)?,
+            directory: optional_string(record, 7)?,
+            attributes: FeatureAttributes::from_bits_retain(integer(record, 8)? as u32),
+        })
+    }
+}
+
+impl ToRecord for FeatureRow {
+    fn to_record(&self) -> Result<Record> {
+        Record::with_fields(
+            None,
+            vec![
+                string_field(&self.feature),
+                optional_string_field(self.parent.as_deref()),
+                optional_string_field(self.title.as_deref()),
+                optional_string_field(self.description.as_deref()),
+                self.display.map_or(Field::Null, Field::IntegerData),
+                Field::IntegerData(self.level),
+                optional_string_field(self.directory.as_deref()),
+                Field::IntegerData(self.attributes.bits() as i32),
+            ],
+        )
+    }
+}
+
+/// A row in the `Property` table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PropertyRow {
+    /// The name of the property.
+    pub property: String,
+    /// The default value of the property.
+    pub value: String,
+}
+
+impl Row for PropertyRow {
+    const TABLE: &'static str = "Property";
+    const COLUMNS: &'static [&'static str] = &["Property", "Value"];
+}
+
+impl FromRecord for PropertyRow {
+    fn from_record(record: &Record) -> Result<Self> {
+        Ok(Self {
+            property: record.string_data(1)?,
+            value: record.string_data(2)?,
+        })
+    }
+}
+
+impl ToRecord for PropertyRow {
+    fn to_record(&self) -> Result<Record> {
+        Record::with_fields(
+            None,
+            vec![string_field(&self.property), string_field(&self.value)],
+        )
+    }
+}
+
+/// A row in the `CustomAction` table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CustomActionRow {
+    /// The name of the action.
+    pub action: String,
+    /// The type of custom action.
+    pub kind: CustomActionType,
+    /// The source of the code e.g., a key in the `Binary` table, depending on the [`CustomActionType::source()`].
+    pub source: Option<String>,
+    /// The target e.g., the function to call in a DLL, depending on the [`CustomActionType::code()`].
+    pub target: Option<String>,
+}
+
+impl Row for CustomActionRow {
+    const TABLE: &'static str = "CustomAction";
+    const COLUMNS: &'static [&'static str] = &["Action", "Type", "Source", "Target"];
+}
+
+impl FromRecord for CustomActionRow {
+    fn from_record(record: &Record) -> Result<Self> {
+        Ok(Self {
+            action: record.string_data(1)?,
+            kind: CustomActionType::from_bits_retain(integer(record, 2)? as u32),
+            source: optional_string(record, 3)?,
+            target: optional_string(record, 4)?,
+        })
+    }
+}
+
+impl ToRecord for CustomActionRow {
+    fn to_record(&self) -> Result<Record> {
+        Record::with_fields(
+            None,
+            vec![
+                string_field(&self.action),
+                Field::IntegerData(self.kind.bits() as i32),
+                optional_string_field(self.source.as_deref()),
+                optional_string_field(self.target.as_deref()),
+            ],
+        )
+    }
+}
+
+/// A row in the `Directory` table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DirectoryRow {
+    /// The primary key, which is also the name of a property containing the directory path.
+    pub directory: String,
+    /// The parent directory, or `None` for a root directory.
+    pub parent: Option<String>,
+    /// The directory name, which may contain target and source names separated by `:`.
+    pub default_dir: String,
+}
+
+impl Row for DirectoryRow {
+    const TABLE: &'static str = "Directory";
+    const COLUMNS: &'static [&'static str] = &["Directory", "Directory_Parent", "DefaultDir"];
+}
+
+impl FromRecord for DirectoryRow {
+    fn from_record(record: &Record) -> Result<Self> {
+        Ok(Self {
+            directory: record.string_data(1)?,
+            parent: optional_string(record, 2)?,
+            default_dir: record.string_data(3)?,
+        })
+    }
+}
+
+impl ToRecord for DirectoryRow {
+    fn to_record(&self) -> Result<Record> {
+        Record::with_fields(
+            None,
+            vec![
+                string_field(&self.directory),
+                optional_string_field(self.parent.as_deref()),
+                string_field(&self.default_dir),
+            ],
+        )
+    }
+}
+
+/// A row in the `Dialog` table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DialogRow {
+    /// The primary key.
+    pub dialog: String,
+    /// The horizontal position of the dialog from 0 (left) to 100 (right) of the screen.
+    pub h_centering: i32,
+    /// The vertical position of the dialog from 0 (top) to 100 (bottom) of the screen.
+    pub v_centering: i32,
+    /// The width of the dialog in installer units.
+    pub width: i32,
+    /// The height of the dialog in installer units.
+    pub height: i32,
+    /// The dialog attributes.
+    pub attributes: DialogAttributes,
+    /// The formatted title of the dialog.
+    pub title: Option<String>,
+    /// The control that has the focus when the dialog is created.
+    pub control_first: String,
+    /// The control that is activated by the Enter key, if any.
+    pub control_default: Option<String>,
+    /// The control that is activated by the Esc key, if any.
+    pub control_cancel: Option<String>,
+}
+
+impl Row for DialogRow {
+    const TABLE: &'static str = "Dialog";
+    const COLUMNS: &'static [&'static str] = &[
+        "Dialog",
+        "HCentering",
+        "VCentering",
+        "Width",
+        "Height",
+        "Attributes",
+        "Title",
+        "Control_First",
+        "Control_Default",
+        "Control_Cancel",
+    ];
+}
+
+impl FromRecord for DialogRow {
+    fn from_record(record: &Record) -> Result<Self> {
+        Ok(Self {
+            dialog: record.string_data(1)?,
+            h_centering: integer(record, 2)?,
+            v_centering: integer(record, 3)?,
+            width: integer(record, 4)?,
+            height: integer(record, 5)?,
+            attributes: DialogAttributes::from_bits_retain(
+                record.integer_data(6).unwrap_or_default() as u32,
+            ),
+            title: optional_string(record, 7)?,
+            control_first: record.string_data(8)?,
+            control_default: optional_string(record, 9)?,
+            control_cancel: optional_string(record, 10)?,
+        })
+    }
+}
+
+impl ToRecord for DialogRow {
+    fn to_record(&self) -> Result<Record> {
+        Record::with_fields(
+            None,
+            vec![
+                string_field(&self.dialog),
+                Field::IntegerData(self.h_centering),
+                Field::IntegerData(self.v_centering),
+                Field::IntegerData(self.width),
+                Field::IntegerData(self.height),
+                Field::IntegerData(self.attributes.bits() as i32),
+                optional_string_field(self.title.as_deref()),
+                string_field(&self.control_first),
+                optional_string_field(self.control_default.as_deref()),
+                optional_string_field(self.control_cancel.as_deref()),
+            ],
+        )
+    }
+}
+
+/// A row in the `Control` table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ControlRow {
+    /// The dialog that contains the control.
+    pub dialog: String,
+    /// The name of the control, which is unique within the dialog.
+    pub control: String,
+    /// The type of control e.g., `PushButton` or `Text`.
+    pub kind: String,
+    /// The horizontal position of the control in installer units.
+    pub x: i32,
+    /// The vertical position of the control in installer units.
+    pub y: i32,
+    /// The width of the control in installer units.
+    pub width: i32,
+    /// The height of the control in installer units.
+    pub height: i32,
+    /// The control attributes.
+    pub attributes: ControlAttributes,
+    /// The property the control displays or sets, if any.
+    pub property: Option<String>,
+    /// The formatted text of the control.
+    pub text: Option<String>,
+    /// The next control in the tab order, if any.
+    pub control_next: Option<String>,
+    /// The tooltip and context-sensitive help separated by `|`.
+    pub help: Option<String>,
+}
+
+impl Row for ControlRow {
+    const TABLE: &'static str = "Control";
+    const COLUMNS: &'static [&'static str] = &[
+        "Dialog_",
+        "Control",
+        "Type",
+        "X",
+        "Y",
+        "Width",
+        "Height",
+        "Attributes",
+        "Property",
+        "Text",
+        "Control_Next",
+        "Help",
+    ];
+}
+
+impl FromRecord for ControlRow {
+    fn from_record(record: &Record) -> Result<Self> {
+        Ok(Self {
+            dialog: record.string_data(1)?,
+            control: record.string_data(2)?,
+            kind: record.string_data(3)?,
+            x: integer(record, 4)?,
+            y: integer(record, 5)?,
+            width: integer(record, 6)?,
+            height: integer(record, 7)?,
+            attributes: ControlAttributes::from_bits_retain(
+                record.integer_data(8).unwrap_or_default() as u32,
+            ),
+            property: optional_string(record, 9)?,
+            text: optional_string(record, 10)?,
+            control_next: optional_string(record, 11)?,
+            help: optional_string(record, 12)?,
+        })
+    }
+}
+
+impl ToRecord for ControlRow {
+    fn to_record(&self) -> Result<Record> {
+        Record::with_fields(
+            None,
+            vec![
+                string_field(&self.dialog),
+                string_field(&self.control),
+                string_field(&self.kind),
+                Field::IntegerData(self.x),
+                Field::IntegerData(self.y),
+                Field::IntegerData(self.width),
+                Field::IntegerData(self.height),
+                Field::IntegerData(self.attributes.bits() as i32),
+                optional_string_field(self.property.as_deref()),
+                optional_string_field(self.text.as_deref()),
+                optional_string_field(self.control_next.as_deref()),
+                optional_string_field(self.help.as_deref()),
+            ],
+        )
+    }
+}
+
+/// A row in the `ControlEvent` table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ControlEventRow {
+    /// The dialog that contains the control.
+    pub dialog: String,
+    /// The control that publishes the event.
+    pub control: String,
+    /// The event e.g., `NewDialog`, or a property to set written as `[Property]`.
+    pub event: String,
+    /// The formatted argument of the event.
+    pub argument: String,
+    /// The condition that determines whether the event is published.
+    pub condition: Option<String>,
+    /// The order in which events are published, if any.
+    pub ordering: Option<i32>,
+}
+
+impl Row for ControlEventRow {
+    const TABLE: &'static str = "ControlEvent";
+    const COLUMNS: &'static [&'static str] = &[
+        "Dialog_",
+        "Control_",
+        "Event",
+        "Argument",
+        "Condition",
+        "Ordering",
+    ];
+}
+
+impl FromRecord for ControlEventRow {
+    fn from_record(record: &Record) -> Result<Self> {
+        Ok(Self {
+            dialog: record.string_data(1)?,
+            control: record.string_data(2)?,
+            event: record.string_data(3)?,
+            argument: record.string_data(4)?,
+            condition: optional_string(record, 5)?,
+            ordering: record.integer_data(6),
+        })
+    }
+}
+
+impl ToRecord for ControlEventRow {
+    fn to_record(&self) -> Result<Record> {
+        Record::with_fields(
+            None,
+            vec![
+                string_field(&self.dialog),
+                string_field(&self.control),
+                string_field(&self.event),
+                string_field(&self.argument),
+                optional_string_field(self.condition.as_deref()),
+                self.ordering.map_or(Field::Null, Field::IntegerData),
+            ],
+        )
+    }
+}
+
+/// The `Action` column of the `ControlCondition` table.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ControlConditionAction {
+    /// Make the control the default control of the dialog.
+    Default,
+    /// Disable the control.
+    Disable,
+    /// Enable the control.
+    Enable,
+    /// Hide the control.
+    Hide,
+    /// Show the control.
+    Show,
+}
+
+impl ControlConditionAction {
+    const NAMES: [(Self, &'static str); 5] = [
+        (Self::Default, "Default"),
+        (Self::Disable, "Disable"),
+        (Self::Enable, "Enable"),
+        (Self::Hide, "Hide"),
+        (Self::Show, "Show"),
+    ];
+
+    /// Gets the name of the action as stored in the `ControlCondition` table.
+    pub fn as_str(&self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find_map(|(action, name)| (action == self).then_some(*name))
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for ControlConditionAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ControlConditionAction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::NAMES
+            .iter()
+            .find_map(|(action, name)| (*name == s).then_some(*action))
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::DataConversion,
+                    format!("invalid control condition action: {s}"),
+                )
+            })
+    }
+}
+
+/// A row in the `ControlCondition` table.
+///
+/// # Example
+///
+/// Hide a control for the rest of the installation from an immediate custom action:
+///
+/// ```no_run
+/// use msica::prelude::*;
+/// use msica::tables::{ControlConditionAction, ControlConditionRow};
+///
+/// # fn example(session: &Session) -> Result<()> {
+/// let row = ControlConditionRow {
+///     dialog: "InstallDirDlg".to_owned(),
+///     control: "ChangeFolder".to_owned(),
+///     action: ControlConditionAction::Hide,
+///     condition: "1".to_owned(),
+/// };
+/// session.database().insert_row(&row, ModifyMode::InsertTemporary)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ControlConditionRow {
+    /// The dialog that contains the control.
+    pub dialog: String,
+    /// The control to change.
+    pub control: String,
+    /// The action to take when the condition is true.
+    pub action: ControlConditionAction,
+    /// The condition that determines whether the action is taken.
+    pub condition: String,
+}
+
+impl Row for ControlConditionRow {
+    const TABLE: &'static str = "ControlCondition";
+    const COLUMNS: &'static [&'static str] = &["Dialog_", "Control_", "Action", "Condition"];
+}
+
+impl FromRecord for ControlConditionRow {
+    fn from_record(record: &Record) -> Result<Self> {
+        Ok(Self {
+            dialog: record.string_data(1)?,
+            control: record.string_data(2)?,
+            action: record.string_data(3)?.parse()?,
+            condition: record.string_data(4)?,
+        })
+    }
+}
+
+impl ToRecord for ControlConditionRow {
+    fn to_record(&self) -> Result<Record> {
+        Record::with_fields(
+            None,
+            vec![
+                string_field(&self.dialog),
+                string_field(&self.control),
+                string_field(self.action.as_str()),
+                string_field(&self.condition),
+            ],
+        )
+    }
+}
+
+/// Gets an integer from a non-nullable column.
+pub(crate) fn integer(record: &Record, field: u32) -> Result<i32> {
+    record
+        .integer_data(field)
+        .ok_or_else(|| Error::new(ErrorKind::DataConversion, format!("field {field} is null")))
+}
+
+/// Gets a string from a nullable column, or `None` if the string is empty.
+pub(crate) fn optional_string(record: &Record, field: u32) -> Result<Option<String>> {
+    let value = record.string_data(field)?;
+    Ok((!value.is_empty()).then_some(value))
+}
+
+pub(crate) fn string_field(value: &str) -> Field {
+    Field::StringData(value.to_owned())
+}
+
+pub(crate) fn optional_string_field(value: Option<&str>) -> Field {
+    value.map_or(Field::Null, string_field)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Converts a `row` to a [`Record`] and back, returning the record to check fields by column.
+    fn round_trip<R: Row + fmt::Debug + PartialEq>(row: &R) -> Record {
+        let record = row.to_record().unwrap();
+        assert_eq!(R::COLUMNS.len() as u32, record.field_count());
+        assert_eq!(*row, R::from_record(&record).unwrap());
+        record
+    }
+
+    /// Gets the 1-based field of a `column` in a [`Row`].
+    fn field<R: Row>(column: &str) -> u32 {
+        R::COLUMNS.iter().position(|c| *c == column).unwrap() as u32 + 1
+    }
+
+    #[test]
+    fn file_row() {
+        let mut row = FileRow {
+            file: "app.exe".to_owned(),
+            component: "App".to_owned(),
+            file_name: "app.exe".to_owned(),
+            file_size: 1024,
+            version: Some("1.2.3.4".to_owned()),
+            language: Some("1033".to_owned()),
+            attributes: FileAttributes::READ_ONLY | FileAttributes::HIDDEN,
+            sequence: 2,
+        };
+        let record = round_trip(&row);
+        assert_eq!(
+            Some(1024),
+            record.integer_data(field::<FileRow>("FileSize"))
+        );
+        assert_eq!(
+            "1.2.3.4",
+            record.string_data(field::<FileRow>("Version")).unwrap()
+        );
+        assert_eq!(
+            "1033",
+            record.string_data(field::<FileRow>("Language")).unwrap()
+        );
+        assert_eq!(Some(3), record.integer_data(field::<FileRow>("Attributes")));
+        assert_eq!(Some(2), record.integer_data(field::<FileRow>("Sequence")));
+
+        row.version = None;
+        row.language = None;
+        let record = round_trip(&row);
+        assert!(record.is_null(field::<FileRow>("Version")));
+        assert!(record.is_null(field::<FileRow>("Language")));
+
+        // Attributes are nullable and default to none.
+        record
+            .set_string_data(field::<FileRow>("Attributes"), None)
+            .unwrap();
+        assert!(FileRow::from_record(&record).unwrap().attributes.is_empty());
+    }
+
+    #[test]
+    fn component_row() {
+        let row = ComponentRow {
+            component: "App".to_owned(),
+            component_id: None,
+            directory: "INSTALLDIR".to_owned(),
+            attributes: ComponentAttributes::from_bits_retain(0x100),
+            condition: Some("VersionNT64".to_owned()),
+            key_path: Some("app.exe".to_owned()),
+        };
+        let record = round_trip(&row);
+        assert!(record.is_null(field::<ComponentRow>("ComponentId")));
+        assert_eq!(
+            "INSTALLDIR",
+            record
+                .string_data(field::<ComponentRow>("Directory_"))
+                .unwrap()
+        );
+        assert_eq!(
+            "app.exe",
+            record
+                .string_data(field::<ComponentRow>("KeyPath"))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn feature_row() {
+        let mut row = FeatureRow {
+            feature: "Complete".to_owned(),
+            parent: None,
+            title: Some("Complete".to_owned()),
+            description: Some("Everything".to_owned()),
+            display: Some(2),
+            level: 1,
+            directory: Some("INSTALLDIR".to_owned()),
+            attributes: FeatureAttributes::from_bits_retain(0x10),
+        };
+        let record = round_trip(&row);
+        assert_eq!(
+            "Everything",
+            record
+                .string_data(field::<FeatureRow>("Description"))
+                .unwrap()
+        );
+        assert_eq!(Some(2), record.integer_data(field::<FeatureRow>("Display")));
+        assert_eq!(Some(1), record.integer_data(field::<FeatureRow>("Level")));
+        assert_eq!(
+            "INSTALLDIR",
+            record
+                .string_data(field::<FeatureRow>("Directory_"))
+                .unwrap()
+        );
+
+        row.title = None;
+        row.description = None;
+        row.display = None;
+        row.directory = None;
+        let record = round_trip(&row);
+        for column in ["Title", "Description", "Display", "Directory_"] {
+            assert!(record.is_null(field::<FeatureRow>(column)), "{column}");
+        }
+    }
+
+    #[test]
+    fn custom_action_row() {
+        let row = CustomActionRow {
+            action: "SetInstallDir".to_owned(),
+            kind: CustomActionType::from_bits_retain(51),
+            source: Some("INSTALLDIR".to_owned()),
+            target: None,
+        };
+        let record = round_trip(&row);
+        assert_eq!(
+            Some(51),
+            record.integer_data(field::<CustomActionRow>("Type"))
+        );
+        assert!(record.is_null(field::<CustomActionRow>("Target")));
+    }
+
+    #[test]
+    fn directory_row() {
+        let row = DirectoryRow {
+            directory: "TARGETDIR".to_owned(),
+            parent: None,
+            default_dir: "SourceDir".to_owned(),
+        };
+        let record = round_trip(&row);
+        assert!(record.is_null(field::<DirectoryRow>("Directory_Parent")));
+    }
+
+    #[test]
+    fn dialog_row() {
+        let mut row = DialogRow {
+            dialog: "WelcomeDlg".to_owned(),
+            h_centering: 50,
+            v_centering: 40,
+            width: 370,
+            height: 270,
+            attributes: DialogAttributes::VISIBLE | DialogAttributes::MODAL,
+            title: Some("[ProductName] Setup".to_owned()),
+            control_first: "Next".to_owned(),
+            control_default: Some("Next".to_owned()),
+            control_cancel: Some("Cancel".to_owned()),
+        };
+        let record = round_trip(&row);
+        assert_eq!(
+            Some(40),
+            record.integer_data(field::<DialogRow>("VCentering"))
+        );
+        assert_eq!(Some(270), record.integer_data(field::<DialogRow>("Height")));
+        assert_eq!(
+            "Cancel",
+            record
+                .string_data(field::<DialogRow>("Control_Cancel"))
+                .unwrap()
+        );
+
+        row.title = None;
+        row.control_default = None;
+        row.control_cancel = None;
+        let record = round_trip(&row);
+        for column in ["Title", "Control_Default", "Control_Cancel"] {
+            assert!(record.is_null(field::<DialogRow>(column)), "{column}");
+        }
+    }
+
+    #[test]
+    fn control_row() {
+        let mut row = ControlRow {
+            dialog: "WelcomeDlg".to_owned(),
+            control: "Next".to_owned(),
+            kind: "PushButton".to_owned(),
+            x: 236,
+            y: 243,
+            width: 56,
+            height: 17,
+            attributes: ControlAttributes::VISIBLE | ControlAttributes::ENABLED,
+            property: Some("NEXT".to_owned()),
+            text: Some("&Next".to_owned()),
+            control_next: Some("Cancel".to_owned()),
+            help: Some("Next|Go to the next page".to_owned()),
+        };
+        let record = round_trip(&row);
+        assert_eq!(
+            "PushButton",
+            record.string_data(field::<ControlRow>("Type")).unwrap()
+        );
+        assert_eq!(Some(243), record.integer_data(field::<ControlRow>("Y")));
+        assert_eq!(
+            Some(3),
+            record.integer_data(field::<ControlRow>("Attributes"))
+        );
+        assert_eq!(
+            "Cancel",
+            record
+                .string_data(field::<ControlRow>("Control_Next"))
+                .unwrap()
+        );
+        assert_eq!(
+            "Next|Go to the next page",
+            record.string_data(field::<ControlRow>("Help")).unwrap()
+        );
+
+        row.property = None;
+        row.text = None;
+        row.control_next = None;
+        row.help = None;
+        let record = round_trip(&row);
+        for column in ["Property", "Text", "Control_Next", "Help"] {
+            assert!(record.is_null(field::<ControlRow>(column)), "{column}");
+        }
+    }
+
+    #[test]
+    fn control_event_row() {
+        let mut row = ControlEventRow {
+            dialog: "WelcomeDlg".to_owned(),
+            control: "Next".to_owned(),
+            event: "NewDialog".to_owned(),
+            argument: "InstallDirDlg".to_owned(),
+            condition: Some("1".to_owned()),
+            ordering: Some(2),
+        };
+        let record = round_trip(&row);
+        assert_eq!(
+            "InstallDirDlg",
+            record
+                .string_data(field::<ControlEventRow>("Argument"))
+                .unwrap()
+        );
+        assert_eq!(
+            Some(2),
+            record.integer_data(field::<ControlEventRow>("Ordering"))
+        );
+
+        row.condition = None;
+        row.ordering = None;
+        let record = round_trip(&row);
+        assert!(record.is_null(field::<ControlEventRow>("Condition")));
+        assert!(record.is_null(field::<ControlEventRow>("Ordering")));
+    }
+
+    #[test]
+    fn control_condition_row() {
+        let row = ControlConditionRow {
+            dialog: "InstallDirDlg".to_owned(),
+            control: "ChangeFolder".to_owned(),
+            action: ControlConditionAction::Hide,
+            condition: "1".to_owned(),
+        };
+        let record = round_trip(&row);
+        assert_eq!(
+            "Hide",
+            record
+                .string_data(field::<ControlConditionRow>("Action"))
+                .unwrap()
+        );
+
+        record
+            .set_string_data(field::<ControlConditionRow>("Action"), Some("hide"))
+            .unwrap();
+        assert!(ControlConditionRow::from_record(&record).is_err());
+    }
+
+    #[test]
+    fn query() {
+        assert_eq!(
+            "SELECT `Directory`, `Directory_Parent`, `DefaultDir` FROM `Directory`",
+            DirectoryRow::query()
+        );
+    }
+
+    #[test]
+    fn control_condition_action() {
+        assert_eq!(
+            ControlConditionAction::Hide,
+            "Hide".parse::<ControlConditionAction>().unwrap()
+        );
+        assert_eq!("Show", ControlConditionAction::Show.to_string());
+        assert!("hide".parse::<ControlConditionAction>().is_err());
+    }
+}
@@ -0,0 +1,43 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(feature = "test-util")]
+//! Utilities for tests to assert that handles are not leaked.
+//!
+//! Requires the `test-util` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use msica::{test_util, Record};
+//!
+//! let before = test_util::live_handles();
+//! {
+//!     let _record = Record::new(1);
+//!     assert_eq!(test_util::live_handles(), before + 1);
+//! }
+//! assert_eq!(test_util::live_handles(), before);
+//! ```
+
+use crate::ffi;
+
+/// Gets the count of handles opened by this crate on the current thread that are still open.
+///
+/// Handles are tracked per thread so that tests running in parallel do not affect each other.
+pub fn live_handles() -> usize {
+    crate::handles::live_handles()
+}
+
+/// Closes all Windows Installer handles opened on the current thread and returns how many were open.
+///
+/// This includes handles not opened by this crate.
+///
+/// # Safety
+///
+/// Any [`Record`](crate::Record), [`View`](crate::View), or [`Database`](crate::Database) still alive on the current thread
+/// will refer to a closed handle, which may be reused by a later call. Only call this at the end of a test.
+pub unsafe fn close_all_handles() -> u32 {
+    let count = ffi::MsiCloseAllHandles();
+    crate::handles::forget_thread();
+    count
+}
@@ -0,0 +1,627 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+#![cfg(feature = "test-util")]
+//! In-memory test doubles for a [`Session`](crate::Session) and [`Database`](crate::Database)
+//! to unit test custom action logic without running an installation.
+//!
+//! A [`MockDatabase`] stores tables in memory and supports a subset of MSI SQL:
+//!
+//! * `SELECT` with `*` or a list of columns, `WHERE` conditions joined with `AND`, and `ORDER BY`.
+//! * `INSERT INTO` with a list of columns and `VALUES`, optionally `TEMPORARY`.
+//! * `DELETE FROM` with `WHERE` conditions joined with `AND`.
+//!
+//! Conditions compare a column to a string, integer, `NULL`, or `?` parameter using
+//! `=`, `<>`, `<`, `>`, `<=`, or `>=`.
+//!
+//! The test doubles implement [`SessionOps`], [`DatabaseOps`], and [`ViewOps`], so custom action logic written
+//! against those traits can be tested with a [`MockSession`] and run with a [`Session`](crate::Session).
+//!
+//! To verify the rows a custom action inserted, compare a table to the expected rows with
+//! [`assert_table_eq!`](crate::assert_table_eq).
+//!
+//! Tables can be loaded from `.idt` files with [`MockDatabase::from_idt_dir()`], or imported into a real database
+//! with [`import_idt_dir()`].
+//!
+//! To test custom actions end to end, a [`TestPackage`] authors a minimal package that runs custom actions
+//! from your DLL and installs it with `msiexec`. To test your own package, run it with [`Msiexec`].
+//!
+//! To test how custom actions handle errors from Windows Installer, make calls fail with a [`FaultInjector`].
+//!
+//! Requires the `test-util` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use msica::prelude::*;
+//! use msica::testing::{MockDatabase, MockSession};
+//!
+//! let database = MockDatabase::new();
+//! database.create_table("Property", &["Property", "Value"]);
+//! database.insert("Property", vec![
+//!     Field::StringData("ProductName".to_owned()),
+//!     Field::StringData("Example".to_owned()),
+//! ])?;
+//!
+//! let session = MockSession::new()
+//!     .with_property("INSTALLDIR", r"C:\Example")
+//!     .with_mode(RunMode::Scheduled)
+//!     .with_database(database);
+//!
+//! let mut view = session.database().open_view("SELECT `Value` FROM `Property` WHERE `Property` = ?")?;
+//! view.execute(Some(Record::with_fields(None, vec![Field::StringData("ProductName".to_owned())])?))?;
+//! assert_eq!("Example", view.next().unwrap().string_data(1)?);
+//! assert!(session.mode(RunMode::Scheduled));
+//! # Ok::<(), msica::Error>(())
+//! ```
+
+mod fault;
+mod idt;
+mod msiexec;
+mod package;
+mod snapshot;
+mod sql;
+
+use crate::tables::Row;
+use crate::{
+    codes, DatabaseOps, Error, Field, MessageType, ModifyMode, Record, Result, RunMode, SessionOps,
+    ViewOps,
+};
+pub use fault::FaultInjector;
+pub use idt::import_idt_dir;
+pub use msiexec::{InstallOutcome, Msiexec, TestInstall};
+pub use package::TestPackage;
+pub use snapshot::{rows_snapshot, snapshot, table_snapshot, SnapshotValue};
+use sql::Statement;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    fmt::Display,
+    rc::Rc,
+};
+
+/// An in-memory double of a [`Session`](crate::Session) with properties, run modes, and a database,
+/// which captures messages and actions for later assertions.
+#[derive(Debug, Default)]
+pub struct MockSession {
+    properties: RefCell<HashMap<String, String>>,
+    modes: HashSet<RunMode>,
+    messages: RefCell<Vec<CapturedMessage>>,
+    actions: RefCell<Vec<String>>,
+    database: MockDatabase,
+}
+
+impl MockSession {
+    /// Creates a [`MockSession`] with no properties, run modes, or tables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a property.
+    pub fn with_property(self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.properties
+            .borrow_mut()
+            .insert(name.into(), value.into());
+        self
+    }
+
+    /// Sets a run mode returned from [`MockSession::mode()`].
+    pub fn with_mode(mut self, mode: RunMode) -> Self {
+        self.modes.insert(mode);
+        self
+    }
+
+    /// Sets the database returned from [`MockSession::database()`].
+    pub fn with_database(mut self, database: MockDatabase) -> Self {
+        self.database = database;
+        self
+    }
+
+    /// Gets the database, which shares its tables with all other clones.
+    pub fn database(&self) -> MockDatabase {
+        self.database.clone()
+    }
+
+    /// Captures the action, or `INSTALL` if `None`. Get captured actions with [`MockSession::actions()`].
+    pub fn do_action(&self, action: Option<&str>) -> Result<()> {
+        self.actions
+            .borrow_mut()
+            .push(action.unwrap_or("INSTALL").to_owned());
+        Ok(())
+    }
+
+    /// Sets the `custom_action_data` to a property named for the `action` and captures the action.
+    pub fn do_deferred_action(&self, action: &str, custom_action_data: impl Display) -> Result<()> {
+        self.set_property(action, Some(&custom_action_data.to_string()))?;
+        self.do_action(Some(action))
+    }
+
+    /// Captures the message and returns 0, as if no UI handled it. Get captured messages with [`MockSession::messages()`].
+    pub fn message(&self, kind: MessageType, record: &Record) -> i32 {
+        let message = CapturedMessage {
+            kind,
+            text: record.string_data(0).unwrap_or_default(),
+            fields: fields(record).unwrap_or_default(),
+        };
+        self.messages.borrow_mut().push(message);
+        0
+    }
+
+    /// Gets whether the run mode was set with [`MockSession::with_mode()`].
+    pub fn mode(&self, mode: RunMode) -> bool {
+        self.modes.contains(&mode)
+    }
+
+    /// Gets the value of the named property, or an empty string if undefined.
+    pub fn property(&self, name: &str) -> Result<String> {
+        Ok(self
+            .properties
+            .borrow()
+            .get(name)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// Sets the value of the named property. Pass `None` or an empty string to remove the property.
+    pub fn set_property(&self, name: &str, value: Option<&str>) -> Result<()> {
+        let mut properties = self.properties.borrow_mut();
+        match value {
+            Some(value) if !value.is_empty() => {
+                properties.insert(name.to_owned(), value.to_owned());
+            }
+            _ => {
+                properties.remove(name);
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the messages passed to [`MockSession::message()`] in the order they were processed.
+    pub fn messages(&self) -> Vec<CapturedMessage> {
+        self.messages.borrow().clone()
+    }
+
+    /// Gets the actions passed to [`MockSession::do_action()`] and [`MockSession::do_deferred_action()`] in the order they were run.
+    pub fn actions(&self) -> Vec<String> {
+        self.actions.borrow().clone()
+    }
+}
+
+/// A message captured by [`MockSession::message()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CapturedMessage {
+    /// The type of message.
+    pub kind: MessageType,
+    /// The template in field 0, or an empty string if not set.
+    pub text: String,
+    /// Fields 1 and greater. Fields that can be read as integers are [`Field::IntegerData`].
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug)]
+struct Table {
+    columns: Vec<String>,
+    rows: Vec<Vec<Field>>,
+}
+
+impl Table {
+    fn column(&self, name: &str) -> Result<usize> {
+        self.columns
+            .iter()
+            .position(|column| column == name)
+            .ok_or_else(|| Error::from_error_code(codes::ERROR_BAD_QUERY_SYNTAX))
+    }
+}
+
+/// An in-memory double of a [`Database`](crate::Database).
+///
+/// Cloning a [`MockDatabase`] is cheap and shares the same tables.
+#[derive(Clone, Debug, Default)]
+pub struct MockDatabase {
+    tables: Rc<RefCell<BTreeMap<String, Table>>>,
+}
+
+impl MockDatabase {
+    /// Creates a [`MockDatabase`] with no tables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty table with the given columns, replacing any existing table with the same name.
+    pub fn create_table(&self, table: &str, columns: &[&str]) {
+        self.tables.borrow_mut().insert(
+            table.to_owned(),
+            Table {
+                columns: columns.iter().map(|c| (*c).to_owned()).collect(),
+                rows: Vec::new(),
+            },
+        );
+    }
+
+    /// Inserts a row with a field for each column of the table.
+    ///
+    /// Returns an error if the table does not exist or the count of fields does not match the count of columns.
+    pub fn insert(&self, table: &str, fields: Vec<Field>) -> Result<()> {
+        let mut tables = self.tables.borrow_mut();
+        let table = tables
+            .get_mut(table)
+            .ok_or_else(|| Error::from_error_code(codes::ERROR_INVALID_TABLE))?;
+        if fields.len() != table.columns.len() {
+            return Err(Error::from_error_code(codes::ERROR_INVALID_FIELD));
+        }
+        table.rows.push(fields);
+        Ok(())
+    }
+
+    /// Prepares a query. Returns an error if the query is not supported, or references a table or column that does not exist.
+    pub fn open_view(&self, sql: &str) -> Result<MockView> {
+        let statement = sql::parse(sql)?;
+        {
+            let tables = self.tables.borrow();
+            let table = tables
+                .get(statement.table())
+                .ok_or_else(|| Error::from_error_code(codes::ERROR_BAD_QUERY_SYNTAX))?;
+            for column in statement.columns() {
+                table.column(column)?;
+            }
+        }
+
+        Ok(MockView {
+            database: self.clone(),
+            statement,
+            results: RefCell::new(VecDeque::new()),
+        })
+    }
+
+    /// Gets all rows of a standard table e.g., [`FileRow`](crate::tables::FileRow).
+    pub fn rows<T: Row>(&self) -> Result<Vec<T>> {
+        let view = self.open_view(&T::query())?;
+        view.execute(None)?;
+        view.map(|record| T::from_record(&record)).collect()
+    }
+
+    /// Inserts a row into a standard table, creating the table if it does not exist.
+    pub fn insert_row<T: Row>(&self, row: &T, mode: ModifyMode) -> Result<()> {
+        if !self.tables.borrow().contains_key(T::TABLE) {
+            self.create_table(T::TABLE, T::COLUMNS);
+        }
+
+        let view = self.open_view(&T::query())?;
+        view.execute(None)?;
+        view.modify(mode, &row.to_record()?)
+    }
+}
+
+/// A query prepared by [`MockDatabase::open_view()`].
+#[derive(Debug)]
+pub struct MockView {
+    database: MockDatabase,
+    statement: Statement,
+    results: RefCell<VecDeque<Vec<Field>>>,
+}
+
+impl MockView {
+    /// Executes the query, replacing each `?` with the corresponding field of the `record`.
+    ///
+    /// Fetch the rows selected by a `SELECT` query by iterating the [`MockView`].
+    pub fn execute(&self, record: Option<Record>) -> Result<()> {
+        let params = match record {
+            Some(record) => fields(&record)?,
+            None => Vec::new(),
+        };
+
+        let mut tables = self.database.tables.borrow_mut();
+        let table = tables
+            .get_mut(self.statement.table())
+            .ok_or_else(|| Error::from_error_code(codes::ERROR_INVALID_TABLE))?;
+
+        match &self.statement {
+            Statement::Select {
+                columns,
+                conditions,
+                order_by,
+                ..
+            } => {
+                let matches = matcher(table, conditions, &params)?;
+                let mut rows: Vec<&Vec<Field>> =
+                    table.rows.iter().filter(|row| matches(row)).collect();
+
+                for column in order_by.iter().rev() {
+                    let column = table.column(column)?;
+                    rows.sort_by(|a, b| sql::compare(&a[column], &b[column]));
+                }
+
+                let columns = match columns {
+                    Some(columns) => columns
+                        .iter()
+                        .map(|column| table.column(column))
+                        .collect::<Result<Vec<_>>>()?,
+                    None => (0..table.columns.len()).collect(),
+                };
+                *self.results.borrow_mut() = rows
+                    .into_iter()
+                    .map(|row| columns.iter().map(|i| row[*i].clone()).collect())
+                    .collect();
+            }
+            Statement::Insert {
+                columns, values, ..
+            } => {
+                let mut row = vec![Field::Null; table.columns.len()];
+                for (column, value) in columns.iter().zip(values) {
+                    row[table.column(column)?] = value.bind(&params).clone();
+                }
+                table.rows.push(row);
+            }
+            Statement::Delete { conditions, .. } => {
+                let matches = matcher(table, conditions, &params)?;
+                let rows = std::mem::take(&mut table.rows);
+                table.rows = rows.into_iter().filter(|row| !matches(row)).collect();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Inserts a record into the table of a `SELECT` query with the fields in the order of the selected columns.
+    ///
+    /// Only [`ModifyMode::Insert`] and [`ModifyMode::InsertTemporary`] are supported.
+    pub fn modify(&self, mode: ModifyMode, record: &Record) -> Result<()> {
+        let (ModifyMode::Insert | ModifyMode::InsertTemporary) = mode else {
+            return Err(Error::from_error_code(codes::ERROR_CALL_NOT_IMPLEMENTED));
+        };
+        let Statement::Select { columns, .. } = &self.statement else {
+            return Err(Error::from_error_code(codes::ERROR_FUNCTION_FAILED));
+        };
+
+        let mut tables = self.database.tables.borrow_mut();
+        let table = tables
+            .get_mut(self.statement.table())
+            .ok_or_else(|| Error::from_error_code(codes::ERROR_INVALID_TABLE))?;
+        let columns = match columns {
+            Some(columns) => columns
+                .iter()
+                .map(|column| table.column(column))
+                .collect::<Result<Vec<_>>>()?,
+            None => (0..table.columns.len()).collect(),
+        };
+
+        let mut row = vec![Field::Null; table.columns.len()];
+        for (column, field) in columns.into_iter().zip(fields(record)?) {
+            row[column] = field;
+        }
+        table.rows.push(row);
+        Ok(())
+    }
+}
+
+impl Iterator for MockView {
+    type Item = Record;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let fields = self.results.borrow_mut().pop_front()?;
+        Record::with_fields(None, fields).ok()
+    }
+}
+
+/// Gets a function that returns whether a row matches all conditions.
+fn matcher<'a>(
+    table: &Table,
+    conditions: &'a [sql::Condition],
+    params: &'a [Field],
+) -> Result<impl Fn(&[Field]) -> bool + 'a> {
+    let conditions = conditions
+        .iter()
+        .map(|condition| Ok((table.column(&condition.column)?, condition)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(move |row: &[Field]| {
+        conditions
+            .iter()
+            .all(|(column, condition)| condition.matches(&row[*column], params))
+    })
+}
+
+impl SessionOps for MockSession {
+    type Database = MockDatabase;
+
+    fn database(&self) -> MockDatabase {
+        MockSession::database(self)
+    }
+
+    fn do_action(&self, action: Option<&str>) -> Result<()> {
+        MockSession::do_action(self, action)
+    }
+
+    fn do_deferred_action(&self, action: &str, custom_action_data: impl Display) -> Result<()> {
+        MockSession::do_deferred_action(self, action, custom_action_data)
+    }
+
+    fn message(&self, kind: MessageType, record: &Record) -> i32 {
+        MockSession::message(self, kind, record)
+    }
+
+    fn mode(&self, mode: RunMode) -> bool {
+        MockSession::mode(self, mode)
+    }
+
+    fn property(&self, name: &str) -> Result<String> {
+        MockSession::property(self, name)
+    }
+
+    fn set_property(&self, name: &str, value: Option<&str>) -> Result<()> {
+        MockSession::set_property(self, name, value)
+    }
+}
+
+impl DatabaseOps for MockDatabase {
+    type View = MockView;
+
+    fn open_view(&self, sql: &str) -> Result<MockView> {
+        MockDatabase::open_view(self, sql)
+    }
+
+    fn insert_row<T: Row>(&self, row: &T, mode: ModifyMode) -> Result<()> {
+        MockDatabase::insert_row(self, row, mode)
+    }
+}
+
+impl ViewOps for MockView {
+    fn execute(&self, record: Option<Record>) -> Result<()> {
+        MockView::execute(self, record)
+    }
+
+    fn modify(&self, mode: ModifyMode, record: &Record) -> Result<()> {
+        MockView::modify(self, mode, record)
+    }
+}
+
+/// Reads fields 1 and greater from a record.
+fn fields(record: &Record) -> Result<Vec<Field>> {
+    (1..=record.field_count())
+        .map(|i| {
+            if record.is_null(i) {
+                return Ok(Field::Null);
+            }
+            match record.integer_data(i) {
+                Some(value) => Ok(Field::IntegerData(value)),
+                None => record.string_data(i).map(Field::StringData),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tables::PropertyRow;
+
+    fn database() -> MockDatabase {
+        let database = MockDatabase::new();
+        database.create_table("File", &["File", "Component_", "Sequence"]);
+        for (file, component, sequence) in [("c", "B", 3), ("a", "A", 1), ("b", "A", 2)] {
+            database
+                .insert(
+                    "File",
+                    vec![
+                        Field::StringData(file.to_owned()),
+                        Field::StringData(component.to_owned()),
+                        Field::IntegerData(sequence),
+                    ],
+                )
+                .unwrap();
+        }
+        database
+    }
+
+    fn strings(view: MockView) -> Vec<String> {
+        view.map(|record| record.string_data(1).unwrap()).collect()
+    }
+
+    #[test]
+    fn select() -> Result<()> {
+        let database = database();
+        let view = database.open_view(
+            "SELECT `File` FROM `File` WHERE `Component_` = ? AND `Sequence` > 0 ORDER BY `Sequence`",
+        )?;
+        view.execute(Some(Record::with_fields(
+            None,
+            vec![Field::StringData("A".to_owned())],
+        )?))?;
+        assert_eq!(vec!["a", "b"], strings(view));
+
+        assert!(database
+            .open_view("SELECT `Missing` FROM `File`")
+            .unwrap_err()
+            .is_bad_query_syntax());
+        assert!(database
+            .open_view("SELECT * FROM `Missing`")
+            .unwrap_err()
+            .is_bad_query_syntax());
+        Ok(())
+    }
+
+    #[test]
+    fn insert_delete() -> Result<()> {
+        let database = database();
+        database
+            .open_view("INSERT INTO `File` (`File`, `Sequence`) VALUES ('d', 4) TEMPORARY")?
+            .execute(None)?;
+        database
+            .open_view("DELETE FROM `File` WHERE `Component_` = 'A'")?
+            .execute(None)?;
+
+        let view = database.open_view("SELECT `File` FROM `File` ORDER BY `File`")?;
+        view.execute(None)?;
+        assert_eq!(vec!["c", "d"], strings(view));
+        Ok(())
+    }
+
+    #[test]
+    fn rows() -> Result<()> {
+        let database = MockDatabase::new();
+        let row = PropertyRow {
+            property: "NAME".to_owned(),
+            value: "value".to_owned(),
+        };
+        database.insert_row(&row, ModifyMode::InsertTemporary)?;
+        assert_eq!(vec![row], database.rows::<PropertyRow>()?);
+        Ok(())
+    }
+
+    fn schedule<S: SessionOps>(session: &S) -> Result<usize> {
+        let rows = session.database().rows::<PropertyRow>()?;
+        for row in &rows {
+            session.do_deferred_action("Deferred", &row.value)?;
+        }
+        Ok(rows.len())
+    }
+
+    #[test]
+    fn session_ops() -> Result<()> {
+        let session = MockSession::new();
+        session.database().insert_row(
+            &PropertyRow {
+                property: "NAME".to_owned(),
+                value: "value".to_owned(),
+            },
+            ModifyMode::InsertTemporary,
+        )?;
+        assert_eq!(1, schedule(&session)?);
+        assert_eq!("value", session.property("Deferred")?);
+        Ok(())
+    }
+
+    #[test]
+    fn session() -> Result<()> {
+        let session = MockSession::new()
+            .with_property("ALLUSERS", "1")
+            .with_mode(RunMode::Scheduled);
+        assert_eq!("1", session.property("ALLUSERS")?);
+        assert_eq!("", session.property("MISSING")?);
+        assert!(session.mode(RunMode::Scheduled));
+        assert!(!session.mode(RunMode::Rollback));
+
+        session.do_deferred_action("Deferred", "data")?;
+        assert_eq!("data", session.property("Deferred")?);
+        session.do_deferred_action("Deferred", 42)?;
+        assert_eq!("42", session.property("Deferred")?);
+        assert_eq!(vec!["Deferred", "Deferred"], session.actions());
+
+        session.set_property("ALLUSERS", None)?;
+        assert_eq!("", session.property("ALLUSERS")?);
+
+        let record = Record::with_fields(
+            Some("[1] [2]"),
+            vec![Field::StringData("text".to_owned()), Field::IntegerData(1)],
+        )?;
+        assert_eq!(0, session.message(MessageType::Info, &record));
+        assert_eq!(
+            vec![CapturedMessage {
+                kind: MessageType::Info,
+                text: "[1] [2]".to_owned(),
+                fields: vec![Field::StringData("text".to_owned()), Field::IntegerData(1)],
+            }],
+            session.messages()
+        );
+        Ok(())
+    }
+}
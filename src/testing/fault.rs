@@ -0,0 +1,121 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi;
+use std::marker::PhantomData;
+
+/// Makes calls into `msi.dll` on the current thread fail with chosen error codes, so error handling and rollback
+/// paths in custom actions can be tested without causing real failures.
+///
+/// Functions are named as documented e.g., `MsiViewExecute` or `MsiGetPropertyW`. Functions that return an error
+/// code return the chosen code without being called, and all other functions return an invalid value
+/// e.g., [`Record::new()`](crate::Record::new()) creates a record with a null handle.
+///
+/// Calls are counted and faults are injected only on the thread that created the [`FaultInjector`], and only
+/// until it is dropped. Test doubles like [`MockSession`](super::MockSession) do not call into `msi.dll`,
+/// so faults affect only a [`Session`](crate::Session), [`Database`](crate::Database), [`View`](crate::View),
+/// or [`Record`](crate::Record).
+///
+/// # Panics
+///
+/// Panics if another [`FaultInjector`] is already active on the current thread.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::prelude::*;
+/// use msica::testing::FaultInjector;
+/// const ERROR_BAD_QUERY_SYNTAX: u32 = 1615;
+///
+/// fn count_rows(session: &Session) -> Result<usize> {
+///     let mut count = 0;
+///     for _ in 0..3 {
+///         let view = session.database().open_view("SELECT * FROM `Property`")?;
+///         view.execute(None)?;
+///         count += view.count();
+///     }
+///     Ok(count)
+/// }
+///
+/// # fn example(session: &Session) -> Result<()> {
+/// let faults = FaultInjector::new().fail_call("MsiViewExecute", 3, ERROR_BAD_QUERY_SYNTAX);
+/// let err = count_rows(session).unwrap_err();
+/// assert_eq!(Some(ERROR_BAD_QUERY_SYNTAX), err.code());
+/// assert_eq!(3, faults.calls("MsiViewExecute"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FaultInjector {
+    // Faults are injected only on the thread that created the injector.
+    _thread: PhantomData<*const ()>,
+}
+
+impl FaultInjector {
+    /// Starts counting calls on the current thread.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        assert!(
+            ffi::fault::start(),
+            "a FaultInjector is already active on this thread"
+        );
+        Self {
+            _thread: PhantomData,
+        }
+    }
+
+    /// Fails every call to `function` with `code`.
+    pub fn fail(self, function: &str, code: u32) -> Self {
+        ffi::fault::add(function, None, code);
+        self
+    }
+
+    /// Fails only the `call`th call to `function`, counting from 1, with `code`.
+    pub fn fail_call(self, function: &str, call: usize, code: u32) -> Self {
+        ffi::fault::add(function, Some(call), code);
+        self
+    }
+
+    /// Gets the number of calls to `function`, including those that failed, since the [`FaultInjector`] was created.
+    pub fn calls(&self, function: &str) -> usize {
+        ffi::fault::calls(function)
+    }
+}
+
+impl Drop for FaultInjector {
+    fn drop(&mut self) {
+        ffi::fault::stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{codes, Record};
+
+    #[test]
+    fn fail_call() {
+        let faults = FaultInjector::new()
+            .fail_call("MsiRecordSetStringW", 2, codes::ERROR_INVALID_PARAMETER)
+            .fail("MsiRecordSetInteger", codes::ERROR_FUNCTION_FAILED);
+
+        let record = Record::new(1);
+        assert!(record.set_string_data(1, Some("first")).is_ok());
+        let err = record.set_string_data(1, Some("second")).unwrap_err();
+        assert_eq!(Some(codes::ERROR_INVALID_PARAMETER), err.code());
+        assert!(record.set_string_data(1, Some("third")).is_ok());
+        assert_eq!("third", record.string_data(1).unwrap());
+        assert!(record.set_integer_data(1, 1).is_err());
+        assert_eq!(3, faults.calls("MsiRecordSetString"));
+
+        drop(faults);
+        assert!(record.set_integer_data(1, 1).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "already active")]
+    fn nested() {
+        let _faults = FaultInjector::new();
+        let _ = FaultInjector::new();
+    }
+}
@@ -0,0 +1,202 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::{MockDatabase, Table};
+use crate::{Database, Error, ErrorKind, Field, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+impl MockDatabase {
+    /// Creates a [`MockDatabase`] with a table imported from each `.idt` file in `dir`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use msica::tables::CustomActionRow;
+    /// use msica::testing::MockDatabase;
+    ///
+    /// let database = MockDatabase::from_idt_dir("tests/fixtures")?;
+    /// let actions: Vec<CustomActionRow> = database.rows()?;
+    /// # Ok::<(), msica::Error>(())
+    /// ```
+    pub fn from_idt_dir(dir: impl AsRef<Path>) -> Result<Self> {
+        let database = Self::new();
+        for path in idt_files(dir.as_ref())? {
+            database.import_idt(path)?;
+        }
+        Ok(database)
+    }
+
+    /// Imports a table from an `.idt` file, replacing any existing table with the same name.
+    ///
+    /// Integer columns are imported as integers and all other columns as strings, including the file names of streams.
+    pub fn import_idt(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let text = fs::read(path)?;
+        let idt = parse(&String::from_utf8_lossy(&text)).map_err(|err| {
+            Error::new(
+                ErrorKind::DataConversion,
+                format!("{}: {err}", path.display()),
+            )
+        })?;
+
+        // The code page is not a table.
+        if idt.table == "_ForceCodepage" {
+            return Ok(());
+        }
+
+        self.tables.borrow_mut().insert(
+            idt.table,
+            Table {
+                columns: idt.columns,
+                rows: idt.rows,
+            },
+        );
+        Ok(())
+    }
+}
+
+/// Imports a table from each `.idt` file in `dir` into a `database` e.g., one created with [`Database::create()`].
+pub fn import_idt_dir(database: &Database, dir: impl AsRef<Path>) -> Result<()> {
+    for path in idt_files(dir.as_ref())? {
+        database.import(path)?;
+    }
+    Ok(())
+}
+
+/// Gets the `.idt` files in `dir` sorted by name, so tables are always imported in the same order.
+fn idt_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_idt = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("idt"));
+        if is_idt && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+#[derive(Debug)]
+struct Idt {
+    table: String,
+    columns: Vec<String>,
+    rows: Vec<Vec<Field>>,
+}
+
+/// Parses an `.idt` file: a line of column names, a line of column types, a line with the table name
+/// followed by its primary keys, then a line for each row. All values are separated by tabs.
+fn parse(text: &str) -> std::result::Result<Idt, String> {
+    let mut lines = text.lines();
+    let mut header = || lines.next().ok_or("missing header");
+    let columns: Vec<String> = header()?.split('\t').map(str::to_owned).collect();
+    let types: Vec<&str> = header()?.split('\t').collect();
+    if types.len() != columns.len() {
+        return Err("count of column types does not match count of columns".to_owned());
+    }
+    let keys: Vec<&str> = header()?.split('\t').collect();
+    let table = match keys.as_slice() {
+        // The code page is the first value of the table definition for the _ForceCodepage table.
+        [_, table] if *table == "_ForceCodepage" => table,
+        [table, ..] if !table.is_empty() => table,
+        _ => return Err("missing table name".to_owned()),
+    };
+
+    let mut rows = Vec::new();
+    for (i, line) in lines.enumerate().filter(|(_, line)| !line.is_empty()) {
+        let values: Vec<&str> = line.split('\t').collect();
+        if values.len() != columns.len() {
+            return Err(format!("wrong count of values in row {}", i + 1));
+        }
+
+        let row = values
+            .iter()
+            .zip(&types)
+            .map(
+                |(value, kind)| match (*value, kind.starts_with(['i', 'I'])) {
+                    ("", _) => Ok(Field::Null),
+                    (value, true) => value
+                        .parse()
+                        .map(Field::IntegerData)
+                        .map_err(|_| format!("invalid integer in row {}: {value}", i + 1)),
+                    (value, false) => Ok(Field::StringData(unescape(value))),
+                },
+            )
+            .collect::<std::result::Result<_, _>>()?;
+        rows.push(row);
+    }
+
+    Ok(Idt {
+        table: table.to_string(),
+        columns,
+        rows,
+    })
+}
+
+/// Replaces the characters an `.idt` file uses in place of carriage returns, line feeds, and tabs.
+fn unescape(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '\x11' => '\r',
+            '\x19' => '\n',
+            '\x15' => '\t',
+            c => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tables::CustomActionRow;
+    use crate::CustomActionType;
+
+    const CUSTOM_ACTION: &str = "Action\tType\tSource\tTarget\r\n\
+        s72\ti2\tS72\tS255\r\n\
+        CustomAction\tAction\r\n\
+        SetFoo\t51\tFOO\tline 1\x11\x19line 2\r\n\
+        Deferred\t1025\tBinary\t\r\n";
+
+    #[test]
+    fn parse_idt() {
+        let idt = parse(CUSTOM_ACTION).unwrap();
+        assert_eq!("CustomAction", idt.table);
+        assert_eq!(vec!["Action", "Type", "Source", "Target"], idt.columns);
+        assert_eq!(
+            vec![
+                Field::StringData("SetFoo".to_owned()),
+                Field::IntegerData(51),
+                Field::StringData("FOO".to_owned()),
+                Field::StringData("line 1\r\nline 2".to_owned()),
+            ],
+            idt.rows[0]
+        );
+        assert_eq!(Field::Null, idt.rows[1][3]);
+
+        assert!(parse("Action\r\ns72\r\n").is_err());
+        assert!(parse("Action\tType\r\ns72\r\nCustomAction\tAction\r\n").is_err());
+        assert!(parse("Type\r\ni2\r\nCustomAction\r\nx\r\n").is_err());
+    }
+
+    #[test]
+    fn from_idt_dir() {
+        let dir = std::env::temp_dir().join(format!("msica-idt-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("CustomAction.idt"), CUSTOM_ACTION).unwrap();
+        fs::write(dir.join("readme.txt"), "not a table").unwrap();
+
+        let database = MockDatabase::from_idt_dir(&dir);
+        let _ = fs::remove_dir_all(&dir);
+
+        let rows: Vec<CustomActionRow> = database.unwrap().rows().unwrap();
+        assert_eq!(2, rows.len());
+        assert_eq!("Deferred", rows[1].action);
+        assert_eq!(CustomActionType::from_bits_retain(1025), rows[1].kind);
+    }
+}
@@ -0,0 +1,260 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::logparse::{self, Failure, LogEntry, LogEvent};
+use crate::{codes, Result};
+use std::{
+    env, fs,
+    path::PathBuf,
+    process::{self, Command},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+static NEXT_TEMP: AtomicU32 = AtomicU32::new(0);
+
+/// Gets a unique path in the temporary directory for packages, logs, and other files created by tests.
+pub(super) fn temp_path(prefix: &str) -> PathBuf {
+    env::temp_dir().join(format!(
+        "{prefix}-{}-{}",
+        process::id(),
+        NEXT_TEMP.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+/// Runs `msiexec` quietly with verbose logging, waits for it to complete, and captures the exit code and log
+/// for integration tests to assert on both.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::testing::{InstallOutcome, Msiexec};
+///
+/// let install = Msiexec::install("example.msi")
+///     .property("INSTALLDIR", r"C:\Program Files\Example")
+///     .run()?;
+///
+/// assert_eq!(InstallOutcome::Success, install.outcome(), "{}", install.log());
+/// assert!(install.log().contains("Property(S): INSTALLDIR"));
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Msiexec {
+    option: &'static str,
+    package: PathBuf,
+    properties: Vec<(String, String)>,
+    log: Option<PathBuf>,
+}
+
+impl Msiexec {
+    /// Creates an [`Msiexec`] that installs the package at `path`.
+    pub fn install(path: impl Into<PathBuf>) -> Self {
+        Self::new("/i", path.into())
+    }
+
+    /// Creates an [`Msiexec`] that uninstalls the package at `path`, or the product with the given product code.
+    pub fn uninstall(path: impl Into<PathBuf>) -> Self {
+        Self::new("/x", path.into())
+    }
+
+    fn new(option: &'static str, package: PathBuf) -> Self {
+        Self {
+            option,
+            package,
+            properties: Vec::new(),
+            log: None,
+        }
+    }
+
+    /// Passes a property on the command line.
+    pub fn property(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.properties.push((name.into(), value.into()));
+        self
+    }
+
+    /// Passes properties on the command line.
+    pub fn properties<I, K, V>(mut self, properties: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.properties.extend(
+            properties
+                .into_iter()
+                .map(|(name, value)| (name.into(), value.into())),
+        );
+        self
+    }
+
+    /// Writes the log to `path` and keeps it after [`Msiexec::run()`] returns.
+    ///
+    /// By default the log is written to a temporary file that is removed after it is read.
+    pub fn log(mut self, path: impl Into<PathBuf>) -> Self {
+        self.log = Some(path.into());
+        self
+    }
+
+    /// Runs `msiexec` and waits for it to complete.
+    ///
+    /// Returns an error only if `msiexec` could not be started. Failures are reported by the returned [`TestInstall`].
+    pub fn run(self) -> Result<TestInstall> {
+        let (log, temporary) = match self.log {
+            Some(log) => (log, false),
+            None => (temp_path("msica-msiexec").with_extension("log"), true),
+        };
+
+        let mut command = Command::new("msiexec");
+        command
+            .arg(self.option)
+            .arg(&self.package)
+            .arg("/qn")
+            .arg("/l*v")
+            .arg(&log);
+        for (name, value) in &self.properties {
+            add_property(&mut command, name, value);
+        }
+        let status = command.status()?;
+
+        let install = TestInstall {
+            exit_code: status.code().unwrap_or(-1) as u32,
+            log: logparse::read(&log).unwrap_or_default(),
+        };
+        if temporary {
+            let _ = fs::remove_file(&log);
+        }
+
+        Ok(install)
+    }
+}
+
+/// Adds a property to the command line, quoting the value as `msiexec` expects.
+fn add_property(command: &mut Command, name: &str, value: &str) {
+    let property = format!("{name}=\"{}\"", value.replace('"', "\"\""));
+
+    // msiexec parses its own command line, so the argument must not be quoted again.
+    #[cfg(windows)]
+    std::os::windows::process::CommandExt::raw_arg(command, property);
+    #[cfg(not(windows))]
+    command.arg(property);
+}
+
+/// The outcome of running `msiexec`, interpreted from its exit code.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum InstallOutcome {
+    /// Completed successfully.
+    Success,
+    /// Completed successfully, but a reboot is required or was initiated.
+    RebootRequired,
+    /// The user or a custom action canceled the installation.
+    Cancel,
+    /// A fatal error occurred e.g., a custom action failed.
+    Failure,
+    /// `msiexec` exited with another error code e.g., `ERROR_INSTALL_ALREADY_RUNNING`.
+    Other(u32),
+}
+
+impl From<u32> for InstallOutcome {
+    fn from(exit_code: u32) -> Self {
+        match exit_code {
+            codes::ERROR_SUCCESS => Self::Success,
+            codes::ERROR_SUCCESS_REBOOT_REQUIRED | codes::ERROR_SUCCESS_REBOOT_INITIATED => {
+                Self::RebootRequired
+            }
+            codes::ERROR_INSTALL_USEREXIT => Self::Cancel,
+            codes::ERROR_INSTALL_FAILURE => Self::Failure,
+            code => Self::Other(code),
+        }
+    }
+}
+
+/// The result of running `msiexec` e.g., with [`Msiexec::run()`] or [`TestPackage::install()`](super::TestPackage::install()).
+#[derive(Clone, Debug)]
+pub struct TestInstall {
+    exit_code: u32,
+    log: String,
+}
+
+impl TestInstall {
+    /// Gets the exit code of `msiexec` e.g., `ERROR_INSTALL_FAILURE` if a custom action failed.
+    pub fn exit_code(&self) -> u32 {
+        self.exit_code
+    }
+
+    /// Gets the outcome of the installation from the exit code.
+    pub fn outcome(&self) -> InstallOutcome {
+        InstallOutcome::from(self.exit_code)
+    }
+
+    /// Gets whether the installation succeeded, even if a reboot is required.
+    pub fn succeeded(&self) -> bool {
+        matches!(
+            self.outcome(),
+            InstallOutcome::Success | InstallOutcome::RebootRequired
+        )
+    }
+
+    /// Gets the verbose log of the installation.
+    pub fn log(&self) -> &str {
+        &self.log
+    }
+
+    /// Gets the value logged when a sequenced action ended: 1 for success, 2 if canceled, 3 for failure, or 0 if skipped.
+    ///
+    /// Deferred custom actions are not logged separately, so get the return value of `InstallFinalize` instead.
+    pub fn return_value(&self, action: &str) -> Option<u32> {
+        self.events()
+            .filter_map(|entry| match entry.event {
+                LogEvent::ActionEnded {
+                    action: ended,
+                    return_value,
+                    ..
+                } if ended == action => Some(return_value),
+                _ => None,
+            })
+            .last()
+    }
+
+    /// Gets the events parsed from the log.
+    pub fn events(&self) -> impl Iterator<Item = LogEntry<'_>> {
+        logparse::parse(&self.log)
+    }
+
+    /// Gets the actions that failed and up to `max_lines` lines logged before each ended.
+    pub fn failures(&self, max_lines: usize) -> Vec<Failure<'_>> {
+        logparse::failures(&self.log, max_lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn return_value() {
+        let install = TestInstall {
+            exit_code: codes::ERROR_INSTALL_FAILURE,
+            log: [
+                "Action start 12:00:00: MyCustomAction.",
+                "Action ended 12:00:00: MyCustomAction. Return value 1.",
+                "Action ended 12:00:01: MyCustomActionFailed. Return value 3.",
+                "Action ended 12:00:02: INSTALL. Return value 3.",
+            ]
+            .join("\r\n"),
+        };
+        assert!(!install.succeeded());
+        assert_eq!(InstallOutcome::Failure, install.outcome());
+        assert_eq!(Some(1), install.return_value("MyCustomAction"));
+        assert_eq!(Some(3), install.return_value("MyCustomActionFailed"));
+        assert_eq!(None, install.return_value("Missing"));
+    }
+
+    #[test]
+    fn outcome() {
+        assert_eq!(InstallOutcome::Success, InstallOutcome::from(0));
+        assert_eq!(InstallOutcome::RebootRequired, InstallOutcome::from(3010));
+        assert_eq!(InstallOutcome::RebootRequired, InstallOutcome::from(1641));
+        assert_eq!(InstallOutcome::Cancel, InstallOutcome::from(1602));
+        assert_eq!(InstallOutcome::Other(1618), InstallOutcome::from(1618));
+    }
+}
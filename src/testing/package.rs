@@ -0,0 +1,283 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use super::msiexec::{temp_path, Msiexec, TestInstall};
+use crate::tables::{CustomActionRow, PropertyRow};
+use crate::{
+    ffi, CustomActionCode, CustomActionSource, CustomActionType, Database, Error, Field, Guid,
+    ModifyMode, Record, Result,
+};
+use std::{
+    collections::hash_map::RandomState,
+    fs,
+    hash::{BuildHasher, Hasher},
+    path::{Path, PathBuf},
+};
+
+/// The key in the `Binary` table for the custom action DLL.
+const BINARY: &str = "CustomActions";
+
+const SCHEMA: &[&str] = &[
+    "CREATE TABLE `Property` (`Property` CHAR(72) NOT NULL, `Value` LONGCHAR NOT NULL LOCALIZABLE PRIMARY KEY `Property`)",
+    "CREATE TABLE `Binary` (`Name` CHAR(72) NOT NULL, `Data` OBJECT NOT NULL PRIMARY KEY `Name`)",
+    "CREATE TABLE `CustomAction` (`Action` CHAR(72) NOT NULL, `Type` SHORT NOT NULL, `Source` CHAR(72), `Target` CHAR(255) PRIMARY KEY `Action`)",
+    "CREATE TABLE `InstallExecuteSequence` (`Action` CHAR(72) NOT NULL, `Condition` CHAR(255), `Sequence` SHORT PRIMARY KEY `Action`)",
+];
+
+/// Authors a minimal package that runs custom actions from a DLL, installs it with `msiexec` quietly,
+/// and reports the result and log for end-to-end tests of custom actions.
+///
+/// The package and log are written to a new temporary directory, which is also passed as `TARGETDIR`
+/// and is removed after the installation. The package does not register the product, so nothing needs to be uninstalled.
+///
+/// Immediate custom actions run in the order added. Deferred custom actions run in the order added between
+/// `InstallInitialize` and `InstallFinalize`, which are only sequenced if any deferred custom actions were added.
+///
+/// Add `msica` with the `test-util` feature to your `[dev-dependencies]`, then build your custom action DLL
+/// before running tests e.g., with `cargo build` in a build script or CI step.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::testing::TestPackage;
+///
+/// let install = TestPackage::new(r"target\debug\custom_actions.dll")
+///     .property("EXAMPLE", "1")
+///     .custom_action("MyCustomAction")
+///     .deferred_custom_action("MyDeferredCustomAction", "EXAMPLE=1")
+///     .install()?;
+///
+/// assert!(install.succeeded(), "{}", install.log());
+/// assert_eq!(Some(1), install.return_value("MyCustomAction"));
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct TestPackage {
+    dll: PathBuf,
+    properties: Vec<(String, String)>,
+    actions: Vec<TestAction>,
+}
+
+#[derive(Clone, Debug)]
+struct TestAction {
+    entry_point: String,
+    custom_action_data: Option<String>,
+}
+
+impl TestPackage {
+    /// Creates a [`TestPackage`] that runs custom actions exported from the DLL at `dll`.
+    pub fn new(dll: impl Into<PathBuf>) -> Self {
+        Self {
+            dll: dll.into(),
+            properties: Vec::new(),
+            actions: Vec::new(),
+        }
+    }
+
+    /// Authors a property in the `Property` table.
+    pub fn property(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.properties.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sequences an immediate custom action that calls `entry_point`, which is also the name of the action.
+    pub fn custom_action(mut self, entry_point: impl Into<String>) -> Self {
+        self.actions.push(TestAction {
+            entry_point: entry_point.into(),
+            custom_action_data: None,
+        });
+        self
+    }
+
+    /// Sequences a deferred custom action that calls `entry_point`, which is also the name of the action,
+    /// and authors a property of the same name so the action can get `custom_action_data`.
+    pub fn deferred_custom_action(
+        mut self,
+        entry_point: impl Into<String>,
+        custom_action_data: impl Into<String>,
+    ) -> Self {
+        self.actions.push(TestAction {
+            entry_point: entry_point.into(),
+            custom_action_data: Some(custom_action_data.into()),
+        });
+        self
+    }
+
+    /// Authors the package, installs it with `msiexec`, and waits for the installation to complete.
+    ///
+    /// Returns an error if the package could not be authored or `msiexec` could not be started.
+    /// Failures during the installation are reported by the returned [`TestInstall`].
+    pub fn install(self) -> Result<TestInstall> {
+        let dir = temp_path("msica-test");
+        fs::create_dir_all(&dir)?;
+
+        let result = self
+            .author(&dir)
+            .and_then(|package| Msiexec::install(package).log(dir.join("test.log")).run());
+        let _ = fs::remove_dir_all(&dir);
+        result
+    }
+
+    fn author(&self, dir: &Path) -> Result<PathBuf> {
+        let path = dir.join("test.msi");
+        let database = Database::create(&path)?;
+        for sql in SCHEMA {
+            database.open_view(sql)?.execute(None)?;
+        }
+
+        let mut properties = vec![
+            ("ProductCode".to_owned(), new_guid().to_string()),
+            ("ProductLanguage".to_owned(), "1033".to_owned()),
+            ("ProductName".to_owned(), "msica test".to_owned()),
+            ("ProductVersion".to_owned(), "1.0.0".to_owned()),
+            ("Manufacturer".to_owned(), "msica".to_owned()),
+            ("TARGETDIR".to_owned(), dir.to_string_lossy().into_owned()),
+        ];
+        properties.extend(self.properties.iter().cloned());
+        for action in &self.actions {
+            if let Some(data) = &action.custom_action_data {
+                properties.push((action.entry_point.clone(), data.clone()));
+            }
+        }
+        for (property, value) in properties {
+            database.insert_row(&PropertyRow { property, value }, ModifyMode::Insert)?;
+        }
+
+        let view = database.open_view("SELECT `Name`, `Data` FROM `Binary`")?;
+        view.execute(None)?;
+        let record = Record::new(2);
+        record.set_string_data(1, Some(BINARY))?;
+        record.set_stream_file(2, &self.dll)?;
+        view.modify(ModifyMode::Insert, &record)?;
+
+        let mut sequence = Vec::new();
+        let (immediate, deferred): (Vec<_>, Vec<_>) = self
+            .actions
+            .iter()
+            .partition(|action| action.custom_action_data.is_none());
+        sequence.extend(immediate.iter().map(|action| action.entry_point.as_str()));
+        if !deferred.is_empty() {
+            sequence.push("InstallInitialize");
+            sequence.extend(deferred.iter().map(|action| action.entry_point.as_str()));
+            sequence.push("InstallFinalize");
+        }
+
+        let kind = CustomActionType::new(CustomActionCode::Dll, CustomActionSource::BinaryData);
+        for action in &self.actions {
+            let row = CustomActionRow {
+                action: action.entry_point.clone(),
+                kind: match action.custom_action_data {
+                    Some(_) => kind.deferred(),
+                    None => kind,
+                },
+                source: Some(BINARY.to_owned()),
+                target: Some(action.entry_point.clone()),
+            };
+            database.insert_row(&row, ModifyMode::Insert)?;
+        }
+
+        for (i, action) in sequence.into_iter().enumerate() {
+            let view = database.open_view(
+                "INSERT INTO `InstallExecuteSequence` (`Action`, `Sequence`) VALUES (?, ?)",
+            )?;
+            view.execute(Some(Record::with_fields(
+                None,
+                vec![
+                    Field::StringData(action.to_owned()),
+                    Field::IntegerData(1000 + i as i32),
+                ],
+            )?))?;
+        }
+
+        set_summary_info(&database, &new_guid())?;
+        database.commit()?;
+
+        Ok(path)
+    }
+}
+
+/// Creates a random GUID for product and package codes.
+fn new_guid() -> Guid {
+    // Each RandomState is seeded differently, which is random enough for a throwaway package.
+    let random = || RandomState::new().build_hasher().finish();
+    let (a, b) = (random(), random());
+    let guid = format!(
+        "{:08X}-{:04X}-4{:03X}-{:04X}-{:012X}",
+        a >> 32,
+        (a >> 16) & 0xffff,
+        a & 0xfff,
+        0x8000 | ((b >> 48) & 0x3fff),
+        b & 0xffff_ffff_ffff,
+    );
+    Guid::parse(&guid).expect("valid GUID")
+}
+
+/// Sets the summary information required to install a package.
+fn set_summary_info(database: &Database, package_code: &Guid) -> Result<()> {
+    const PID_TEMPLATE: u32 = 7;
+    const PID_REVNUMBER: u32 = 9;
+    const PID_PAGECOUNT: u32 = 14;
+    const PID_WORDCOUNT: u32 = 15;
+    const VT_I4: u32 = 3;
+    const VT_LPSTR: u32 = 30;
+
+    let template = if cfg!(target_arch = "x86_64") {
+        "x64;1033"
+    } else if cfg!(target_arch = "aarch64") {
+        "Arm64;1033"
+    } else {
+        "Intel;1033"
+    };
+
+    unsafe {
+        let mut h = ffi::SummaryInfoHandle::null();
+        let ret = ffi::MsiGetSummaryInformation(
+            ffi::DatabaseHandle::from_raw(database.as_raw()),
+            std::ptr::null(),
+            4,
+            &mut h,
+        );
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+        let h = h.to_owned();
+
+        let set = |property: u32, kind: u32, i: i32, s: &str| -> Result<()> {
+            let s = ffi::to_wide(s)?;
+            let ret = ffi::MsiSummaryInfoSetProperty(
+                *h,
+                property,
+                kind,
+                i,
+                std::ptr::null_mut(),
+                s.as_ptr(),
+            );
+            if ret != ffi::ERROR_SUCCESS {
+                return Err(Error::from_last_error(ret));
+            }
+            Ok(())
+        };
+        set(PID_TEMPLATE, VT_LPSTR, 0, template)?;
+        set(PID_REVNUMBER, VT_LPSTR, 0, package_code)?;
+        set(PID_PAGECOUNT, VT_I4, 500, "")?;
+        // Short file names are not used, and elevation is not required.
+        set(PID_WORDCOUNT, VT_I4, 0x08, "")?;
+
+        let ret = ffi::MsiSummaryInfoPersist(*h);
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_last_error(ret));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_guid_unique() {
+        assert_ne!(new_guid(), new_guid());
+    }
+}
@@ -0,0 +1,201 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::{DatabaseOps, Field, Result, ViewOps};
+
+/// Serializes the records fetched from an executed view to canonical text for comparison in tests.
+///
+/// Each record is written on its own line with fields separated by tabs, and lines are sorted so the order in which rows
+/// were inserted does not matter. Integers are written as decimal strings and null fields as empty strings,
+/// which Windows Installer treats the same. Backslashes, tabs, carriage returns, and line feeds are escaped.
+pub fn snapshot<V: ViewOps>(view: V) -> Result<String> {
+    let rows = view
+        .map(|record| {
+            (1..=record.field_count())
+                .map(|i| record.string_data(i))
+                .collect::<Result<Vec<_>>>()
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(rows_snapshot(rows))
+}
+
+/// Serializes all rows of a `table` to canonical text as described in [`snapshot()`].
+pub fn table_snapshot<D: DatabaseOps>(database: &D, table: &str) -> Result<String> {
+    let view = database.open_view(&format!("SELECT * FROM `{table}`"))?;
+    view.execute(None)?;
+    snapshot(view)
+}
+
+/// Serializes expected rows to canonical text as described in [`snapshot()`].
+pub fn rows_snapshot<I, R, S>(rows: I) -> String
+where
+    I: IntoIterator<Item = R>,
+    R: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut lines: Vec<String> = rows
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|value| escape(value.as_ref()))
+                .collect::<Vec<_>>()
+                .join("\t")
+        })
+        .collect();
+    lines.sort();
+    lines.join("\n")
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str(r"\\"),
+            '\t' => escaped.push_str(r"\t"),
+            '\r' => escaped.push_str(r"\r"),
+            '\n' => escaped.push_str(r"\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A value in an expected row passed to [`assert_table_eq!`](crate::assert_table_eq).
+///
+/// Use `None` for null fields.
+pub trait SnapshotValue {
+    /// Gets the value as it is written in a [`snapshot()`].
+    fn snapshot_value(&self) -> String;
+}
+
+impl SnapshotValue for str {
+    fn snapshot_value(&self) -> String {
+        self.to_owned()
+    }
+}
+
+impl SnapshotValue for String {
+    fn snapshot_value(&self) -> String {
+        self.clone()
+    }
+}
+
+impl SnapshotValue for i32 {
+    fn snapshot_value(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl SnapshotValue for Field {
+    fn snapshot_value(&self) -> String {
+        match self {
+            Field::StringData(s) => s.clone(),
+            Field::IntegerData(i) => i.to_string(),
+            Field::Null => String::new(),
+        }
+    }
+}
+
+impl<T: SnapshotValue> SnapshotValue for Option<T> {
+    fn snapshot_value(&self) -> String {
+        self.as_ref().map(T::snapshot_value).unwrap_or_default()
+    }
+}
+
+impl<T: SnapshotValue + ?Sized> SnapshotValue for &T {
+    fn snapshot_value(&self) -> String {
+        (**self).snapshot_value()
+    }
+}
+
+/// Asserts that a table contains exactly the expected rows in any order.
+///
+/// The database may be any [`DatabaseOps`](crate::DatabaseOps) e.g., a [`MockDatabase`](crate::testing::MockDatabase)
+/// or the [`Database`](crate::Database) for a session. Each value may be a string, integer, [`Field`](crate::Field),
+/// or an [`Option`] of those where `None` is null. On failure, both tables are shown as described in
+/// [`snapshot()`](crate::testing::snapshot()).
+///
+/// Requires the `test-util` feature.
+///
+/// # Example
+///
+/// ```
+/// use msica::prelude::*;
+/// use msica::assert_table_eq;
+/// use msica::tables::PropertyRow;
+/// use msica::testing::MockDatabase;
+///
+/// let database = MockDatabase::new();
+/// database.insert_row(
+///     &PropertyRow { property: "INSTALLDIR".to_owned(), value: r"C:\Example".to_owned() },
+///     ModifyMode::InsertTemporary,
+/// )?;
+///
+/// assert_table_eq!(database, "Property", [["INSTALLDIR", r"C:\Example"]]);
+/// # Ok::<(), msica::Error>(())
+/// ```
+#[macro_export]
+macro_rules! assert_table_eq {
+    ($database:expr, $table:expr, [$([$($value:expr),* $(,)?]),* $(,)?] $(,)?) => {
+        match $crate::testing::table_snapshot(&$database, $table) {
+            Ok(actual) => {
+                let expected = $crate::testing::rows_snapshot([
+                    $(::std::vec![$($crate::testing::SnapshotValue::snapshot_value(&$value)),*]),*
+                ]);
+                assert_eq!(expected, actual, "rows in table `{}` do not match", $table);
+            }
+            Err(err) => panic!("failed to read table `{}`: {}", $table, err),
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockDatabase;
+
+    #[test]
+    fn table_snapshot_sorted() {
+        let database = MockDatabase::new();
+        database.create_table(
+            "InstallExecuteSequence",
+            &["Action", "Condition", "Sequence"],
+        );
+        for (action, condition, sequence) in
+            [("Second", None, 2), ("First", Some("NOT\tInstalled"), 1)]
+        {
+            database
+                .insert(
+                    "InstallExecuteSequence",
+                    vec![
+                        Field::StringData(action.to_owned()),
+                        condition.map_or(Field::Null, |c: &str| Field::StringData(c.to_owned())),
+                        Field::IntegerData(sequence),
+                    ],
+                )
+                .unwrap();
+        }
+
+        assert_eq!(
+            "First\tNOT\\tInstalled\t1\nSecond\t\t2",
+            table_snapshot(&database, "InstallExecuteSequence").unwrap()
+        );
+        assert_table_eq!(
+            database,
+            "InstallExecuteSequence",
+            [
+                ["Second", None::<&str>, 2],
+                ["First", Some("NOT\tInstalled"), 1]
+            ]
+        );
+        assert!(table_snapshot(&database, "Missing").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "rows in table `Property` do not match")]
+    fn assert_table_ne() {
+        let database = MockDatabase::new();
+        database.create_table("Property", &["Property", "Value"]);
+        assert_table_eq!(database, "Property", [["A", "1"]]);
+    }
+}
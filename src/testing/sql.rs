@@ -0,0 +1,505 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! A parser for the subset of MSI SQL supported by [`MockDatabase`](super::MockDatabase).
+
+use crate::{codes, Error, Field, Result};
+use std::cmp::Ordering;
+
+/// A parsed SQL statement.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Statement {
+    Select {
+        table: String,
+        /// The selected columns, or `None` for `*`.
+        columns: Option<Vec<String>>,
+        conditions: Vec<Condition>,
+        order_by: Vec<String>,
+    },
+    Insert {
+        table: String,
+        columns: Vec<String>,
+        values: Vec<Value>,
+    },
+    Delete {
+        table: String,
+        conditions: Vec<Condition>,
+    },
+}
+
+impl Statement {
+    /// Gets the table referenced by the statement.
+    pub(crate) fn table(&self) -> &str {
+        match self {
+            Self::Select { table, .. }
+            | Self::Insert { table, .. }
+            | Self::Delete { table, .. } => table,
+        }
+    }
+
+    /// Gets the columns referenced by the statement, excluding `*`.
+    pub(crate) fn columns(&self) -> impl Iterator<Item = &str> {
+        let (columns, conditions, order_by): (&[String], &[Condition], &[String]) = match self {
+            Self::Select {
+                columns,
+                conditions,
+                order_by,
+                ..
+            } => (columns.as_deref().unwrap_or_default(), conditions, order_by),
+            Self::Insert { columns, .. } => (columns, &[], &[]),
+            Self::Delete { conditions, .. } => (&[], conditions, &[]),
+        };
+        columns
+            .iter()
+            .chain(conditions.iter().map(|c| &c.column))
+            .chain(order_by)
+            .map(String::as_str)
+    }
+}
+
+/// A condition in a `WHERE` clause. Conditions are joined with `AND`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct Condition {
+    pub(crate) column: String,
+    pub(crate) op: Op,
+    pub(crate) value: Value,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+}
+
+/// A literal value or a parameter bound when a view is executed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Value {
+    Literal(Field),
+    /// The 0-based index of the parameter, which is bound from field `index + 1` of the parameter record.
+    Param(usize),
+}
+
+impl Value {
+    pub(crate) fn bind<'a>(&'a self, params: &'a [Field]) -> &'a Field {
+        match self {
+            Self::Literal(field) => field,
+            Self::Param(index) => params.get(*index).unwrap_or(&Field::Null),
+        }
+    }
+}
+
+impl Condition {
+    /// Evaluates the condition against a field value.
+    pub(crate) fn matches(&self, field: &Field, params: &[Field]) -> bool {
+        let value = self.value.bind(params);
+        let ordering = compare(field, value);
+        match self.op {
+            Op::Eq => ordering == Ordering::Equal,
+            Op::Ne => ordering != Ordering::Equal,
+            // Comparisons with null are false, as in the installer.
+            _ if is_null(field) || is_null(value) => false,
+            Op::Lt => ordering == Ordering::Less,
+            Op::Gt => ordering == Ordering::Greater,
+            Op::Le => ordering != Ordering::Greater,
+            Op::Ge => ordering != Ordering::Less,
+        }
+    }
+}
+
+/// Gets whether a field is null. Empty strings are null in the installer.
+pub(crate) fn is_null(field: &Field) -> bool {
+    match field {
+        Field::Null => true,
+        Field::StringData(s) => s.is_empty(),
+        Field::IntegerData(_) => false,
+    }
+}
+
+/// Compares fields numerically if both are integers, or as strings otherwise. Nulls sort first.
+pub(crate) fn compare(a: &Field, b: &Field) -> Ordering {
+    match (a, b) {
+        (Field::IntegerData(a), Field::IntegerData(b)) => a.cmp(b),
+        _ => match (is_null(a), is_null(b)) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => text(a).cmp(&text(b)),
+        },
+    }
+}
+
+fn text(field: &Field) -> String {
+    match field {
+        Field::StringData(s) => s.clone(),
+        Field::IntegerData(i) => i.to_string(),
+        Field::Null => String::new(),
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    Ident(String),
+    Keyword(String),
+    String(String),
+    Integer(i32),
+    Symbol(&'static str),
+}
+
+const KEYWORDS: &[&str] = &[
+    "AND",
+    "BY",
+    "DELETE",
+    "FROM",
+    "INSERT",
+    "INTO",
+    "NULL",
+    "ORDER",
+    "SELECT",
+    "TEMPORARY",
+    "VALUES",
+    "WHERE",
+];
+
+const SYMBOLS: &[&str] = &[
+    "<>", "<=", ">=", "=", "<", ">", ",", "(", ")", "*", "?", ".",
+];
+
+fn bad_query() -> Error {
+    Error::from_error_code(codes::ERROR_BAD_QUERY_SYNTAX)
+}
+
+fn tokenize(sql: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = sql.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        let len = if c == '`' || c == '\'' {
+            let end = rest[1..].find(c).ok_or_else(bad_query)? + 1;
+            let value = rest[1..end].to_owned();
+            tokens.push(match c {
+                '`' => Token::Ident(value),
+                _ => Token::String(value),
+            });
+            end + 1
+        } else if c.is_ascii_digit()
+            || (c == '-' && rest[1..].starts_with(|c: char| c.is_ascii_digit()))
+        {
+            let end = rest[1..]
+                .find(|c: char| !c.is_ascii_digit())
+                .map_or(rest.len(), |i| i + 1);
+            tokens.push(Token::Integer(
+                rest[..end].parse().map_err(|_| bad_query())?,
+            ));
+            end
+        } else if c.is_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let word = &rest[..end];
+            let upper = word.to_ascii_uppercase();
+            tokens.push(match KEYWORDS.contains(&upper.as_str()) {
+                true => Token::Keyword(upper),
+                false => Token::Ident(word.to_owned()),
+            });
+            end
+        } else {
+            let symbol = SYMBOLS
+                .iter()
+                .find(|s| rest.starts_with(**s))
+                .ok_or_else(bad_query)?;
+            tokens.push(Token::Symbol(symbol));
+            symbol.len()
+        };
+        rest = rest[len..].trim_start();
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    params: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self.peek().cloned().ok_or_else(bad_query)?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Keyword(k)) if k == keyword)
+    }
+
+    fn is_symbol(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<()> {
+        match self.next()? {
+            Token::Keyword(k) if k == keyword => Ok(()),
+            _ => Err(bad_query()),
+        }
+    }
+
+    fn symbol(&mut self, symbol: &str) -> Result<()> {
+        match self.next()? {
+            Token::Symbol(s) if s == symbol => Ok(()),
+            _ => Err(bad_query()),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.next()? {
+            Token::Ident(name) => Ok(name),
+            _ => Err(bad_query()),
+        }
+    }
+
+    /// Parses a column name, ignoring any table qualifier.
+    fn column(&mut self) -> Result<String> {
+        let mut name = self.ident()?;
+        if self.is_symbol(".") {
+            self.position += 1;
+            name = self.ident()?;
+        }
+        Ok(name)
+    }
+
+    fn list<T>(&mut self, mut f: impl FnMut(&mut Self) -> Result<T>) -> Result<Vec<T>> {
+        let mut items = vec![f(self)?];
+        while self.is_symbol(",") {
+            self.position += 1;
+            items.push(f(self)?);
+        }
+        Ok(items)
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        Ok(match self.next()? {
+            Token::String(s) => Value::Literal(Field::StringData(s)),
+            Token::Integer(i) => Value::Literal(Field::IntegerData(i)),
+            Token::Keyword(k) if k == "NULL" => Value::Literal(Field::Null),
+            Token::Symbol("?") => {
+                self.params += 1;
+                Value::Param(self.params - 1)
+            }
+            _ => return Err(bad_query()),
+        })
+    }
+
+    fn conditions(&mut self) -> Result<Vec<Condition>> {
+        if !self.is_keyword("WHERE") {
+            return Ok(Vec::new());
+        }
+        self.position += 1;
+
+        let mut conditions = Vec::new();
+        loop {
+            let column = self.column()?;
+            let op = match self.next()? {
+                Token::Symbol("=") => Op::Eq,
+                Token::Symbol("<>") => Op::Ne,
+                Token::Symbol("<") => Op::Lt,
+                Token::Symbol(">") => Op::Gt,
+                Token::Symbol("<=") => Op::Le,
+                Token::Symbol(">=") => Op::Ge,
+                _ => return Err(bad_query()),
+            };
+            let value = self.value()?;
+            conditions.push(Condition { column, op, value });
+
+            if !self.is_keyword("AND") {
+                return Ok(conditions);
+            }
+            self.position += 1;
+        }
+    }
+
+    fn select(&mut self) -> Result<Statement> {
+        self.keyword("SELECT")?;
+        let columns = match self.is_symbol("*") {
+            true => {
+                self.position += 1;
+                None
+            }
+            false => Some(self.list(Self::column)?),
+        };
+        self.keyword("FROM")?;
+        let table = self.ident()?;
+        let conditions = self.conditions()?;
+        let order_by = match self.is_keyword("ORDER") {
+            true => {
+                self.position += 1;
+                self.keyword("BY")?;
+                self.list(Self::column)?
+            }
+            false => Vec::new(),
+        };
+
+        Ok(Statement::Select {
+            table,
+            columns,
+            conditions,
+            order_by,
+        })
+    }
+
+    fn insert(&mut self) -> Result<Statement> {
+        self.keyword("INSERT")?;
+        self.keyword("INTO")?;
+        let table = self.ident()?;
+        self.symbol("(")?;
+        let columns = self.list(Self::column)?;
+        self.symbol(")")?;
+        self.keyword("VALUES")?;
+        self.symbol("(")?;
+        let values = self.list(Self::value)?;
+        self.symbol(")")?;
+        if self.is_keyword("TEMPORARY") {
+            self.position += 1;
+        }
+        if columns.len() != values.len() {
+            return Err(bad_query());
+        }
+
+        Ok(Statement::Insert {
+            table,
+            columns,
+            values,
+        })
+    }
+
+    fn delete(&mut self) -> Result<Statement> {
+        self.keyword("DELETE")?;
+        self.keyword("FROM")?;
+        let table = self.ident()?;
+        let conditions = self.conditions()?;
+
+        Ok(Statement::Delete { table, conditions })
+    }
+}
+
+/// Parses a `SELECT`, `INSERT`, or `DELETE` statement.
+pub(crate) fn parse(sql: &str) -> Result<Statement> {
+    let mut parser = Parser {
+        tokens: tokenize(sql)?,
+        position: 0,
+        params: 0,
+    };
+    let statement = match parser.peek() {
+        Some(Token::Keyword(k)) if k == "SELECT" => parser.select()?,
+        Some(Token::Keyword(k)) if k == "INSERT" => parser.insert()?,
+        Some(Token::Keyword(k)) if k == "DELETE" => parser.delete()?,
+        _ => return Err(bad_query()),
+    };
+    if parser.peek().is_some() {
+        return Err(bad_query());
+    }
+
+    Ok(statement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_select() {
+        let statement = parse(
+            "SELECT `File`.`File`, FileName FROM `File` WHERE `Component_` = ? AND Sequence >= 2 ORDER BY `Sequence`",
+        )
+        .unwrap();
+        assert_eq!(
+            Statement::Select {
+                table: "File".to_owned(),
+                columns: Some(vec!["File".to_owned(), "FileName".to_owned()]),
+                conditions: vec![
+                    Condition {
+                        column: "Component_".to_owned(),
+                        op: Op::Eq,
+                        value: Value::Param(0),
+                    },
+                    Condition {
+                        column: "Sequence".to_owned(),
+                        op: Op::Ge,
+                        value: Value::Literal(Field::IntegerData(2)),
+                    },
+                ],
+                order_by: vec!["Sequence".to_owned()],
+            },
+            statement
+        );
+    }
+
+    #[test]
+    fn parse_insert_delete() {
+        assert_eq!(
+            Statement::Insert {
+                table: "Property".to_owned(),
+                columns: vec!["Property".to_owned(), "Value".to_owned()],
+                values: vec![
+                    Value::Literal(Field::StringData("NAME".to_owned())),
+                    Value::Param(0)
+                ],
+            },
+            parse("INSERT INTO `Property` (`Property`, `Value`) VALUES ('NAME', ?) TEMPORARY")
+                .unwrap()
+        );
+        assert_eq!(
+            Statement::Delete {
+                table: "Property".to_owned(),
+                conditions: vec![Condition {
+                    column: "Value".to_owned(),
+                    op: Op::Ne,
+                    value: Value::Literal(Field::IntegerData(-1)),
+                }],
+            },
+            parse("delete from Property where Value <> -1").unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        for sql in [
+            "",
+            "SELECT FROM `File`",
+            "SELECT * FROM `File",
+            "SELECT * FROM `File` WHERE",
+            "INSERT INTO `File` (`File`) VALUES (1, 2)",
+            "UPDATE `File` SET `File` = 1",
+            "SELECT * FROM `File` extra",
+        ] {
+            assert!(parse(sql).unwrap_err().is_bad_query_syntax(), "{sql}");
+        }
+    }
+
+    #[test]
+    fn compare_fields() {
+        let condition = Condition {
+            column: "Value".to_owned(),
+            op: Op::Lt,
+            value: Value::Literal(Field::IntegerData(10)),
+        };
+        assert!(condition.matches(&Field::IntegerData(2), &[]));
+        assert!(!condition.matches(&Field::Null, &[]));
+        assert_eq!(
+            Ordering::Equal,
+            compare(&Field::Null, &Field::StringData(String::new()))
+        );
+        assert_eq!(
+            Ordering::Equal,
+            compare(&Field::IntegerData(1), &Field::StringData("1".to_owned()))
+        );
+    }
+}
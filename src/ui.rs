@@ -0,0 +1,351 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+use crate::ffi;
+use crate::{Error, ErrorKind, LogModes, MessageType, Record, Result, Session};
+use std::{
+    ffi::c_void,
+    panic::{self, AssertUnwindSafe},
+    ptr::NonNull,
+    sync::Mutex,
+};
+
+repr_enum! {
+    /// How much of its own user interface the installer shows.
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub enum InstallUiLevel: u32 {
+        /// Keep the current UI level.
+        NoChange = 0,
+        /// Use the default UI level.
+        Default = 1,
+        /// Show no UI.
+        None = 2,
+        /// Show only progress and errors.
+        Basic = 3,
+        /// Show authored dialogs but no wizard dialogs.
+        Reduced = 4,
+        /// Show all authored dialogs.
+        Full = 5,
+    }
+}
+
+flags! {
+    /// Modifiers combined with an [`InstallUiLevel`].
+    pub struct InstallUiModifiers: u32 {
+        /// Hide the cancel button in progress dialogs.
+        const HIDE_CANCEL = 0x20;
+        /// Show only the progress dialog, with [`InstallUiLevel::Basic`].
+        const PROGRESS_ONLY = 0x40;
+        /// Show a completion dialog at the end of the installation, with [`InstallUiLevel::None`] or [`InstallUiLevel::Basic`].
+        const END_DIALOG = 0x80;
+        /// Show only source resolution dialogs, with [`InstallUiLevel::None`].
+        const SOURCE_RESOLUTION_ONLY = 0x100;
+        /// Show only elevation prompts, with [`InstallUiLevel::None`].
+        const UAC_ONLY = 0x200;
+    }
+}
+
+/// Sets how much of its own user interface the installer shows for subsequent installations in the current process,
+/// and returns the previous UI level and modifiers.
+///
+/// Pass a `window` to own the installer's dialogs, or `None` to keep the current owner.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{set_internal_ui, InstallUiLevel, InstallUiModifiers};
+///
+/// let (previous, _) = set_internal_ui(InstallUiLevel::Basic, InstallUiModifiers::HIDE_CANCEL, None)?;
+/// println!("previous UI level: {previous:?}");
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub fn set_internal_ui(
+    level: InstallUiLevel,
+    modifiers: InstallUiModifiers,
+    window: Option<NonNull<c_void>>,
+) -> Result<(InstallUiLevel, InstallUiModifiers)> {
+    let mut window = window.map_or(std::ptr::null_mut(), NonNull::as_ptr);
+    let phwnd = match window.is_null() {
+        true => std::ptr::null_mut(),
+        false => &mut window as *mut *mut c_void,
+    };
+
+    let previous =
+        unsafe { ffi::MsiSetInternalUI((u32::from(level) | modifiers.bits()) as i32, phwnd) };
+    if previous == 0 {
+        // INSTALLUILEVEL_NOCHANGE is returned if the level was invalid.
+        return Err(Error::from_error_code(ffi::ERROR_INVALID_PARAMETER));
+    }
+
+    split(previous as u32)
+}
+
+repr_enum! {
+    /// The result returned by an [`ExternalUi`] handler.
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub enum MessageResult: i32 {
+        /// An error occurred while processing the message.
+        Error = -1,
+        /// The message was not processed, so the installer will process it.
+        None = 0,
+        /// The user chose OK.
+        Ok = 1,
+        /// The user chose Cancel.
+        Cancel = 2,
+        /// The user chose Abort.
+        Abort = 3,
+        /// The user chose Retry.
+        Retry = 4,
+        /// The user chose Ignore.
+        Ignore = 5,
+        /// The user chose Yes.
+        Yes = 6,
+        /// The user chose No.
+        No = 7,
+    }
+}
+
+type Handler = Mutex<Box<dyn FnMut(MessageType, &Record) -> MessageResult + Send>>;
+
+/// Receives messages from the installer for subsequent installations in the current process
+/// so that a frontend can show its own user interface.
+///
+/// Only one external UI handler is registered at a time. The previous handler is restored when the [`ExternalUi`]
+/// is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{ExternalUi, LogModes, MessageResult, MessageType};
+///
+/// let _ui = ExternalUi::new(
+///     LogModes::ACTION_START | LogModes::PROGRESS,
+///     |kind, record| {
+///         if kind == MessageType::ActionStart {
+///             println!("{}", record.string_data(2).unwrap_or_default());
+///         }
+///         MessageResult::None
+///     },
+/// )?;
+/// # Ok::<(), msica::Error>(())
+/// ```
+pub struct ExternalUi {
+    // Boxed so the address passed to the installer does not change.
+    _handler: Box<Handler>,
+    previous: ffi::INSTALLUI_HANDLER_RECORD,
+}
+
+impl ExternalUi {
+    /// Registers a `handler` for messages matching the `filter`.
+    ///
+    /// The [`Record`] passed to the handler is owned by the installer and is never closed by it or any clone,
+    /// but must not be used after the handler returns.
+    /// If the handler panics, [`MessageResult::Error`] is returned to the installer.
+    pub fn new<F>(filter: LogModes, handler: F) -> Result<Self>
+    where
+        F: FnMut(MessageType, &Record) -> MessageResult + Send + 'static,
+    {
+        let handler: Box<Handler> = Box::new(Mutex::new(Box::new(handler)));
+        let context = &*handler as *const Handler as *const c_void;
+        let mut previous: ffi::INSTALLUI_HANDLER_RECORD = None;
+
+        let ret = unsafe {
+            ffi::MsiSetExternalUIRecord(Some(record_handler), filter.bits(), context, &mut previous)
+        };
+        if ret != ffi::ERROR_SUCCESS {
+            return Err(Error::from_error_code(ret));
+        }
+
+        Ok(Self {
+            _handler: handler,
+            previous,
+        })
+    }
+}
+
+impl Drop for ExternalUi {
+    fn drop(&mut self) {
+        // A filter of 0 restores the previous handler with its own filter and context.
+        unsafe {
+            ffi::MsiSetExternalUIRecord(self.previous, 0, std::ptr::null(), std::ptr::null_mut());
+        }
+    }
+}
+
+unsafe extern "system" fn record_handler(context: *mut c_void, message_type: u32, h: u32) -> i32 {
+    let handler = &*(context as *const Handler);
+    let Ok(kind) = MessageType::try_from(message_type & 0xff00_0000) else {
+        return MessageResult::None.into();
+    };
+
+    // The installer owns the record, so never close it even if the handler keeps a clone.
+    let record = Record::from_borrowed(ffi::RecordHandle::from_raw(ffi::MSIHANDLE::from(h)));
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut handler = handler.lock().unwrap_or_else(|err| err.into_inner());
+        handler(kind, &record)
+    }))
+    .unwrap_or(MessageResult::Error)
+    .into()
+}
+
+type TextHandler = Mutex<Box<dyn FnMut(MessageType, &str) -> MessageResult + Send>>;
+
+/// Receives messages from the installer as formatted text for subsequent installations in the current process.
+///
+/// Unlike [`ExternalUi`], messages are formatted by the installer like they are written to the log.
+/// The previous handler is restored when the [`ExternalUiText`] is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use msica::{ExternalUiText, LogModes, MessageResult};
+///
+/// let _ui = ExternalUiText::new(LogModes::ERROR | LogModes::WARNING, |kind, message| {
+///     eprintln!("{kind:?}: {message}");
+///     MessageResult::None
+/// });
+/// ```
+pub struct ExternalUiText {
+    // Boxed so the address passed to the installer does not change.
+    _handler: Box<TextHandler>,
+    previous: ffi::INSTALLUI_HANDLERW,
+}
+
+impl ExternalUiText {
+    /// Registers a `handler` for messages matching the `filter`.
+    ///
+    /// If the handler panics, [`MessageResult::Error`] is returned to the installer.
+    pub fn new<F>(filter: LogModes, handler: F) -> Self
+    where
+        F: FnMut(MessageType, &str) -> MessageResult + Send + 'static,
+    {
+        let handler: Box<TextHandler> = Box::new(Mutex::new(Box::new(handler)));
+        let context = &*handler as *const TextHandler as *const c_void;
+
+        let previous = unsafe { ffi::MsiSetExternalUI(Some(text_handler), filter.bits(), context) };
+
+        Self {
+            _handler: handler,
+            previous,
+        }
+    }
+}
+
+impl Drop for ExternalUiText {
+    fn drop(&mut self) {
+        // A filter of 0 restores the previous handler with its own filter and context.
+        unsafe {
+            ffi::MsiSetExternalUI(self.previous, 0, std::ptr::null());
+        }
+    }
+}
+
+unsafe extern "system" fn text_handler(
+    context: *mut c_void,
+    message_type: u32,
+    message: ffi::LPCWSTR,
+) -> i32 {
+    let handler = &*(context as *const TextHandler);
+    let Ok(kind) = MessageType::try_from(message_type & 0xff00_0000) else {
+        return MessageResult::None.into();
+    };
+
+    let message = ffi::from_ptr_lossy(message);
+    panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut handler = handler.lock().unwrap_or_else(|err| err.into_inner());
+        handler(kind, &message)
+    }))
+    .unwrap_or(MessageResult::Error)
+    .into()
+}
+
+impl Session {
+    /// Gets the UI level and modifiers of the installation from the `UILevel` property.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use msica::prelude::*;
+    /// use msica::InstallUiLevel;
+    ///
+    /// # fn example(session: &Session) -> Result<()> {
+    /// let (level, _) = session.ui_level()?;
+    /// if level == InstallUiLevel::Full {
+    ///     // Dialogs can be shown.
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn ui_level(&self) -> Result<(InstallUiLevel, InstallUiModifiers)> {
+        let value = self.property("UILevel")?;
+        let value = value.parse::<u32>().map_err(|_| {
+            Error::new(
+                ErrorKind::DataConversion,
+                format!("invalid UILevel: {value}"),
+            )
+        })?;
+        split(value)
+    }
+}
+
+/// Splits a UI level e.g., from the `UILevel` property into an [`InstallUiLevel`] and [`InstallUiModifiers`].
+fn split(value: u32) -> Result<(InstallUiLevel, InstallUiModifiers)> {
+    let level = InstallUiLevel::try_from(value & 0x0f)?;
+    Ok((level, InstallUiModifiers::from_bits_retain(value & !0x0f)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_modifiers() {
+        let (level, modifiers) = split(0xe3).unwrap();
+        assert_eq!(InstallUiLevel::Basic, level);
+        assert_eq!(
+            InstallUiModifiers::HIDE_CANCEL
+                | InstallUiModifiers::PROGRESS_ONLY
+                | InstallUiModifiers::END_DIALOG,
+            modifiers
+        );
+        assert!(split(0x0f).is_err());
+    }
+
+    #[test]
+    fn message_result_try_from() {
+        assert_eq!(MessageResult::Cancel, MessageResult::try_from(2).unwrap());
+        assert_eq!(-1, i32::from(MessageResult::Error));
+        assert!(MessageResult::try_from(8).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "mock")]
+    fn record_handler_never_closes_record() {
+        use std::cell::RefCell;
+
+        thread_local! {
+            static KEPT: RefCell<Option<Record>> = const { RefCell::new(None) };
+        }
+
+        let installer = Record::new(1);
+        installer.set_string_data(1, Some("text")).unwrap();
+
+        let handler: Handler = Mutex::new(Box::new(|_, record: &Record| {
+            KEPT.set(Some(record.clone()));
+            MessageResult::Ok
+        }));
+        let ret = unsafe {
+            record_handler(
+                &handler as *const Handler as *mut c_void,
+                u32::from(MessageType::Info),
+                *installer.as_raw(),
+            )
+        };
+        assert_eq!(i32::from(MessageResult::Ok), ret);
+
+        let kept = KEPT.take().unwrap();
+        assert!(kept.clone().into_raw().is_err());
+        drop(kept);
+        assert_eq!("text", installer.string_data(1).unwrap());
+    }
+}
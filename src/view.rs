@@ -1,8 +1,9 @@
 // Copyright 2024 Heath Stewart.
 // Licensed under the MIT License. See LICENSE.txt in the project root for license information.
 
-use crate::ffi;
+use crate::{ffi, profiler};
 use crate::{Error, Record, Result};
+use std::mem::ManuallyDrop;
 
 #[cfg(doc)]
 use crate::Database;
@@ -15,7 +16,7 @@ use crate::Database;
 /// The query may be executed again, with different parameters if needed,
 /// but only after freeing the result set either by fetching all the records or by calling the [`View::close()`] method.
 pub struct View {
-    h: ffi::PMSIHANDLE,
+    h: ffi::PMSIHANDLE<ffi::kind::View>,
 }
 
 impl View {
@@ -28,6 +29,33 @@ impl View {
         }
     }
 
+    /// Returns a [`Record`] containing the names or definitions of the columns in the view.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use msica::prelude::*;
+    ///
+    /// # fn example(session: &Session) -> Result<()> {
+    /// let database = session.database();
+    /// let view = database.open_view("SELECT `Property`, `Value` FROM `Property`")?;
+    /// let names = view.column_info(ColumnInfo::Names)?;
+    /// assert_eq!(names.string_data(1)?, "Property");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn column_info(&self, kind: ColumnInfo) -> Result<Record> {
+        unsafe {
+            let mut h = ffi::RecordHandle::null();
+            let ret = ffi::MsiViewGetColumnInfo(*self.h, kind, &mut h);
+            if ret != ffi::ERROR_SUCCESS {
+                return Err(Error::from_error_code(ret));
+            }
+
+            Ok(Record::from_handle(h))
+        }
+    }
+
     /// The `execute` method uses the question mark token to represent parameters in an SQL statement.
     /// For more information, see [SQL syntax](https://docs.microsoft.com/windows/win32/msi/sql-syntax).
     ///
@@ -38,14 +66,15 @@ impl View {
         unsafe {
             let h = match record {
                 Some(r) => *r.h,
-                None => ffi::MSIHANDLE::null(),
+                None => ffi::RecordHandle::null(),
             };
 
-            let ret = ffi::MsiViewExecute(*self.h, h);
+            let ret = profiler::measure(
+                || "View::execute".into(),
+                || ffi::MsiViewExecute(*self.h, h),
+            );
             if ret != ffi::ERROR_SUCCESS {
-                return Err(
-                    Error::from_last_error_record().unwrap_or_else(|| Error::from_error_code(ret))
-                );
+                return Err(Error::from_last_error(ret));
             }
 
             Ok(())
@@ -66,16 +95,42 @@ impl View {
         unsafe {
             let ret = ffi::MsiViewModify(*self.h, mode, *record.h);
             if ret != ffi::ERROR_SUCCESS {
-                return Err(
-                    Error::from_last_error_record().unwrap_or_else(|| Error::from_error_code(ret))
-                );
+                return Err(Error::from_last_error(ret));
             }
 
             Ok(())
         }
     }
 
-    pub(crate) fn from_handle(h: ffi::MSIHANDLE) -> Self {
+    /// Creates a [`View`] from a raw handle, taking ownership of it.
+    ///
+    /// The handle is closed when the [`View`] is dropped.
+    ///
+    /// # Safety
+    ///
+    /// The handle must be a valid, open view handle that is not closed elsewhere.
+    #[track_caller]
+    pub unsafe fn from_raw(h: ffi::MSIHANDLE) -> Self {
+        Self::from_handle(ffi::ViewHandle::from_raw(h))
+    }
+
+    /// Consumes the [`View`] and returns the raw handle without closing it.
+    ///
+    /// The caller is responsible for closing the handle e.g., by passing it to [`View::from_raw()`].
+    pub fn into_raw(self) -> ffi::MSIHANDLE {
+        let view = ManuallyDrop::new(self);
+        // Safety: `view` is never dropped, so the handle is only moved out once.
+        let h = unsafe { std::ptr::read(&view.h) };
+        h.into_raw()
+    }
+
+    /// Gets the raw handle without transferring ownership.
+    pub fn as_raw(&self) -> ffi::MSIHANDLE {
+        **self.h
+    }
+
+    #[track_caller]
+    pub(crate) fn from_handle(h: ffi::ViewHandle) -> Self {
         View { h: h.to_owned() }
     }
 }
@@ -91,7 +146,7 @@ impl Iterator for View {
 
     fn next(&mut self) -> Option<Self::Item> {
         unsafe {
-            let mut h = ffi::MSIHANDLE::null();
+            let mut h = ffi::RecordHandle::null();
             ffi::MsiViewFetch(*self.h, &mut h);
 
             if h.is_null() {
@@ -103,45 +158,61 @@ impl Iterator for View {
     }
 }
 
-/// Modify modes passed to [`View::modify()`].
-#[repr(u32)]
-pub enum ModifyMode {
-    /// Refreshes the information in the supplied record without changing the position in the result set and without affecting subsequent fetch operations.
-    /// The record may then be used for subsequent Update, Delete, and Refresh. All primary key columns of the table must be in the query and the record must have at least as many fields as the query.
-    /// Seek cannot be used with multi-table queries. This mode cannot be used with a view containing joins.
-    Seek = u32::MAX,
-
-    /// Refreshes the information in the record. Must first call [`View::next()`] with the same record.
-    /// Fails for a deleted row. Works with read-write and read-only records.
-    Refresh = 0,
-
-    /// Inserts a record. Fails if a row with the same primary keys exists. Fails with a read-only database.
-    /// This mode cannot be used with a view containing joins.
-    Insert = 1,
-
-    /// Updates an existing record. Non-primary keys only. Must first call [`View::next()`].
-    /// Fails with a deleted record. Works only with read-write records.
-    Update = 2,
-
-    /// Writes current data in the cursor to a table row. Updates record if the primary keys match an existing row and inserts if they do not match.
-    /// Fails with a read-only database. This mode cannot be used with a view containing joins.
-    Assign = 3,
-
-    /// Updates or deletes and inserts a record into a table. Must first call [`View::next()`] with the same record.
-    /// Updates record if the primary keys are unchanged. Deletes old row and inserts new if primary keys have changed. Fails with a read-only database.
-    /// This mode cannot be used with a view containing joins.
-    Replace = 4,
-
-    /// Inserts or validates a record in a table. Inserts if primary keys do not match any row and validates if there is a match.
-    /// Fails if the record does not match the data in the table. Fails if there is a record with a duplicate key that is not identical. Works only with read-write records.
-    /// This mode cannot be used with a view containing joins.
-    Merge = 5,
-
-    /// Remove a row from the table. You must first call the [`View::next()`] function with the same record.
-    /// Fails if the row has been deleted. Works only with read-write records. This mode cannot be used with a view containing joins.
-    Delete = 6,
-
-    /// Inserts a temporary record. The information is not persistent. Fails if a row with the same primary key exists.
-    /// Works only with read-write records. This mode cannot be used with a view containing joins.
-    InsertTemporary = 7,
+repr_enum! {
+    /// Modify modes passed to [`View::modify()`].
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub enum ModifyMode: u32 {
+        /// Refreshes the information in the supplied record without changing the position in the result set and without affecting subsequent fetch operations.
+        /// The record may then be used for subsequent Update, Delete, and Refresh. All primary key columns of the table must be in the query and the record must have at least as many fields as the query.
+        /// Seek cannot be used with multi-table queries. This mode cannot be used with a view containing joins.
+        Seek = u32::MAX,
+
+        /// Refreshes the information in the record. Must first call [`View::next()`] with the same record.
+        /// Fails for a deleted row. Works with read-write and read-only records.
+        Refresh = 0,
+
+        /// Inserts a record. Fails if a row with the same primary keys exists. Fails with a read-only database.
+        /// This mode cannot be used with a view containing joins.
+        Insert = 1,
+
+        /// Updates an existing record. Non-primary keys only. Must first call [`View::next()`].
+        /// Fails with a deleted record. Works only with read-write records.
+        Update = 2,
+
+        /// Writes current data in the cursor to a table row. Updates record if the primary keys match an existing row and inserts if they do not match.
+        /// Fails with a read-only database. This mode cannot be used with a view containing joins.
+        Assign = 3,
+
+        /// Updates or deletes and inserts a record into a table. Must first call [`View::next()`] with the same record.
+        /// Updates record if the primary keys are unchanged. Deletes old row and inserts new if primary keys have changed. Fails with a read-only database.
+        /// This mode cannot be used with a view containing joins.
+        Replace = 4,
+
+        /// Inserts or validates a record in a table. Inserts if primary keys do not match any row and validates if there is a match.
+        /// Fails if the record does not match the data in the table. Fails if there is a record with a duplicate key that is not identical. Works only with read-write records.
+        /// This mode cannot be used with a view containing joins.
+        Merge = 5,
+
+        /// Remove a row from the table. You must first call the [`View::next()`] function with the same record.
+        /// Fails if the row has been deleted. Works only with read-write records. This mode cannot be used with a view containing joins.
+        Delete = 6,
+
+        /// Inserts a temporary record. The information is not persistent. Fails if a row with the same primary key exists.
+        /// Works only with read-write records. This mode cannot be used with a view containing joins.
+        InsertTemporary = 7,
+    }
+}
+
+repr_enum! {
+    /// Kinds of column information returned by [`View::column_info()`].
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    pub enum ColumnInfo: u32 {
+        /// Column names.
+        Names = 0,
+
+        /// Column definitions e.g., `s72` for a string of up to 72 characters, `i2` for a short integer, or `v0` for a stream.
+        ///
+        /// An uppercase type denotes a nullable column.
+        Types = 1,
+    }
 }
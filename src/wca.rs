@@ -0,0 +1,273 @@
+// Copyright 2024 Heath Stewart.
+// Licensed under the MIT License. See LICENSE.txt in the project root for license information.
+
+//! Helpers that mirror the WiX `wcautil` library.
+//!
+//! These functions make it easier to port existing C++ custom actions written
+//! against `wcautil` to Rust with a near-mechanical translation e.g.,
+//! `WcaGetIntProperty(L"ALLUSERS", &iAllUsers)` becomes `wca::get_int_property(&session, "ALLUSERS")?`.
+//!
+//! Functions that do not format strings accept any [`SessionOps`], so they can be tested with a `MockSession`
+//! from the `testing` module.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use msica::prelude::*;
+//! use msica::wca::{self, LogLevel};
+//! const ERROR_SUCCESS: u32 = 0;
+//! const ERROR_INSTALL_FAILURE: u32 = 1603;
+//!
+//! #[no_mangle]
+//! pub extern "C" fn MyCustomAction(session: Session) -> u32 {
+//!     match wca::get_property(&session, "INSTALLDIR") {
+//!         Ok(dir) => {
+//!             wca::log(&session, LogLevel::Standard, &format!("installing to {dir}"));
+//!             ERROR_SUCCESS
+//!         }
+//!         Err(_) => ERROR_INSTALL_FAILURE,
+//!     }
+//! }
+//! ```
+
+use crate::{
+    codes, DatabaseOps, Error, ErrorKind, Field, MessageType, ModifyMode, Record, Result, Session,
+    SessionOps, ViewOps,
+};
+use std::fmt::Display;
+
+/// Log levels passed to [`log()`]. Mirrors `LOGMSG` in `wcautil`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LogLevel {
+    /// Always written to the log.
+    Standard,
+
+    /// Only written to the log when verbose logging is enabled. See [`is_verbose()`].
+    Verbose,
+}
+
+/// Writes a message to the installation log. Mirrors `WcaLog`.
+///
+/// Square brackets in the message are not formatted.
+pub fn log<S: SessionOps>(session: &S, level: LogLevel, message: &str) {
+    if level == LogLevel::Verbose && !is_verbose(session) {
+        return;
+    }
+
+    // Pass text as a field so that square brackets are not formatted.
+    if let Ok(record) =
+        Record::with_fields(Some("[1]"), vec![Field::StringData(message.to_owned())])
+    {
+        session.message(MessageType::Info, &record);
+    }
+}
+
+/// Gets whether verbose logging is enabled. Mirrors `WcaIsVerbose`.
+///
+/// Verbose logging is enabled if the `LOGVERBOSE` property is set,
+/// or the `MsiLogging` property contains `v` or `x`.
+pub fn is_verbose<S: SessionOps>(session: &S) -> bool {
+    if is_property_set(session, "LOGVERBOSE") {
+        return true;
+    }
+
+    session
+        .property("MsiLogging")
+        .map(|modes| modes.contains(['v', 'V', 'x', 'X']))
+        .unwrap_or(false)
+}
+
+/// Gets the value of the named property, or an empty string if undefined. Mirrors `WcaGetProperty`.
+pub fn get_property<S: SessionOps>(session: &S, name: &str) -> Result<String> {
+    session.property(name)
+}
+
+/// Gets the value of the named property after formatting it. Mirrors `WcaGetFormattedProperty`.
+pub fn get_formatted_property(session: &Session, name: &str) -> Result<String> {
+    let value = session.property(name)?;
+    get_formatted_string(session, &value)
+}
+
+/// Formats a string containing property references e.g., `[INSTALLDIR]`. Mirrors `WcaGetFormattedString`.
+pub fn get_formatted_string(session: &Session, value: &str) -> Result<String> {
+    let record = Record::try_from(value)?;
+    session.format_record(&record)
+}
+
+/// Gets the integer value of the named property. Mirrors `WcaGetIntProperty`.
+///
+/// Returns `None` if the property is undefined.
+pub fn get_int_property<S: SessionOps>(session: &S, name: &str) -> Result<Option<i32>> {
+    let value = session.property(name)?;
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    value
+        .trim()
+        .parse::<i32>()
+        .map(Some)
+        .map_err(|err| Error::new(ErrorKind::DataConversion, err))
+}
+
+/// Sets the value of the named property. Mirrors `WcaSetProperty`.
+pub fn set_property<S: SessionOps>(session: &S, name: &str, value: &str) -> Result<()> {
+    session.set_property(name, Some(value))
+}
+
+/// Sets the named property to an integer value. Mirrors `WcaSetIntProperty`.
+pub fn set_int_property<S: SessionOps>(session: &S, name: &str, value: i32) -> Result<()> {
+    session.set_property(name, Some(&value.to_string()))
+}
+
+/// Gets whether the named property is set to a non-empty value. Mirrors `WcaIsPropertySet`.
+pub fn is_property_set<S: SessionOps>(session: &S, name: &str) -> bool {
+    session
+        .property(name)
+        .map(|value| !value.is_empty())
+        .unwrap_or(false)
+}
+
+/// Inserts a temporary row into the named table of the active database. Mirrors `WcaAddTempRecord`.
+///
+/// Fields must be specified in the order of the table's columns.
+pub fn add_temp_record<S: SessionOps>(session: &S, table: &str, fields: Vec<Field>) -> Result<()> {
+    let database = session.database();
+    let view = database.open_view(&format!("SELECT * FROM `{}`", table))?;
+    let record = Record::with_fields(None, fields)?;
+    view.modify(ModifyMode::InsertTemporary, &record)
+}
+
+/// Sets custom action data and schedules a deferred custom action,
+/// adding `cost` ticks to the progress bar. Mirrors `WcaDoDeferredAction`.
+pub fn do_deferred_action<S: SessionOps>(
+    session: &S,
+    action: &str,
+    custom_action_data: impl Display,
+    cost: i32,
+) -> Result<()> {
+    session.do_deferred_action(action, custom_action_data)?;
+    if cost > 0 {
+        progress_message(session, cost, true)?;
+    }
+
+    Ok(())
+}
+
+/// Increments the progress bar by `ticks`, or extends the total number of ticks
+/// if `extend` is `true`. Mirrors `WcaProgressMessage`.
+///
+/// Returns an error with [`ErrorKind::ErrorCode`] containing `ERROR_INSTALL_USEREXIT` if the user canceled.
+pub fn progress_message<S: SessionOps>(session: &S, ticks: i32, extend: bool) -> Result<()> {
+    let record = Record::with_fields(
+        None,
+        vec![
+            Field::IntegerData(if extend { 3 } else { 2 }),
+            Field::IntegerData(ticks),
+            Field::IntegerData(0),
+        ],
+    )?;
+
+    match session.message(MessageType::Progress, &record) {
+        IDCANCEL => Err(Error::from_error_code(codes::ERROR_INSTALL_USEREXIT)),
+        _ => Ok(()),
+    }
+}
+
+const IDCANCEL: i32 = 2;
+
+#[cfg(all(test, feature = "test-util"))]
+mod tests {
+    use super::*;
+    use crate::testing::{CapturedMessage, MockDatabase, MockSession};
+
+    fn texts(session: &MockSession) -> Vec<String> {
+        session
+            .messages()
+            .into_iter()
+            .map(|message| match message.fields.as_slice() {
+                [Field::StringData(text)] => text.clone(),
+                _ => message.text,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn log_levels() {
+        let session = MockSession::new();
+        log(&session, LogLevel::Standard, "standard [INSTALLDIR]");
+        log(&session, LogLevel::Verbose, "verbose");
+        assert_eq!(vec!["standard [INSTALLDIR]"], texts(&session));
+        assert_eq!(MessageType::Info, session.messages()[0].kind);
+
+        for (name, value) in [("LOGVERBOSE", "1"), ("MsiLogging", "voicewarmupx")] {
+            let session = MockSession::new().with_property(name, value);
+            assert!(is_verbose(&session));
+            log(&session, LogLevel::Verbose, "verbose");
+            assert_eq!(vec!["verbose"], texts(&session));
+        }
+
+        assert!(!is_verbose(
+            &MockSession::new().with_property("MsiLogging", "iwe")
+        ));
+    }
+
+    #[test]
+    fn int_property() -> Result<()> {
+        let session = MockSession::new().with_property("EMPTY", "");
+        assert_eq!(None, get_int_property(&session, "EMPTY")?);
+
+        set_int_property(&session, "ALLUSERS", 1)?;
+        assert_eq!(Some(1), get_int_property(&session, "ALLUSERS")?);
+        assert!(is_property_set(&session, "ALLUSERS"));
+
+        set_property(&session, "ALLUSERS", "x")?;
+        let error = get_int_property(&session, "ALLUSERS").unwrap_err();
+        assert_eq!(&ErrorKind::DataConversion, error.kind());
+        Ok(())
+    }
+
+    #[test]
+    fn add_temp_record_fields() -> Result<()> {
+        let database = MockDatabase::new();
+        database.create_table("Property", &["Property", "Value"]);
+        let session = MockSession::new().with_database(database.clone());
+
+        add_temp_record(
+            &session,
+            "Property",
+            vec![
+                Field::StringData("TEMP".to_owned()),
+                Field::StringData("1".to_owned()),
+            ],
+        )?;
+        crate::assert_table_eq!(database, "Property", [["TEMP", "1"]]);
+
+        assert!(add_temp_record(&session, "Missing", vec![Field::Null]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn do_deferred_action_cost() -> Result<()> {
+        let session = MockSession::new();
+        do_deferred_action(&session, "Deferred", "data", 0)?;
+        assert!(session.messages().is_empty());
+
+        do_deferred_action(&session, "Deferred", 42, 100)?;
+        assert_eq!("42", session.property("Deferred")?);
+        assert_eq!(vec!["Deferred", "Deferred"], session.actions());
+        assert_eq!(
+            vec![CapturedMessage {
+                kind: MessageType::Progress,
+                text: String::new(),
+                fields: vec![
+                    Field::IntegerData(3),
+                    Field::IntegerData(100),
+                    Field::IntegerData(0),
+                ],
+            }],
+            session.messages()
+        );
+        Ok(())
+    }
+}